@@ -0,0 +1,96 @@
+CREATE TABLE IF NOT EXISTS threads (
+    id serial PRIMARY KEY,
+    user_id BIGINT NOT NULL,
+    guild_id BIGINT NOT NULL,
+    channel_id BIGINT NOT NULL,
+    category varchar(100) NULL,
+    thread_name varchar(100) NULL,
+    note varchar(200) NULL
+);
+
+ALTER TABLE threads ADD COLUMN IF NOT EXISTS thread_name varchar(100) NULL;
+ALTER TABLE threads ADD COLUMN IF NOT EXISTS note varchar(200) NULL;
+
+DELETE FROM threads a USING threads b
+WHERE a.id > b.id AND a.guild_id = b.guild_id AND a.user_id = b.user_id AND a.channel_id = b.channel_id;
+
+CREATE UNIQUE INDEX IF NOT EXISTS threads_guild_user_channel ON threads (guild_id, user_id, channel_id);
+
+CREATE TABLE IF NOT EXISTS watchers (
+    id serial PRIMARY KEY,
+    user_id BIGINT NOT NULL,
+    guild_id BIGINT NOT NULL,
+    channel_id BIGINT NOT NULL,
+    message_id BIGINT NOT NULL,
+    categories varchar(200) NULL
+);
+
+CREATE TABLE IF NOT EXISTS muses (
+    id serial PRIMARY KEY,
+    user_id BIGINT NOT NULL,
+    guild_id BIGINT NOT NULL,
+    muse_name varchar(100) NOT NULL
+);
+
+CREATE TABLE IF NOT EXISTS todos (
+    id serial PRIMARY KEY,
+    user_id BIGINT NOT NULL,
+    guild_id BIGINT NOT NULL,
+    content varchar(300) NOT NULL,
+    category varchar(100) NULL
+);
+
+DELETE FROM muses a USING muses b
+WHERE a.id > b.id AND a.guild_id = b.guild_id AND a.user_id = b.user_id AND lower(a.muse_name) = lower(b.muse_name);
+
+CREATE UNIQUE INDEX IF NOT EXISTS muses_guild_user_name ON muses (guild_id, user_id, lower(muse_name));
+
+DELETE FROM todos a USING todos b
+WHERE a.id > b.id AND a.guild_id = b.guild_id AND a.user_id = b.user_id AND lower(a.content) = lower(b.content);
+
+CREATE UNIQUE INDEX IF NOT EXISTS todos_guild_user_content ON todos (guild_id, user_id, lower(content));
+
+CREATE TABLE IF NOT EXISTS subscriptions (
+    id serial PRIMARY KEY,
+    user_id BIGINT NOT NULL
+);
+
+CREATE TABLE IF NOT EXISTS user_settings (
+    id serial PRIMARY KEY,
+    user_id BIGINT NOT NULL,
+    name varchar(300) NOT NULL,
+    value varchar(300) NOT NULL
+);
+
+CREATE TABLE IF NOT EXISTS scheduled_messages (
+    id serial PRIMARY KEY,
+    user_id BIGINT NOT NULL,
+    channel_id BIGINT NOT NULL,
+    datetime varchar(60) NOT NULL,
+    repeat varchar(60) NULL,
+    title varchar(300) NOT NULL,
+    message varchar(2000) NOT NULL,
+    archived BOOLEAN NOT NULL
+);
+
+CREATE TABLE IF NOT EXISTS queued_notifications (
+    id serial PRIMARY KEY,
+    user_id BIGINT NOT NULL,
+    content varchar(2000) NOT NULL,
+    preview_title varchar(300) NULL,
+    preview varchar(4096) NULL
+);
+
+CREATE TABLE IF NOT EXISTS guild_settings (
+    id serial PRIMARY KEY,
+    guild_id BIGINT NOT NULL,
+    name varchar(300) NOT NULL,
+    value varchar(300) NOT NULL
+);
+
+CREATE TABLE IF NOT EXISTS server_nicknames (
+    id serial PRIMARY KEY,
+    user_id BIGINT NOT NULL,
+    guild_id BIGINT NOT NULL,
+    nickname varchar(100) NOT NULL
+);
@@ -0,0 +1 @@
+ALTER TABLE threads ADD COLUMN IF NOT EXISTS deleted_at TIMESTAMPTZ NULL;
@@ -0,0 +1,3 @@
+ALTER TABLE watchers ADD COLUMN IF NOT EXISTS notify BOOLEAN NOT NULL DEFAULT FALSE;
+ALTER TABLE watchers ADD COLUMN IF NOT EXISTS content_hash BIGINT NULL;
+ALTER TABLE watchers ADD COLUMN IF NOT EXISTS pending_count INTEGER NULL;
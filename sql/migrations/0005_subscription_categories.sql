@@ -0,0 +1,7 @@
+CREATE TABLE IF NOT EXISTS subscription_categories (
+    id serial PRIMARY KEY,
+    user_id BIGINT NOT NULL,
+    category varchar(100) NOT NULL
+);
+
+CREATE UNIQUE INDEX IF NOT EXISTS subscription_categories_user_category ON subscription_categories (user_id, lower(category));
@@ -0,0 +1,2 @@
+ALTER TABLE queued_notifications ADD COLUMN IF NOT EXISTS guild_id BIGINT NULL;
+ALTER TABLE queued_notifications ADD COLUMN IF NOT EXISTS channel_id BIGINT NULL;
@@ -0,0 +1 @@
+ALTER TABLE watchers ADD COLUMN IF NOT EXISTS paused BOOLEAN NOT NULL DEFAULT FALSE;
@@ -0,0 +1 @@
+ALTER TABLE todos ADD COLUMN IF NOT EXISTS channel_id BIGINT NULL;
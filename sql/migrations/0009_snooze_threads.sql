@@ -0,0 +1 @@
+ALTER TABLE threads ADD COLUMN IF NOT EXISTS snoozed_until TIMESTAMPTZ NULL;
@@ -0,0 +1,6 @@
+CREATE TABLE IF NOT EXISTS thread_views (
+    user_id BIGINT NOT NULL,
+    channel_id BIGINT NOT NULL,
+    last_seen TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (user_id, channel_id)
+);
@@ -0,0 +1,2 @@
+ALTER TABLE watchers ADD COLUMN IF NOT EXISTS last_updated TIMESTAMPTZ NULL;
+ALTER TABLE watchers ADD COLUMN IF NOT EXISTS last_error TEXT NULL;
@@ -0,0 +1,9 @@
+CREATE TABLE IF NOT EXISTS category_order (
+    id serial PRIMARY KEY,
+    guild_id BIGINT NOT NULL,
+    user_id BIGINT NOT NULL,
+    category varchar(100) NOT NULL,
+    sort_order INT NOT NULL
+);
+
+CREATE UNIQUE INDEX IF NOT EXISTS category_order_guild_user_category ON category_order (guild_id, user_id, lower(category));
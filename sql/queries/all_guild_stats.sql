@@ -0,0 +1,20 @@
+SELECT
+	guild_users.guild_id,
+	COUNT(DISTINCT guild_users.user_id) AS users,
+	(SELECT COUNT(DISTINCT channel_id) FROM threads WHERE threads.guild_id = guild_users.guild_id AND deleted_at IS NULL) AS threads_distinct,
+	(SELECT COUNT(*) FROM threads WHERE threads.guild_id = guild_users.guild_id AND deleted_at IS NULL) AS threads_total,
+	(SELECT COUNT(*) FROM muses WHERE muses.guild_id = guild_users.guild_id) AS muses,
+	(SELECT COUNT(*) FROM todos WHERE todos.guild_id = guild_users.guild_id) AS todos,
+	(SELECT COUNT(*) FROM watchers WHERE watchers.guild_id = guild_users.guild_id) AS watchers
+FROM (
+	SELECT user_id, guild_id FROM muses
+	UNION
+	SELECT user_id, guild_id FROM threads WHERE deleted_at IS NULL
+	UNION
+	SELECT user_id, guild_id FROM todos
+	UNION
+	SELECT user_id, guild_id FROM watchers
+) AS guild_users
+WHERE guild_users.guild_id <> 0 -- muses available in every server are stored with a guild ID of zero
+GROUP BY guild_users.guild_id
+ORDER BY guild_users.guild_id;
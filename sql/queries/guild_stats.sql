@@ -0,0 +1,16 @@
+SELECT
+	COUNT(DISTINCT user_id) AS users,
+	(SELECT COUNT(DISTINCT channel_id) FROM threads WHERE guild_id = $1 AND deleted_at IS NULL) AS threads_distinct,
+	(SELECT COUNT(*) FROM threads WHERE guild_id = $1 AND deleted_at IS NULL) AS threads_total,
+	(SELECT COUNT(*) FROM muses WHERE guild_id = $1) AS muses,
+	(SELECT COUNT(*) FROM todos WHERE guild_id = $1) AS todos,
+	(SELECT COUNT(*) FROM watchers WHERE guild_id = $1) AS watchers
+FROM (
+	SELECT user_id FROM muses WHERE guild_id = $1
+	UNION
+	SELECT user_id FROM threads WHERE guild_id = $1 AND deleted_at IS NULL
+	UNION
+	SELECT user_id FROM todos WHERE guild_id = $1
+	UNION
+	SELECT user_id FROM watchers WHERE guild_id = $1
+) AS guild_users;
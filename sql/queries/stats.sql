@@ -1,8 +1,8 @@
 SELECT
 	COUNT(DISTINCT user_id) AS users,
 	COUNT(DISTINCT guild_id) - 1 AS servers, -- -1 corrects for the zero-values added in the union below
-	(SELECT COUNT(DISTINCT channel_id) FROM threads) AS threads_distinct,
-    (SELECT COUNT(*) FROM threads) AS threads_total,
+	(SELECT COUNT(DISTINCT channel_id) FROM threads WHERE deleted_at IS NULL) AS threads_distinct,
+    (SELECT COUNT(*) FROM threads WHERE deleted_at IS NULL) AS threads_total,
 	(SELECT COUNT(*) FROM muses) AS muses,
 	(SELECT COUNT(*) FROM todos) AS todos,
 	(SELECT COUNT(*) FROM watchers) AS watchers,
@@ -10,7 +10,7 @@ SELECT
 FROM (
 	SELECT user_id, guild_id FROM muses
 	UNION
-	SELECT user_id, guild_id FROM threads
+	SELECT user_id, guild_id FROM threads WHERE deleted_at IS NULL
 	UNION
 	SELECT user_id, guild_id FROM todos
 	UNION
@@ -6,16 +6,20 @@ use std::{
 
 use serenity::{gateway::ActivityData, model::prelude::*, prelude::*};
 use tokio::{
-    sync::mpsc::{Receiver, Sender},
-    task::JoinSet,
+    sync::{
+        mpsc::{Receiver, Sender},
+        watch,
+        Semaphore,
+    },
+    task::{JoinError, JoinHandle, JoinSet},
 };
 use tracing::{error, info};
 
 use crate::{
-    cache::MessageCache,
+    cache::{ActiveThreadsCache, MessageCache, UnavailableChannelCache},
     commands::{
         scheduling::send_scheduled_messages,
-        threads::send_reply_notification,
+        threads::{send_queued_notifications, send_reply_notification, send_weekly_digests},
         watchers,
     },
     consts::*,
@@ -27,7 +31,7 @@ use crate::{
 #[derive(Clone)]
 pub(crate) enum Task {
     /// Handle notifications for new thread replies, if any are needed.
-    Notify(Message),
+    Notify(Box<Message>),
     /// Update discord status and ensure it is set to online for the given shard context.
     Heartbeat(Arc<Context>),
     /// Kick off a watcher update thread.
@@ -36,64 +40,152 @@ pub(crate) enum Task {
     PurgeCache,
     /// Send any scheduled messages at the proper time(s)
     SendScheduledMessages,
+    /// Send any reply notifications held back during users' quiet hours
+    SendQueuedNotifications,
+    /// Permanently delete untracked threads once they can no longer be restored
+    PurgeDeletedThreads,
+    /// Send weekly summaries of pending threads to users who have opted in, once they're due
+    SendWeeklyDigests,
 }
 
 /// Start a new thread which listens for `Task` messages and running the appropriate actions for each task.
+/// Once `shutdown` is signalled, no new tasks are accepted and the thread exits after running the queued ones and
+/// waiting for any work they started in the background.
 pub(crate) fn listen_for_background_tasks(
     mut receiver: Receiver<Task>,
     data: Arc<RwLock<Data>>,
     context: Arc<impl CacheHttp + 'static>,
-) {
+    mut shutdown: watch::Receiver<bool>,
+) -> JoinHandle<()> {
     use Task::*;
 
     info!("Starting background task listening thread");
 
+    let shared_data = data.clone();
     tokio::spawn(async move {
         let data = data.read().await;
         let database = &data.database;
         let cache = &data.message_cache;
+        let active_threads_cache = &data.active_threads_cache;
+        let unavailable_channels = &data.unavailable_channels;
+        let mut running = JoinSet::new();
+        let mut closed = false;
+
+        loop {
+            let task = tokio::select! {
+                task = receiver.recv() => task,
+                Some(result) = running.join_next(), if !running.is_empty() => {
+                    log_task_result(result);
+                    continue;
+                },
+                _ = shutdown.changed(), if !closed => {
+                    info!("No longer accepting background tasks; finishing queued tasks");
+                    receiver.close();
+                    closed = true;
+                    continue;
+                },
+            };
+
+            let Some(task) = task else {
+                break;
+            };
 
-        while let Some(task) = receiver.recv().await {
             match task {
                 Notify(message) => {
-                    send_reply_notification(message, database.clone(), context.clone()).await
+                    send_reply_notification(*message, database.clone(), context.clone()).await
                 },
                 Heartbeat(context) => heartbeat(&context).await,
-                UpdateWatchers => {
-                    start_watcher_update_thread(context.clone(), database.clone(), cache.clone())
+                UpdateWatchers => start_watcher_update_thread(&mut running, &data, context.clone()),
+                PurgeCache => {
+                    purge_expired_cache_entries(Arc::new(cache.clone())).await;
+                    active_threads_cache.purge_expired().await;
+                    unavailable_channels.purge_expired().await;
                 },
-                PurgeCache => purge_expired_cache_entries(Arc::new(cache.clone())).await,
                 SendScheduledMessages => {
-                    start_scheduled_messages_thread(database.clone(), context.clone()).await
+                    start_scheduled_messages_thread(&mut running, database.clone(), context.clone())
+                },
+                SendQueuedNotifications => {
+                    start_queued_notifications_thread(&mut running, database.clone(), context.clone())
+                },
+                PurgeDeletedThreads => purge_deleted_threads(database).await,
+                SendWeeklyDigests => {
+                    start_weekly_digest_thread(&mut running, shared_data.clone(), context.clone())
                 },
             };
         }
-    });
+
+        if !running.is_empty() {
+            info!("Waiting for {} running background tasks to finish", running.len());
+        }
+
+        while let Some(result) = running.join_next().await {
+            log_task_result(result);
+        }
+
+        info!("Background task listening thread stopped");
+    })
+}
+
+/// Log the outcome of background work which failed to run to completion.
+fn log_task_result(result: Result<(), JoinError>) {
+    if let Err(e) = result {
+        error!("Background task did not complete: {}", e);
+    }
 }
 
 /// Core task spawning function for per-shard tasks.
-pub(crate) fn run_periodic_shard_tasks(context: &Context, sender: &Sender<Task>) {
+pub(crate) fn run_periodic_shard_tasks(
+    context: &Context,
+    sender: &Sender<Task>,
+    shutdown: &watch::Receiver<bool>,
+) {
     info!("Starting periodic per-shard tasks");
     let c = Arc::new(context.clone());
-    spawn_task_loop(sender.clone(), HEARTBEAT_INTERVAL, false, move || Task::Heartbeat(c.clone()));
+    spawn_task_loop(sender.clone(), shutdown.clone(), HEARTBEAT_INTERVAL, false, move || {
+        Task::Heartbeat(c.clone())
+    });
 }
 
 /// Core task spawning function. Creates a set of periodically recurring tasks on their own threads.
-pub(crate) fn start_periodic_tasks(sender: &Sender<Task>) {
+pub(crate) fn start_periodic_tasks(
+    sender: &Sender<Task>,
+    shutdown: &watch::Receiver<bool>,
+    cache_trim_interval: Duration,
+) {
     info!("Starting periodic global tasks");
-    spawn_task_loop(sender.clone(), CACHE_TRIM_INTERVAL, true, || Task::PurgeCache);
-    spawn_task_loop(sender.clone(), WATCHER_UPDATE_INTERVAL, true, || Task::UpdateWatchers);
-    spawn_task_loop(sender.clone(), SCHEDULED_MESSAGE_INTERVAL, true, || {
+    spawn_task_loop(sender.clone(), shutdown.clone(), cache_trim_interval, true, || Task::PurgeCache);
+    spawn_task_loop(sender.clone(), shutdown.clone(), WATCHER_UPDATE_INTERVAL, true, || {
+        Task::UpdateWatchers
+    });
+    spawn_task_loop(sender.clone(), shutdown.clone(), SCHEDULED_MESSAGE_INTERVAL, true, || {
         Task::SendScheduledMessages
     });
+    spawn_task_loop(sender.clone(), shutdown.clone(), QUEUED_NOTIFICATION_INTERVAL, true, || {
+        Task::SendQueuedNotifications
+    });
+    spawn_task_loop(sender.clone(), shutdown.clone(), DELETED_THREAD_PURGE_INTERVAL, true, || {
+        Task::PurgeDeletedThreads
+    });
+    spawn_task_loop(sender.clone(), shutdown.clone(), WEEKLY_DIGEST_INTERVAL, true, || {
+        Task::SendWeeklyDigests
+    });
 }
 
-/// Spawns a task which loops indefinitely, with a wait period between each iteration.
-fn spawn_task_loop<F>(sender: Sender<Task>, period: Duration, delay: bool, mut task: F)
-where
+/// Spawns a task which loops until `shutdown` is signalled, with a wait period between each iteration.
+fn spawn_task_loop<F>(
+    sender: Sender<Task>,
+    mut shutdown: watch::Receiver<bool>,
+    period: Duration,
+    delay: bool,
+    mut task: F,
+) where
     F: FnMut() -> Task + Send + 'static,
 {
     tokio::spawn(async move {
+        if *shutdown.borrow() {
+            return;
+        }
+
         let mut interval = tokio::time::interval(period);
 
         if delay {
@@ -102,8 +194,16 @@ where
         }
 
         loop {
-            interval.tick().await;
+            tokio::select! {
+                _ = interval.tick() => {},
+                _ = shutdown.changed() => break,
+            }
+
             if let Err(e) = sender.send(task()).await {
+                if sender.is_closed() {
+                    break;
+                }
+
                 error!("Error creating background task: {}", e);
             }
         }
@@ -119,29 +219,50 @@ pub(crate) async fn heartbeat(ctx: &Context) {
     info!("heartbeat set_presence request completed for shard ID {}", ctx.shard_id);
 }
 
-fn start_watcher_update_thread(
-    context: Arc<impl CacheHttp + 'static>,
-    database: Database,
-    cache: MessageCache,
-) {
-    tokio::spawn(async move {
-        if let Err(e) = update_watchers(context, database, cache).await {
+/// Start a background thread to update every watcher.
+fn start_watcher_update_thread(running: &mut JoinSet<()>, data: &Data, context: Arc<impl CacheHttp + 'static>) {
+    let database = data.database.clone();
+    let cache = data.message_cache.clone();
+    let active_threads_cache = data.active_threads_cache.clone();
+    let unavailable_channels = data.unavailable_channels.clone();
+    let max_tasks = data.max_watcher_update_tasks;
+    let permits = data.watcher_update_permits.clone();
+
+    running.spawn(async move {
+        let result = update_watchers(
+            context,
+            database,
+            cache,
+            active_threads_cache,
+            unavailable_channels,
+            max_tasks,
+            permits,
+        )
+        .await;
+        if let Err(e) = result {
             error!("Error updating watchers: {}", e);
         }
     });
 }
 
 /// Updates all recorded watchers and edits their referenced messages with the new content.
+/// Watchers are split between up to `max_tasks` tasks, and each update waits for one of the shared `permits`.
+/// There are as many permits as tasks, so a single run never waits on them; they only stop overlapping runs, such as
+/// a slow run still going when the next one starts, from editing more messages at once between them.
 pub(crate) async fn update_watchers(
     cache_http: Arc<impl CacheHttp + 'static>,
     database: Database,
     message_cache: MessageCache,
+    active_threads_cache: ActiveThreadsCache,
+    unavailable_channels: UnavailableChannelCache,
+    max_tasks: usize,
+    permits: Arc<Semaphore>,
 ) -> anyhow::Result<()> {
     let task_start = Instant::now();
     info!("Watcher update loop started");
 
     let mut stagger_interval = tokio::time::interval(Duration::from_millis(100));
-    let batches = get_watcher_batches(&database).await?;
+    let batches = get_watcher_batches(&database, max_tasks).await?;
     let context = Arc::clone(&cache_http);
 
     let mut tasks = JoinSet::new();
@@ -150,14 +271,28 @@ pub(crate) async fn update_watchers(
         let database = database.clone();
         let ctx = Arc::clone(&context);
         let message_cache = message_cache.clone();
+        let active_threads_cache = active_threads_cache.clone();
+        let unavailable_channels = unavailable_channels.clone();
+        let permits = Arc::clone(&permits);
         tasks.spawn(async move {
             for watcher in watcher_batch {
+                let Ok(_permit) = permits.acquire().await else {
+                    break;
+                };
+
                 let id = watcher.id;
-                let result =
-                    watchers::update_watched_message(watcher, &ctx, &database, &message_cache)
-                        .await;
+                let result = watchers::update_watched_message(
+                    watcher,
+                    &ctx,
+                    &database,
+                    &message_cache,
+                    &active_threads_cache,
+                    &unavailable_channels,
+                )
+                .await;
                 if let Err(e) = result {
                     error!("error updating watcher {}: {}", id, e);
+                    watchers::record_status(&database, id, Some(&e.to_string())).await;
                 }
             }
         });
@@ -179,25 +314,40 @@ pub(crate) async fn update_watchers(
     Ok(())
 }
 
-/// Retrieves the list of watchers in the database, subdivided into batches of at least 10.
-async fn get_watcher_batches(database: &Database) -> sqlx::Result<Vec<Vec<ThreadWatcher>>> {
-    let list: Vec<ThreadWatcher> = db::list_watchers(database).await?;
-    let batch_size = cmp::min(MIN_WATCHER_BATCH_SIZE, list.len() / MAX_WATCHER_UPDATE_TASKS);
+/// Retrieves the list of watchers in the database that aren't paused, subdivided into batches by `into_batches`.
+async fn get_watcher_batches(database: &Database, max_batches: usize) -> sqlx::Result<Vec<Vec<ThreadWatcher>>> {
+    let list: Vec<ThreadWatcher> =
+        db::list_watchers(database).await?.into_iter().filter(|watcher| !watcher.paused).collect();
+
+    Ok(into_batches(list, max_batches))
+}
+
+/// Subdivide a list into at most `max_batches` batches of at least `MIN_WATCHER_BATCH_SIZE`, except for the last
+/// batch. A `max_batches` of 0 is treated as 1.
+fn into_batches<T>(list: Vec<T>, max_batches: usize) -> Vec<Vec<T>> {
+    let batch_size = batch_size(list.len(), max_batches);
 
     let mut result = Vec::new();
     let mut chunk = Vec::new();
-    for watcher in list {
+    for item in list {
         if chunk.len() >= batch_size {
             result.push(chunk);
             chunk = Vec::new();
         }
 
-        chunk.push(watcher);
+        chunk.push(item);
     }
 
-    result.push(chunk);
+    if !chunk.is_empty() {
+        result.push(chunk);
+    }
+
+    result
+}
 
-    Ok(result)
+/// The number of items in each batch when splitting `len` items into at most `max_batches` batches.
+fn batch_size(len: usize, max_batches: usize) -> usize {
+    cmp::max(MIN_WATCHER_BATCH_SIZE, len.div_ceil(max_batches.max(1)))
 }
 
 /// Purge any expired entries in the message cache.
@@ -207,13 +357,99 @@ async fn purge_expired_cache_entries(cache: Arc<MessageCache>) {
 }
 
 /// Start a background thread to handle sending scheduled messages.
-pub(crate) async fn start_scheduled_messages_thread(
+fn start_scheduled_messages_thread(
+    running: &mut JoinSet<()>,
     database: Database,
     ctx: Arc<impl CacheHttp + 'static>,
 ) {
-    tokio::spawn(async move {
+    running.spawn(async move {
         if let Err(e) = send_scheduled_messages(database, ctx).await {
             error!("Error sending scheduled messages: {}", e);
         }
     });
 }
+
+/// Start a background thread to send any queued reply notifications that are no longer held back.
+fn start_queued_notifications_thread(
+    running: &mut JoinSet<()>,
+    database: Database,
+    ctx: Arc<impl CacheHttp + 'static>,
+) {
+    running.spawn(async move {
+        if let Err(e) = send_queued_notifications(database, ctx).await {
+            error!("Error sending queued reply notifications: {}", e);
+        }
+    });
+}
+
+/// Start a background thread to send any weekly digests which are due.
+fn start_weekly_digest_thread(
+    running: &mut JoinSet<()>,
+    data: Arc<RwLock<Data>>,
+    ctx: Arc<impl CacheHttp + 'static>,
+) {
+    running.spawn(async move {
+        let data = data.read().await;
+        if let Err(e) = send_weekly_digests(&data, ctx).await {
+            error!("Error sending weekly digests: {}", e);
+        }
+    });
+}
+
+/// Permanently delete any untracked threads which are past the undo window, along with the record of when they were
+/// last seen.
+async fn purge_deleted_threads(database: &Database) {
+    match db::purge_deleted_threads(database, THREAD_UNDO_WINDOW).await {
+        Ok(0) => {},
+        Ok(count) => info!("purged {} untracked threads past the undo window", count),
+        Err(e) => error!("Error purging untracked threads: {}", e),
+    }
+
+    match db::purge_orphaned_thread_views(database).await {
+        Ok(0) => {},
+        Ok(count) => info!("purged {} views of threads which are no longer tracked", count),
+        Err(e) => error!("Error purging views of untracked threads: {}", e),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fewer_items_than_batches_makes_one_batch() {
+        let batches = into_batches((0..3).collect(), 8);
+        assert_eq!(batches, vec![vec![0, 1, 2]]);
+    }
+
+    #[test]
+    fn no_items_makes_no_batches() {
+        assert!(into_batches(Vec::<u32>::new(), 4).is_empty());
+        assert_eq!(batch_size(0, 4), MIN_WATCHER_BATCH_SIZE);
+    }
+
+    #[test]
+    fn zero_max_batches_makes_a_single_batch() {
+        let count = MIN_WATCHER_BATCH_SIZE * 3;
+        assert_eq!(batch_size(count, 0), count);
+        assert_eq!(into_batches((0..count).collect(), 0).len(), 1);
+    }
+
+    #[test]
+    fn items_are_split_between_max_batches() {
+        let count = MIN_WATCHER_BATCH_SIZE * 4;
+        let batches = into_batches((0..count).collect(), 4);
+
+        assert_eq!(batches.len(), 4);
+        assert!(batches.iter().all(|b| b.len() == MIN_WATCHER_BATCH_SIZE));
+    }
+
+    #[test]
+    fn batches_are_never_smaller_than_the_minimum_except_the_last() {
+        let count = MIN_WATCHER_BATCH_SIZE * 2 + 1;
+        let batches = into_batches((0..count).collect(), 10);
+
+        assert_eq!(batches.len(), 3);
+        assert_eq!(batches[2].len(), 1);
+    }
+}
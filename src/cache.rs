@@ -1,8 +1,11 @@
 use std::{
-    collections::HashMap,
+    collections::{BTreeMap, HashMap},
     future::Future,
     hash::Hash,
-    sync::Arc,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
     time::{Duration, Instant},
 };
 
@@ -13,8 +16,94 @@ use crate::{consts::CACHE_LIFETIME, utils::ChannelMessage};
 /// Specialised `MemoryCache` that stores received `Message` items.
 pub(crate) type MessageCache = MemoryCache<ChannelMessage, Message>;
 
-/// Type alias for a HashMap that only stores `Cached<T>` items.
-type CacheMap<TKey, TValue> = HashMap<TKey, Cached<TValue>>;
+/// Specialised `MemoryCache` that stores the active threads for each guild.
+pub(crate) type ActiveThreadsCache = MemoryCache<GuildId, Vec<GuildChannel>>;
+
+/// Specialised `MemoryCache` that stores the text command prefix for each guild.
+pub(crate) type GuildPrefixCache = MemoryCache<GuildId, String>;
+
+/// Specialised `MemoryCache` that stores the emojis which delete Titi's messages in each guild. An empty list means
+/// deleting messages by reaction is disabled.
+pub(crate) type DeleteEmojiCache = MemoryCache<GuildId, Vec<String>>;
+
+/// Specialised `MemoryCache` that records channels which recently couldn't be looked up, so that they aren't fetched
+/// again on every list render. Entries expire so the channels are retried once in a while.
+pub(crate) type UnavailableChannelCache = MemoryCache<ChannelId, ()>;
+
+/// The entries of a `MemoryCache`, along with the order they were stored in so the oldest can be evicted quickly.
+#[derive(Debug)]
+struct CacheMap<TKey, TValue> {
+    /// The cached entries.
+    entries: HashMap<TKey, Cached<TValue>>,
+    /// The key of each entry, by the sequence number it was stored with. The first key is the oldest entry.
+    order: BTreeMap<u64, TKey>,
+    /// The sequence number for the next entry stored.
+    next_sequence: u64,
+}
+
+impl<TKey, TValue> CacheMap<TKey, TValue>
+where
+    TKey: Eq + Hash + Clone,
+{
+    fn new() -> Self {
+        Self { entries: HashMap::new(), order: BTreeMap::new(), next_sequence: 0 }
+    }
+
+    fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    fn get(&self, key: &TKey) -> Option<&Cached<TValue>> {
+        self.entries.get(key)
+    }
+
+    fn contains_key(&self, key: &TKey) -> bool {
+        self.entries.contains_key(key)
+    }
+
+    /// Store an entry, replacing any existing entry with the same key. The entry counts as the newest one.
+    fn insert(&mut self, key: TKey, data: &Arc<TValue>) {
+        let sequence = self.next_sequence;
+        self.next_sequence += 1;
+
+        self.order.insert(sequence, key.clone());
+        if let Some(previous) = self.entries.insert(key, Cached::new(data, sequence)) {
+            self.order.remove(&previous.sequence);
+        }
+    }
+
+    fn remove(&mut self, key: &TKey) -> Option<Cached<TValue>> {
+        let removed = self.entries.remove(key)?;
+        self.order.remove(&removed.sequence);
+        Some(removed)
+    }
+
+    /// Remove the oldest entry, if there is one.
+    fn remove_oldest(&mut self) -> Option<Cached<TValue>> {
+        let (_, key) = self.order.pop_first()?;
+        self.entries.remove(&key)
+    }
+
+    /// Keep only the entries matching the predicate.
+    fn retain<F>(&mut self, mut predicate: F)
+    where
+        F: FnMut(&TKey, &Cached<TValue>) -> bool,
+    {
+        let order = &mut self.order;
+        self.entries.retain(|k, v| {
+            let keep = predicate(k, v);
+            if !keep {
+                order.remove(&v.sequence);
+            }
+
+            keep
+        });
+    }
+
+    fn shrink_to_fit(&mut self) {
+        self.entries.shrink_to_fit();
+    }
+}
 
 #[derive(Debug)]
 /// Wrapper struct for managing cached data.
@@ -23,12 +112,14 @@ struct Cached<T> {
     pub data: Arc<T>,
     /// The Instant when the data was cached.
     pub timestamp: Instant,
+    /// The position of this entry in the order entries were stored in.
+    pub sequence: u64,
 }
 
 impl<T> Cached<T> {
     /// Create a new cache item.
-    pub fn new(data: &Arc<T>) -> Self {
-        Self { data: Arc::clone(data), timestamp: Instant::now() }
+    pub fn new(data: &Arc<T>, sequence: u64) -> Self {
+        Self { data: Arc::clone(data), timestamp: Instant::now(), sequence }
     }
 
     /// Returns true if the cache entry is older than the defined maximum lifetime.
@@ -45,21 +136,53 @@ where
 {
     /// The internal storage of the cache, in a threadsafe wrapper.
     storage: Arc<RwLock<CacheMap<TKey, TData>>>,
+    /// The maximum number of entries to keep, if any. The oldest entries are evicted first.
+    max_entries: Option<usize>,
+    /// How long entries remain valid once cached.
+    lifetime: Duration,
+    /// The number of lookups which found an entry in the cache.
+    hits: Arc<AtomicU64>,
+    /// The number of lookups which did not find an entry in the cache.
+    misses: Arc<AtomicU64>,
 }
 
 impl<TKey, TData> MemoryCache<TKey, TData>
 where
     TKey: PartialEq + Eq + Hash + Clone,
 {
-    /// Create a new MemoryCache.
-    pub fn new() -> Self {
-        let storage = Arc::new(RwLock::new(HashMap::new()));
-        Self { storage }
+    /// Create a new MemoryCache. If `max_entries` is set, the oldest entries are evicted once the cache is full.
+    pub fn new(max_entries: Option<usize>) -> Self {
+        let storage = Arc::new(RwLock::new(CacheMap::new()));
+        Self {
+            storage,
+            max_entries,
+            lifetime: CACHE_LIFETIME,
+            hits: Arc::new(AtomicU64::new(0)),
+            misses: Arc::new(AtomicU64::new(0)),
+        }
+    }
+
+    /// Set how long entries remain valid once cached. Expired entries are treated as missing.
+    pub fn with_lifetime(mut self, lifetime: Duration) -> Self {
+        self.lifetime = lifetime;
+        self
     }
 
     /// Get an entry out of the cache.
     pub async fn get(&self, id: &TKey) -> Option<Arc<TData>> {
-        self.storage.read().await.get(id).map(|c| &c.data).cloned()
+        let result = self
+            .storage
+            .read()
+            .await
+            .get(id)
+            .filter(|c| !c.expired(self.lifetime))
+            .map(|c| &c.data)
+            .cloned();
+
+        let counter = if result.is_some() { &self.hits } else { &self.misses };
+        counter.fetch_add(1, Ordering::Relaxed);
+
+        result
     }
 
     /// Remove an entry from the cache.
@@ -67,6 +190,18 @@ where
         self.storage.write().await.remove(id).map(|c| c.data)
     }
 
+    /// Remove every entry whose key matches the given predicate, returning the number of entries removed.
+    pub async fn remove_where<F>(&self, predicate: F) -> usize
+    where
+        F: Fn(&TKey) -> bool,
+    {
+        let mut cache = self.storage.write().await;
+        let before = cache.len();
+        cache.retain(|k, _| !predicate(k));
+
+        before - cache.len()
+    }
+
     /// Check if the cache contains an entry with the specified key
     pub async fn contains_key(&self, id: &TKey) -> bool {
         self.storage.read().await.contains_key(id)
@@ -90,16 +225,82 @@ where
         let mut cache = self.storage.write().await;
 
         let value = Arc::new(value);
-        cache.insert(key, Cached::new(&value));
+        cache.insert(key, &value);
+
+        if let Some(max_entries) = self.max_entries {
+            while cache.len() > max_entries {
+                if cache.remove_oldest().is_none() {
+                    break;
+                }
+            }
+        }
 
         value
     }
 
+    /// Get the number of cache hits, cache misses, and the current number of entries in the cache.
+    pub async fn stats(&self) -> (u64, u64, usize) {
+        let len = self.storage.read().await.len();
+        (self.hits.load(Ordering::Relaxed), self.misses.load(Ordering::Relaxed), len)
+    }
+
     /// Remove any expired cache entries.
     pub async fn purge_expired(&self) {
         let mut cache = self.storage.write().await;
 
-        cache.retain(|_, v| !v.expired(CACHE_LIFETIME));
+        cache.retain(|_, v| !v.expired(self.lifetime));
         cache.shrink_to_fit();
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn store_evicts_oldest_entries_past_max_entries() {
+        let cache: MemoryCache<u32, u32> = MemoryCache::new(Some(3));
+
+        for key in 0..5 {
+            cache.store(key, key * 10).await;
+        }
+
+        let (_, _, len) = cache.stats().await;
+        assert_eq!(len, 3);
+        assert!(!cache.contains_key(&0).await);
+        assert!(!cache.contains_key(&1).await);
+        for key in 2..5 {
+            assert!(cache.contains_key(&key).await);
+        }
+    }
+
+    #[tokio::test]
+    async fn storing_an_existing_key_makes_it_the_newest() {
+        let cache: MemoryCache<u32, u32> = MemoryCache::new(Some(2));
+
+        cache.store(1, 1).await;
+        cache.store(2, 2).await;
+        cache.store(1, 10).await;
+        cache.store(3, 3).await;
+
+        assert!(cache.contains_key(&1).await);
+        assert!(!cache.contains_key(&2).await);
+        assert!(cache.contains_key(&3).await);
+        assert_eq!(cache.get(&1).await.as_deref(), Some(&10));
+    }
+
+    #[tokio::test]
+    async fn removed_entries_are_not_evicted_again() {
+        let cache: MemoryCache<u32, u32> = MemoryCache::new(Some(2));
+
+        cache.store(1, 1).await;
+        cache.store(2, 2).await;
+        cache.remove(&1).await;
+        cache.store(3, 3).await;
+
+        let (_, _, len) = cache.stats().await;
+        assert_eq!(len, 2);
+        assert!(cache.contains_key(&2).await);
+        assert!(cache.contains_key(&3).await);
+    }
+}
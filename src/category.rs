@@ -0,0 +1,175 @@
+use std::{
+    cmp::Ordering,
+    fmt::{self, Display},
+    hash::{Hash, Hasher},
+};
+
+use crate::{commands::CommandError, consts::UNCATEGORISED_KEYWORDS};
+
+/// The name of a category that threads and todo list entries can be filed under.
+///
+/// Names are trimmed and runs of whitespace are collapsed to a single space. Categories compare case-insensitively,
+/// but keep the casing they were entered with so they can be shown the way the user wrote them.
+#[derive(Debug, Clone)]
+pub(crate) struct Category(String);
+
+impl Category {
+    /// Normalise a category name. Returns `None` if the name is empty or is one of the keywords for "no category".
+    pub(crate) fn new(name: &str) -> Option<Self> {
+        let name = name.split_whitespace().collect::<Vec<_>>().join(" ");
+        if name.is_empty() || is_uncategorised(&name) {
+            None
+        }
+        else {
+            Some(Self(name))
+        }
+    }
+
+    /// Normalise an optional category given to a command. Leaving the category out or using one of the keywords for
+    /// "no category" gives `None`, but a name made up only of whitespace is rejected.
+    pub(crate) fn from_argument(name: Option<&str>) -> Result<Option<Self>, CommandError> {
+        match name {
+            Some(name) if name.trim().is_empty() => Err(CommandError::new("Category names can't be blank.")),
+            Some(name) => Ok(Self::new(name)),
+            None => Ok(None),
+        }
+    }
+
+    /// The category name, with its original casing.
+    pub(crate) fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    /// The form of the name used to compare categories.
+    fn key(&self) -> String {
+        self.0.to_lowercase()
+    }
+}
+
+impl Display for Category {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl AsRef<str> for Category {
+    fn as_ref(&self) -> &str {
+        &self.0
+    }
+}
+
+impl PartialEq for Category {
+    fn eq(&self, other: &Self) -> bool {
+        self.key() == other.key()
+    }
+}
+
+impl Eq for Category {}
+
+impl Hash for Category {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.key().hash(state);
+    }
+}
+
+impl PartialOrd for Category {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Category {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.key().cmp(&other.key())
+    }
+}
+
+/// Returns true if an entry with the given category is selected by a category filter. Filters compare
+/// case-insensitively, and the keywords for "no category" select entries without one.
+pub(crate) fn matches_filter(filter: &str, category: Option<&str>) -> bool {
+    if is_uncategorised(filter) {
+        category.is_none()
+    }
+    else {
+        category.is_some_and(|c| c.trim().to_lowercase() == filter.trim().to_lowercase())
+    }
+}
+
+/// Returns true if the category filter is one of the keywords that selects entries without a category.
+pub(crate) fn is_uncategorised(category: &str) -> bool {
+    UNCATEGORISED_KEYWORDS.iter().any(|k| k.eq_ignore_ascii_case(category.trim()))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::{BTreeSet, HashSet};
+
+    use super::*;
+
+    #[test]
+    fn new_trims_and_collapses_whitespace() {
+        let category = Category::new("  Main   Plot \t Threads ").expect("category should be created");
+        assert_eq!(category.as_str(), "Main Plot Threads");
+    }
+
+    #[test]
+    fn new_keeps_original_casing() {
+        assert_eq!(Category::new("Side Plots").map(|c| c.to_string()).as_deref(), Some("Side Plots"));
+    }
+
+    #[test]
+    fn new_returns_none_for_empty_names() {
+        assert!(Category::new("").is_none());
+        assert!(Category::new("   \t ").is_none());
+    }
+
+    #[test]
+    fn new_returns_none_for_uncategorised_keywords() {
+        for name in ["none", "None", " NONE ", "uncategorised", "Uncategorized"] {
+            assert!(Category::new(name).is_none(), "'{}' should mean no category", name);
+        }
+    }
+
+    #[test]
+    fn categories_compare_case_insensitively() {
+        let lower = Category::new("main").unwrap();
+        let upper = Category::new("MAIN").unwrap();
+        let spaced = Category::new("  Main ").unwrap();
+
+        assert_eq!(lower, upper);
+        assert_eq!(lower, spaced);
+        assert_ne!(lower, Category::new("mains").unwrap());
+    }
+
+    #[test]
+    fn categories_hash_case_insensitively() {
+        let set: HashSet<Category> = ["Main", "main", "MAIN", "Side"].iter().filter_map(|n| Category::new(n)).collect();
+        assert_eq!(set.len(), 2);
+    }
+
+    #[test]
+    fn categories_order_case_insensitively() {
+        let ordered: Vec<String> = ["beta", "Alpha", "GAMMA"]
+            .iter()
+            .filter_map(|n| Category::new(n))
+            .collect::<BTreeSet<_>>()
+            .into_iter()
+            .map(|c| c.to_string())
+            .collect();
+
+        assert_eq!(ordered, ["Alpha", "beta", "GAMMA"]);
+    }
+
+    #[test]
+    fn from_argument_rejects_blank_names() {
+        assert!(Category::from_argument(Some("")).is_err());
+        assert!(Category::from_argument(Some("   ")).is_err());
+    }
+
+    #[test]
+    fn from_argument_accepts_missing_and_uncategorised() {
+        assert!(matches!(Category::from_argument(None), Ok(None)));
+        assert!(matches!(Category::from_argument(Some("none")), Ok(None)));
+        assert!(matches!(Category::from_argument(Some(" Main ")), Ok(Some(c)) if c.as_str() == "Main"));
+    }
+}
@@ -1,7 +1,10 @@
+pub(crate) mod bugs;
 pub(crate) mod greetings;
 pub(crate) mod help;
 pub(crate) mod muses;
 pub(crate) mod scheduling;
+pub(crate) mod search;
+pub(crate) mod server;
 pub(crate) mod stats;
 pub(crate) mod threads;
 pub(crate) mod todos;
@@ -9,9 +12,13 @@ pub(crate) mod watchers;
 
 use std::{borrow::Cow, fmt::Display};
 
-use crate::{Data, Error};
+use crate::{
+    utils::{parse_discord_link, parse_snowflake},
+    Data,
+    Error,
+};
 
-use poise::ChoiceParameter;
+use poise::{serenity_prelude as serenity, ChoiceParameter, PopArgument, SlashArgError, SlashArgument};
 
 pub(crate) type CommandContext<'a> = poise::Context<'a, Data, CommandError>;
 pub(crate) type CommandResult<T> = std::result::Result<T, CommandError>;
@@ -62,36 +69,136 @@ impl From<sqlx::Error> for CommandError {
 }
 
 #[derive(Debug, Copy, Clone, ChoiceParameter)]
+#[allow(clippy::enum_variant_names)]
 pub(crate) enum SortResultsBy {
     #[name = "Oldest first"]
     OldestFirst,
     #[name = "Newest first"]
     NewestFirst,
+    #[name = "Awaiting my reply first"]
+    PendingFirst,
+}
+
+/// A channel or thread given to a command. Slash commands take it from Discord's channel picker; text commands also
+/// accept a channel or message link, as well as the mentions, IDs and names that Discord's own parsing accepts.
+#[derive(Debug, Clone)]
+pub(crate) struct ThreadChannel(pub serenity::GuildChannel);
+
+#[poise::async_trait]
+impl SlashArgument for ThreadChannel {
+    async fn extract(
+        ctx: &serenity::Context,
+        interaction: &serenity::CommandInteraction,
+        value: &serenity::ResolvedValue<'_>,
+    ) -> Result<Self, SlashArgError> {
+        <serenity::GuildChannel as SlashArgument>::extract(ctx, interaction, value).await.map(Self)
+    }
+
+    fn create(builder: serenity::CreateCommandOption) -> serenity::CreateCommandOption {
+        <serenity::GuildChannel as SlashArgument>::create(builder)
+    }
+}
+
+#[poise::async_trait]
+impl<'a> PopArgument<'a> for ThreadChannel {
+    async fn pop_from(
+        args: &'a str,
+        attachment_index: usize,
+        ctx: &serenity::Context,
+        msg: &serenity::Message,
+    ) -> Result<(&'a str, usize, Self), (Error, Option<String>)> {
+        let (rest, attachment_index, text) =
+            poise::pop_prefix_argument!(String, args, attachment_index, ctx, msg).await?;
+
+        let link = parse_discord_link(&text);
+        if link.is_some_and(|l| l.guild_id.is_some() && l.guild_id != msg.guild_id) {
+            return Err((Box::new(CommandError::new("That link is to a channel in a different server.")), Some(text)));
+        }
+
+        let channel_id = link.map(|l| l.channel_id).or_else(|| parse_snowflake(&text).map(serenity::ChannelId::new));
+        let channel = match channel_id {
+            Some(id) => id.to_channel(ctx).await.map_err(|e| (Box::new(e) as Error, Some(text.clone())))?.guild(),
+            None => {
+                let converted = <serenity::GuildChannel as serenity::ArgumentConvert>::convert(
+                    ctx,
+                    msg.guild_id,
+                    Some(msg.channel_id),
+                    &text,
+                )
+                .await;
+                converted.ok()
+            },
+        };
+
+        match channel {
+            Some(channel) => Ok((rest, attachment_index, Self(channel))),
+            None => Err((Box::new(CommandError::new("Couldn't find that channel or thread.")), Some(text))),
+        }
+    }
 }
 
 /// Retrieve the full list of commands for the bot.
 pub(crate) fn list() -> Vec<poise::Command<Data, CommandError>> {
     vec![
+        bugs::report(),
         greetings::hello(),
         help::help(),
         muses::add(),
         muses::remove(),
+        muses::rename(),
+        muses::copy(),
         muses::list(),
         stats::send_statistics(),
+        stats::send_statistics_dump(),
+        stats::send_guild_statistics(),
+        stats::ping(),
         scheduling::schedule(),
+        search::find(),
+        server::audit_threads(),
+        server::set_notification_channel(),
+        server::server_nickname(),
+        server::set_prefix(),
+        server::set_delete_emoji(),
+        server::register_commands(),
         threads::add(),
+        threads::track_forum(),
+        threads::dedupe(),
         threads::untrack(),
+        threads::undo_untrack(),
         threads::set_category(),
+        threads::set_category_order(),
+        threads::move_threads(),
+        threads::set_note(),
+        threads::set_label(),
+        threads::snooze(),
+        threads::share(),
+        threads::unsnooze(),
+        threads::transfer(),
+        threads::list_trackers(),
         threads::send_list(),
+        threads::send_all_list(),
         threads::send_pending_list(),
+        threads::send_last_reply_by_list(),
         threads::send_random_thread(),
         threads::notify_replies(),
         threads::set_timestamps(),
+        threads::set_bold_pending(),
+        threads::set_thread_name_length(),
+        threads::set_default_sort(),
+        threads::show_settings(),
+        threads::refresh_thread(),
+        threads::show_thread(),
+        threads::cleanup(),
+        threads::revive(),
         todos::add(),
         todos::remove(),
+        todos::reorder(),
         todos::list(),
+        todos::orphans(),
         watchers::add(),
         watchers::remove(),
         watchers::list(),
+        watchers::pause(),
+        watchers::resume(),
     ]
 }
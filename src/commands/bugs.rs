@@ -0,0 +1,28 @@
+use poise::serenity_prelude::Attachment;
+use tracing::info;
+
+use crate::{
+    commands::{CommandContext, CommandError, CommandResult},
+    messaging::submit_bug_report,
+};
+
+/// Send a bug report to the Thread Tracker developers, optionally with screenshots.
+#[poise::command(slash_command, rename = "tt_bug", category = "Bugs")]
+pub(crate) async fn report(
+    ctx: CommandContext<'_>,
+    #[description = "Describe the bug, including the steps to reproduce it"] report: String,
+    #[description = "A screenshot or video of the bug"] attachment: Option<Attachment>,
+    #[description = "Another screenshot or video of the bug"] second_attachment: Option<Attachment>,
+) -> CommandResult<()> {
+    let user = ctx.author();
+    info!("submitting bug report from {} ({})", user.name, user.id);
+
+    let attachments: Vec<Attachment> = attachment.into_iter().chain(second_attachment).collect();
+
+    ctx.defer_ephemeral().await?;
+    submit_bug_report(&ctx, &report, &attachments)
+        .await
+        .map_err(|e| CommandError::detailed("Unable to submit bug report", e))?;
+
+    Ok(())
+}
@@ -1,9 +1,12 @@
+use serenity::utils::MessageBuilder;
 use tracing::info;
 
 use crate::{
     commands::{CommandContext, CommandError},
     consts::*,
     messaging::reply,
+    utils::partition_into_map,
+    Data,
 };
 
 
@@ -13,6 +16,7 @@ pub(crate) enum HelpMessage {
     Main,
     Muses,
     Scheduling,
+    Server,
     Threads,
     Todos,
 }
@@ -26,6 +30,7 @@ impl HelpMessage {
             Some("threads" | "thread tracking") => Self::Threads,
             Some("todos" | "todo list") => Self::Todos,
             Some("scheduling") => Self::Scheduling,
+            Some("server" | "server settings") => Self::Server,
             _ => Self::Main,
         }
     }
@@ -41,6 +46,7 @@ impl HelpMessage {
             Self::Threads => THREADS,
             Self::Todos => TODOS,
             Self::Scheduling => SCHEDULING,
+            Self::Server => SERVER,
         }
     }
 
@@ -55,6 +61,7 @@ impl HelpMessage {
             Self::Threads => THREADS_TITLE,
             Self::Todos => TODOS_TITLE,
             Self::Scheduling => SCHEDULING_TITLE,
+            Self::Server => SERVER_TITLE,
         }
     }
 }
@@ -66,8 +73,13 @@ pub(crate) async fn help(
     #[description = "Specific command to show help about"]
     #[autocomplete = "poise::builtins::autocomplete_command"]
     command: Option<String>,
+    #[description = "List every available command instead of the help summary (default: off)"] detailed: Option<bool>,
 ) -> Result<(), CommandError> {
-    if command.is_none() {
+    if command.is_none() && detailed.unwrap_or(false) {
+        info!("listing all commands for help");
+        reply(&ctx, "Available commands", &list_commands(&ctx.framework().options.commands)).await?;
+    }
+    else if command.is_none() {
         reply(&ctx, "Command help", HelpMessage::Main.text()).await?;
     }
     else {
@@ -104,3 +116,38 @@ pub(crate) async fn help(
     }
     Ok(())
 }
+
+/// Build a list of the registered slash commands and their descriptions, grouped by category.
+fn list_commands(commands: &[poise::Command<Data, CommandError>]) -> String {
+    let mut entries = Vec::new();
+    for command in commands.iter().filter(|c| c.slash_action.is_some() || !c.subcommands.is_empty()) {
+        if command.subcommands.is_empty() {
+            entries.push((command.category.as_deref(), command.name.clone(), command.description.as_deref()));
+        }
+        else {
+            for subcommand in &command.subcommands {
+                let name = format!("{} {}", command.name, subcommand.name);
+                entries.push((command.category.as_deref(), name, subcommand.description.as_deref()));
+            }
+        }
+    }
+
+    let mut message = MessageBuilder::new();
+    for (category, mut entries) in partition_into_map(entries, |(category, _, _)| *category) {
+        entries.sort_by(|a, b| a.1.cmp(&b.1));
+
+        message.push("### ").push_line(category.unwrap_or("Other")).push_line("");
+        for (_, name, description) in entries {
+            message.push(format!("- `/{}`", name));
+            if let Some(description) = description {
+                message.push(" — ").push(description);
+            }
+
+            message.push_line("");
+        }
+
+        message.push_line("");
+    }
+
+    message.build()
+}
@@ -1,12 +1,13 @@
 use anyhow::anyhow;
 use serenity::{
-    model::prelude::{GuildId, UserId},
+    model::prelude::{GuildId, User, UserId},
     utils::{ContentModifier::*, MessageBuilder},
 };
 use tracing::{error, info};
 
 use crate::{
     commands::{CommandResult, CommandContext},
+    consts::GLOBAL_MUSE_GUILD_ID,
     db::{self, Database},
     messaging::reply,
 };
@@ -16,6 +17,7 @@ use crate::{
 pub(crate) async fn add(
     ctx: CommandContext<'_>,
     #[description = "The name of the muse to add"] muse_name: String,
+    #[description = "Register the muse in every server instead of only this one (default: off)"] global: Option<bool>,
 ) -> CommandResult<()> {
     let guild_id = match ctx.guild_id() {
         Some(id) => id,
@@ -24,15 +26,22 @@ pub(crate) async fn add(
 
     let user = ctx.author();
     let database = &ctx.data().database;
+    let global = global.unwrap_or(false);
 
-    info!("adding muse `{}` for {} ({})", &muse_name, user.name, user.id);
+    info!("adding muse `{}` for {} ({}), global: {}", &muse_name, user.name, user.id, global);
 
     let mut result = MessageBuilder::new();
     let mut errors = MessageBuilder::new();
     result.push("Muse ").push(Italic + &muse_name);
-    match db::add_muse(database, guild_id.get(), user.id.get(), &muse_name).await {
+    match db::add_muse(database, muse_guild_id(guild_id, global), user.id.get(), &muse_name).await {
         Ok(true) => {
-            result.push_line(" added successfully.");
+            if global {
+                result.push_line(" added successfully for all servers.");
+            }
+            else {
+                result.push_line(" added successfully.");
+            }
+
             reply(&ctx, "Add muse", &result.build()).await?;
             Ok(())
         },
@@ -54,7 +63,8 @@ pub(crate) async fn add(
 #[poise::command(slash_command, guild_only, rename = "tt_removemuse", category = "Muses")]
 pub(crate) async fn remove(
     ctx: CommandContext<'_>,
-    #[description = "The name of the muse to remove"] muse_name: String,
+    #[description = "The name or ID of the muse to remove"] muse_name: String,
+    #[description = "Remove a muse registered for every server (default: off)"] global: Option<bool>,
 ) -> CommandResult<()> {
     let guild_id = match ctx.guild_id() {
         Some(id) => id,
@@ -68,7 +78,17 @@ pub(crate) async fn remove(
 
     let mut result = MessageBuilder::new();
     result.push("Muse ").push(Italic + &muse_name);
-    match db::remove_muse(database, guild_id.get(), user.id.get(), &muse_name).await? {
+    let guild_id = muse_guild_id(guild_id, global.unwrap_or(false));
+    let mut removed = db::remove_muse(database, guild_id, user.id.get(), &muse_name).await?;
+
+    // Fall back to treating the name as a muse ID, for names which are difficult to type out exactly.
+    if removed == 0 {
+        if let Ok(id) = muse_name.trim().trim_start_matches('#').parse::<i32>() {
+            removed = db::remove_muse_by_id(database, user.id.get(), id).await?;
+        }
+    }
+
+    match removed {
         0 => {
             result.push_line(" was not found.");
             let error = result.build();
@@ -82,9 +102,103 @@ pub(crate) async fn remove(
     }
 }
 
+/// Rename one of your muses.
+#[poise::command(slash_command, guild_only, rename = "tt_renamemuse", category = "Muses")]
+pub(crate) async fn rename(
+    ctx: CommandContext<'_>,
+    #[description = "The current name of the muse"] muse_name: String,
+    #[description = "The new name for the muse"] new_name: String,
+    #[description = "Rename a muse registered for every server (default: off)"] global: Option<bool>,
+) -> CommandResult<()> {
+    let guild_id = match ctx.guild_id() {
+        Some(id) => id,
+        None => return Err(anyhow!("Unable to manage muses outside of a server").into()),
+    };
+
+    let user = ctx.author();
+    let database = &ctx.data().database;
+
+    info!("renaming muse `{}` to `{}` for {} ({})", &muse_name, &new_name, user.name, user.id);
+
+    let guild_id = muse_guild_id(guild_id, global.unwrap_or(false));
+    let mut result = MessageBuilder::new();
+    result.push("Muse ").push(Italic + &muse_name);
+    match db::rename_muse(database, guild_id, user.id.get(), &muse_name, &new_name).await {
+        Ok(0) => {
+            result.push_line(" was not found.");
+            Err(anyhow!(result.build()).into())
+        },
+        Ok(_) => {
+            result.push(" was renamed to ").push(Italic + &new_name).push_line(".");
+            reply(&ctx, "Muse renamed", &result.build()).await?;
+            Ok(())
+        },
+        Err(e) if e.as_database_error().is_some_and(|e| e.is_unique_violation()) => {
+            let mut error = MessageBuilder::new();
+            error.push("Muse ").push(Italic + &new_name).push(" is already known for ").mention(&user.id).push_line(".");
+            Err(anyhow!(error.build()).into())
+        },
+        Err(e) => Err(anyhow!("Error renaming muse: {}", e).into()),
+    }
+}
+
+/// Copy your muses from another server into this one.
+#[poise::command(slash_command, guild_only, rename = "tt_copymuses", category = "Muses")]
+pub(crate) async fn copy(
+    ctx: CommandContext<'_>,
+    #[description = "The server to copy muses from (your nickname for it, or its ID)"] source_server: String,
+) -> CommandResult<()> {
+    let guild_id = match ctx.guild_id() {
+        Some(id) => id,
+        None => return Err(anyhow!("Unable to manage muses outside of a server").into()),
+    };
+
+    let user = ctx.author();
+    let database = &ctx.data().database;
+
+    let source_guild_id = match db::get_server_id_from_nickname(database, user.id.get(), source_server.trim()).await? {
+        Some(entry) => entry.guild_id(),
+        None => match source_server.trim().parse::<u64>() {
+            Ok(id) if id != 0 => GuildId::new(id),
+            _ => {
+                return Err(anyhow!(
+                    "Could not find a server called `{}`. Use a server nickname set with `/tt_servername`, or a server ID.",
+                    source_server
+                )
+                .into())
+            },
+        },
+    };
+
+    if source_guild_id == guild_id {
+        return Err(anyhow!("You can't copy muses from a server into itself.").into());
+    }
+
+    info!("copying muses from guild {} to {} for {} ({})", source_guild_id, guild_id, user.name, user.id);
+
+    let muses = db::list_muses(database, source_guild_id.get(), user.id.get()).await?;
+    let mut copied = 0;
+    for muse in muses.iter().filter(|m| !m.is_global()) {
+        if db::add_muse(database, guild_id.get(), user.id.get(), &muse.muse_name).await? {
+            copied += 1;
+        }
+    }
+
+    let message = match copied {
+        0 => "No muses were copied. You may already have all of them registered in this server.".to_owned(),
+        _ => format!("Copied {} muse(s) into this server.", copied),
+    };
+    reply(&ctx, "Copy muses", &message).await?;
+
+    Ok(())
+}
+
 /// Show your muse list.
 #[poise::command(slash_command, guild_only, rename = "tt_muses", category = "Muses")]
-pub(crate) async fn list(ctx: CommandContext<'_>) -> CommandResult<()> {
+pub(crate) async fn list(
+    ctx: CommandContext<'_>,
+    #[description = "Show each muse's ID, which can be used to remove it (default: off)"] show_ids: Option<bool>,
+) -> CommandResult<()> {
     let guild_id = match ctx.guild_id() {
         Some(id) => id,
         None => return Err(anyhow!("Unable to list muses outside of a server").into()),
@@ -92,7 +206,7 @@ pub(crate) async fn list(ctx: CommandContext<'_>) -> CommandResult<()> {
     let database = &ctx.data().database;
     let user = ctx.author();
 
-    let muses = match get_list(database, user.id, guild_id).await {
+    let muses = match db::list_muses(database, guild_id.get(), user.id.get()).await {
         Ok(m) => m,
         Err(e) => return Err(anyhow!("Error listing muses: {}", e).into()),
     };
@@ -102,7 +216,17 @@ pub(crate) async fn list(ctx: CommandContext<'_>) -> CommandResult<()> {
         result.push("Muses registered for ").mention(&user.id).push_line(":");
 
         for muse in muses {
-            result.push_line(format!("- {}", muse));
+            result.push("- ");
+            if show_ids.unwrap_or(false) {
+                result.push(format!("`#{}` ", muse.id));
+            }
+
+            result.push_safe(&muse.muse_name);
+            if muse.is_global() {
+                result.push(" (all servers)");
+            }
+
+            result.push_line("");
         }
     }
     else {
@@ -115,7 +239,17 @@ pub(crate) async fn list(ctx: CommandContext<'_>) -> CommandResult<()> {
     Ok(())
 }
 
-/// Get the list of muses for the user out of the database.
+/// Get the guild ID to store a muse under, depending on whether it should apply in every server.
+fn muse_guild_id(guild_id: GuildId, global: bool) -> u64 {
+    if global {
+        GLOBAL_MUSE_GUILD_ID
+    }
+    else {
+        guild_id.get()
+    }
+}
+
+/// Get the list of muses for the user out of the database, including their global muses.
 pub(crate) async fn get_list(
     database: &Database,
     user_id: UserId,
@@ -127,3 +261,57 @@ pub(crate) async fn get_list(
         .map(|m| m.muse_name)
         .collect())
 }
+
+/// Determine whether a message author is one of the user's muses. A muse matches the author's username, display name,
+/// or nickname in the server, ignoring case and surrounding whitespace.
+pub(crate) fn muse_matches(author: &User, nick: &str, muses: &[String]) -> bool {
+    let names: Vec<String> = [Some(author.name.as_str()), author.global_name.as_deref(), Some(nick)]
+        .into_iter()
+        .flatten()
+        .map(|name| name.trim().to_lowercase())
+        .collect();
+
+    muses.iter().any(|muse| names.contains(&muse.trim().to_lowercase()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn author(name: &str, global_name: Option<&str>) -> User {
+        let mut user = User::default();
+        user.name = name.to_owned();
+        user.global_name = global_name.map(str::to_owned);
+        user
+    }
+
+    fn muses(names: &[&str]) -> Vec<String> {
+        names.iter().map(|n| n.to_string()).collect()
+    }
+
+    #[test]
+    fn matches_username_only() {
+        let author = author("tupperbox_bot", None);
+        assert!(muse_matches(&author, "", &muses(&["Tupperbox_Bot"])));
+        assert!(!muse_matches(&author, "", &muses(&["Someone Else"])));
+    }
+
+    #[test]
+    fn matches_nickname_only() {
+        let author = author("webhook", None);
+        assert!(muse_matches(&author, "Lady Morgana", &muses(&[" lady morgana "])));
+        assert!(!muse_matches(&author, "Lady Morgana", &muses(&["Morgana"])));
+    }
+
+    #[test]
+    fn matches_display_name_alias() {
+        let author = author("player123", Some("Sir Gawain"));
+        assert!(muse_matches(&author, "player123", &muses(&["Arthur", "sir gawain"])));
+    }
+
+    #[test]
+    fn no_muses_never_matches() {
+        let author = author("player123", Some("Sir Gawain"));
+        assert!(!muse_matches(&author, "Gawain", &[]));
+    }
+}
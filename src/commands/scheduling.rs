@@ -3,18 +3,41 @@ use std::str::FromStr;
 use anyhow::anyhow;
 use chrono::{DateTime, Days, FixedOffset, Months, NaiveDateTime, TimeDelta, Utc};
 use chrono_tz::Tz;
+use poise::ChoiceParameter;
 use regex::Regex;
-use serenity::{all::CacheHttp, model::prelude::*, utils::MessageBuilder};
-use tracing::{error, info};
+use serenity::{all::CacheHttp, builder::EditThread, model::prelude::*, utils::MessageBuilder};
+use tracing::{error, info, warn};
 
 use crate::{
     commands::{CommandContext, CommandError, CommandResult},
-    consts::setting_names::*,
+    consts::{setting_names::*, DEFAULT_UPCOMING_OCCURRENCES, MAX_UPCOMING_OCCURRENCES, SCHEDULED_MESSAGE_STAGGER},
     db::{self, Database},
     messaging::{reply, reply_error, send_invalid_command_call_error, send_message, whisper, whisper_error},
-    utils::truncate_string,
+    utils::{can_send_messages, truncate_string, MessageBuilderExtensions},
 };
 
+/// How to show dates and times for scheduled messages.
+#[derive(Debug, Copy, Clone, ChoiceParameter)]
+pub(crate) enum DateFormat {
+    #[name = "RFC 2822"]
+    Rfc2822,
+    #[name = "ISO 8601"]
+    Iso8601,
+    #[name = "Friendly"]
+    Friendly,
+}
+
+impl DateFormat {
+    /// Format the datetime in this format.
+    fn format(&self, datetime: &DateTime<Tz>) -> String {
+        match self {
+            DateFormat::Rfc2822 => datetime.to_rfc2822(),
+            DateFormat::Iso8601 => datetime.to_rfc3339(),
+            DateFormat::Friendly => datetime.format("%Y-%m-%d %H:%M %Z").to_string(),
+        }
+    }
+}
+
 /// Manage scheduled messages
 #[poise::command(
     slash_command,
@@ -27,7 +50,10 @@ use crate::{
         "update_message",
         "list_messages",
         "get_message",
-        "set_timezone"
+        "upcoming_messages",
+        "duplicate_message",
+        "set_timezone",
+        "set_date_format"
     )
 )]
 pub(crate) async fn schedule(ctx: CommandContext<'_>) -> CommandResult<()> {
@@ -48,7 +74,11 @@ pub(crate) async fn list_messages(ctx: CommandContext<'_>) -> CommandResult<()>
     } else {
         let mut content = MessageBuilder::new();
         for msg in messages {
-            let local_datetime = parse_and_display_local_time(&msg.datetime, author.id, &data.database).await?;
+            let datetime = match DateTime::parse_from_rfc3339(&msg.datetime) {
+                Ok(dt) => dt,
+                Err(e) => return Err(CommandError::detailed("Error parsing stored message datetime", e)),
+            };
+            let local_datetime = display_as_local_time(datetime, author.id, &data.database).await?;
             content
                 .push("- ")
                 .push_bold(msg.id.to_string())
@@ -59,6 +89,13 @@ pub(crate) async fn list_messages(ctx: CommandContext<'_>) -> CommandResult<()>
                 .push(" @ ")
                 .push(&local_datetime);
 
+            if msg.archived {
+                content.push(" ").push_italic("(already sent)");
+            }
+            else if let Ok(timestamp) = Timestamp::from_unix_timestamp(datetime.timestamp()) {
+                content.push(" (").push_timestamp(timestamp).push(")");
+            }
+
             if !msg.repeat.is_empty() && msg.repeat != "None" {
                 content.push(" (every ").push(msg.repeat).push(")");
             }
@@ -103,6 +140,47 @@ pub(crate) async fn get_message(
     Ok(())
 }
 
+/// Show the next few times a scheduled message will be sent
+#[poise::command(slash_command, guild_only, rename = "upcoming", category = "Scheduling")]
+pub(crate) async fn upcoming_messages(
+    ctx: CommandContext<'_>,
+    #[description = "The numeric ID of the message"] message_id: i32,
+    #[description = "How many upcoming times to show (default: 5)"]
+    #[min = 1]
+    #[max = 25]
+    count: Option<usize>,
+) -> CommandResult<()> {
+    let data = ctx.data();
+    let author = ctx.author();
+
+    let message = match db::get_scheduled_message(&data.database, message_id).await? {
+        Some(msg) if msg.user_id() == author.id && !msg.archived => msg,
+        _ => return Err(CommandError::new(format!("Unable to find the message with id {}", message_id))),
+    };
+
+    let count = count.unwrap_or(DEFAULT_UPCOMING_OCCURRENCES).min(MAX_UPCOMING_OCCURRENCES);
+    let mut occurrence = DateTime::parse_from_rfc3339(&message.datetime)
+        .map_err(|e| CommandError::detailed("Unable to read the message's scheduled time", e))?
+        .to_utc();
+
+    let mut response = MessageBuilder::new();
+    response.push("Upcoming times for ").push_bold_line_safe(&message.title);
+    for _ in 0..count {
+        let local_datetime = display_as_local_time(occurrence.fixed_offset(), author.id, &data.database).await?;
+        response.push_line(format!("- {}", local_datetime));
+
+        if message.repeat.is_empty() || message.repeat == "None" {
+            break;
+        }
+
+        occurrence = apply_repeat_duration(&message.repeat, occurrence)?;
+    }
+
+    whisper(&ctx, "Upcoming scheduled messages", &response.build()).await?;
+
+    Ok(())
+}
+
 /// Update an existing scheduled message
 #[poise::command(slash_command, guild_only, rename = "update", category = "Scheduling")]
 pub(crate) async fn update_message(
@@ -111,7 +189,7 @@ pub(crate) async fn update_message(
     #[description = "The title of the message"] title: Option<String>,
     #[description = "The message to send"] message: Option<String>,
     #[description = "When to send the message (format: yyyy-MM-dd hh:mm:ss)"] datetime: Option<String>,
-    #[description = "How often to repeat, in minutes (m), hours (h), days (d), weeks (w), or years (y)"]
+    #[description = "How often to repeat, in minutes (min), hours (h), days (d), weeks (w), months (M), or years (y)"]
     repeat: Option<String>,
     #[description = "The channel to send the message to when it's time to be sent"]
     #[channel_types("NewsThread", "PrivateThread", "PublicThread", "Text")]
@@ -128,6 +206,10 @@ pub(crate) async fn update_message(
             Ok(())
         },
         _ => {
+            if let Some(channel) = &channel {
+                validate_channel_permissions(&ctx, channel).await?;
+            }
+
             let data = ctx.data();
             match db::get_scheduled_message(&data.database, message_id).await? {
                 Some(existing_message) if existing_message.user_id() == author.id => {
@@ -165,11 +247,28 @@ pub(crate) async fn update_message(
                             },
                         };
 
-                        apply_repeat_duration(r, dt)?;
+                        validate_repeat_duration(r, dt)?;
                     }
 
                     let channel_id = channel.map(|c| c.id.get());
 
+                    // Giving an already-sent message a new datetime reactivates it, so say so when it happens.
+                    let mut result = MessageBuilder::new();
+                    match (existing_message.archived, parsed_datetime) {
+                        (true, Some(dt)) => {
+                            result
+                                .push("Scheduled message updated. It had already been sent, and will be sent again ")
+                                .push_timestamp(Timestamp::from(dt))
+                                .push(".");
+                        },
+                        (true, None) => {
+                            result.push("Scheduled message updated. It has already been sent; give it a new `datetime` to send it again.");
+                        },
+                        (false, _) => {
+                            result.push("Scheduled message updated successfully.");
+                        },
+                    }
+
                     match db::update_scheduled_message(
                         &data.database,
                         message_id,
@@ -182,8 +281,7 @@ pub(crate) async fn update_message(
                     .await
                     {
                         Ok(true) => {
-                            reply(&ctx, REPLY_TITLE, "Scheduled message updated successfully.")
-                                .await?;
+                            reply(&ctx, REPLY_TITLE, &result.build()).await?;
                             Ok(())
                         },
                         Ok(false) => {
@@ -257,12 +355,14 @@ pub(crate) async fn add_message(
     #[description = "The channel to send the message to when it's time to be sent"]
     #[channel_types("NewsThread", "PrivateThread", "PublicThread", "Text")]
     channel: GuildChannel,
-    #[description = "How often to repeat, in minutes (m), hours (h), days (d), weeks (w), or years (y)"]
+    #[description = "How often to repeat, in minutes (min), hours (h), days (d), weeks (w), months (M), or years (y)"]
     repeat: Option<String>,
 ) -> CommandResult<()> {
     let data = ctx.data();
     let author = ctx.author();
 
+    validate_channel_permissions(&ctx, &channel).await?;
+
     let target_datetime = parse_datetime_to_utc(&data.database, &datetime, author.id).await?;
 
     if !validate_datetime(target_datetime) {
@@ -274,7 +374,7 @@ pub(crate) async fn add_message(
 
     // If a repeat was specified, verify that adding it to the target datetime won't cause an error.
     if let Some(repeat) = &repeat {
-        apply_repeat_duration(repeat, target_datetime)?;
+        validate_repeat_duration(repeat, target_datetime)?;
     }
 
     let repeat = repeat.unwrap_or_else(|| "None".to_owned());
@@ -309,6 +409,99 @@ pub(crate) async fn add_message(
     Ok(())
 }
 
+/// Schedule a copy of one of your scheduled messages, optionally at another time or in another channel.
+#[poise::command(slash_command, guild_only, rename = "duplicate", category = "Scheduling")]
+pub(crate) async fn duplicate_message(
+    ctx: CommandContext<'_>,
+    #[description = "The numeric ID of the message to copy"] message_id: i32,
+    #[description = "When to send the copy (format: yyyy-MM-dd hh:mm:ss); defaults to the original's time"]
+    datetime: Option<String>,
+    #[description = "The channel to send the copy to; defaults to the original's channel"]
+    #[channel_types("NewsThread", "PrivateThread", "PublicThread", "Text")]
+    channel: Option<GuildChannel>,
+) -> CommandResult<()> {
+    let data = ctx.data();
+    let author = ctx.author();
+
+    let source = match db::get_scheduled_message(&data.database, message_id).await? {
+        Some(m) if m.user_id() == author.id => m,
+        _ => return Err(CommandError::new(format!("Could not find a scheduled message with ID {}.", message_id))),
+    };
+
+    let channel = match channel {
+        Some(c) => c,
+        None => match source.channel_id().to_channel(&ctx).await {
+            Ok(Channel::Guild(c)) => c,
+            _ => {
+                return Err(CommandError::new(format!(
+                    "The channel for scheduled message {} is no longer available. Choose a channel for the copy.",
+                    message_id
+                )))
+            },
+        },
+    };
+
+    validate_channel_permissions(&ctx, &channel).await?;
+
+    let target_datetime = match &datetime {
+        Some(d) => parse_datetime_to_utc(&data.database, d, author.id).await?,
+        None => match DateTime::parse_from_rfc3339(&source.datetime) {
+            Ok(dt) => dt.to_utc(),
+            Err(e) => return Err(CommandError::detailed("Error parsing already stored datetime!", e)),
+        },
+    };
+
+    if !validate_datetime(target_datetime) {
+        return Err(CommandError::new(format!(
+            "The target datetime {} is invalid as it is not in the future. Give the copy a new datetime.",
+            target_datetime.to_rfc3339()
+        )));
+    }
+
+    if !source.repeat.is_empty() && source.repeat != "None" {
+        apply_repeat_duration(&source.repeat, target_datetime)?;
+    }
+
+    info!("Duplicating scheduled message {} for user {} ({})", message_id, author.name, author.id);
+
+    let success = db::add_scheduled_message(
+        &data.database,
+        author.id,
+        target_datetime,
+        &source.repeat,
+        &source.title,
+        &source.message,
+        channel.id,
+    )
+    .await?;
+
+    if success {
+        let local_datetime = display_as_local_time(target_datetime.fixed_offset(), author.id, &data.database).await?;
+        reply(
+            &ctx,
+            "Duplicated scheduled message successfully",
+            &format_scheduled_message(
+                None,
+                &source.title,
+                &source.message,
+                &local_datetime,
+                Some(&source.repeat),
+                channel.id,
+            ),
+        )
+        .await?;
+    } else {
+        whisper_error(
+            &ctx,
+            "Failed to duplicate scheduled message",
+            "Scheduled message was not added to the database, but no error was encountered.",
+        )
+        .await?;
+    }
+
+    Ok(())
+}
+
 /// Set the timezone used for all messages scheduled by you.
 #[poise::command(slash_command, guild_only, rename = "timezone", category = "Scheduling")]
 pub(crate) async fn set_timezone(
@@ -343,6 +536,35 @@ pub(crate) async fn set_timezone(
     Ok(())
 }
 
+/// Set how dates and times are shown in your scheduled messages.
+#[poise::command(slash_command, guild_only, rename = "dateformat", category = "Scheduling")]
+pub(crate) async fn set_date_format(
+    ctx: CommandContext<'_>,
+    #[description = "How to show dates and times; leave empty to reset to RFC 2822"] format: Option<DateFormat>,
+) -> CommandResult<()> {
+    const REPLY_TITLE: &str = "Date format";
+    let database = &ctx.data().database;
+    let user = ctx.author();
+
+    let message = match format {
+        Some(format) => {
+            db::update_user_setting(database, user.id, USER_DATE_FORMAT, format.name()).await?;
+
+            let example = format.format(&Utc::now().with_timezone(&get_user_timezone(database, user.id).await?));
+            format!("Scheduled message times will be shown like this: {}", example)
+        },
+        None => {
+            db::remove_user_setting(database, user.id, USER_DATE_FORMAT).await?;
+
+            "Scheduled message times will be shown in the default RFC 2822 format.".to_owned()
+        },
+    };
+
+    whisper(&ctx, REPLY_TITLE, &message).await?;
+
+    Ok(())
+}
+
 /// Format a single scheduled message for display.
 fn format_scheduled_message(
     id: Option<i32>,
@@ -357,11 +579,16 @@ fn format_scheduled_message(
         content.push_bold("Id: ").push_line(id.to_string());
     }
 
+    let repeat = repeat.unwrap_or("None");
+    content.push_bold("Datetime: ").push_line(datetime).push_bold("Repeat: ").push(repeat);
+
+    // Show how the repeat was understood, so that mistakes like `m` (minutes) for `M` (months) are easy to spot.
+    match describe_repeat_duration(repeat) {
+        Some(description) if repeat != "None" => content.push_line(format!(" (every {})", description)),
+        _ => content.push_line(""),
+    };
+
     content
-        .push_bold("Datetime: ")
-        .push_line(datetime)
-        .push_bold("Repeat: ")
-        .push_line(repeat.unwrap_or("None"))
         .push_bold("Channel: ")
         .mention(&channel)
         .push_line("")
@@ -383,12 +610,22 @@ async fn parse_and_display_local_time(datetime: &str, user_id: UserId, database:
     display_as_local_time(parsed_datetime, user_id, database).await
 }
 
-/// Convert a datetime to the user's local timezone and format it for display using RFC2822 standards.
+/// Convert a datetime to the user's local timezone and format it for display in their chosen date format.
 async fn display_as_local_time(datetime: DateTime<FixedOffset>, user_id: UserId, database: &Database) -> CommandResult<String> {
     let timezone = get_user_timezone(database, user_id).await?;
     let local_time = datetime.with_timezone(&timezone);
 
-    Ok(local_time.to_rfc2822())
+    Ok(get_date_format(database, user_id).await.format(&local_time))
+}
+
+/// Get the format the user wants dates and times shown in, or RFC 2822 if none is set.
+pub(crate) async fn get_date_format(database: &Database, user_id: UserId) -> DateFormat {
+    db::get_user_setting(database, user_id, USER_DATE_FORMAT)
+        .await
+        .ok()
+        .flatten()
+        .and_then(|s| DateFormat::from_name(&s.value))
+        .unwrap_or(DateFormat::Rfc2822)
 }
 
 /// Parse a string into a valid UTC datetime.
@@ -414,65 +651,207 @@ async fn parse_datetime_to_utc(
 }
 
 /// Get the currently set timezone for the user, or UTC if none is set.
-async fn get_user_timezone(database: &Database, user_id: UserId) -> db::Result<Tz> {
+pub(crate) async fn get_user_timezone(database: &Database, user_id: UserId) -> db::Result<Tz> {
     Ok(db::get_user_setting(database, user_id, USER_TIMEZONE)
         .await?
         .map(|opt| chrono_tz::Tz::from_str(&opt.value).unwrap_or(chrono_tz::Tz::UTC))
         .unwrap_or(chrono_tz::Tz::UTC))
 }
 
-/// Apply the given repeat duration to the current datetime and return the resulting datetime.
-pub(crate) fn apply_repeat_duration(
-    repeat: &str,
-    current_datetime: DateTime<Utc>,
-) -> anyhow::Result<DateTime<Utc>> {
+/// Ensure Titi is able to send messages to the given channel, so that scheduled messages don't fail when they're sent.
+async fn validate_channel_permissions(ctx: &CommandContext<'_>, channel: &GuildChannel) -> CommandResult<()> {
+    let bot_id = ctx.framework().bot_id;
+    match can_send_messages(channel, bot_id, ctx).await {
+        Ok(true) => Ok(()),
+        Ok(false) => Err(CommandError::new(format!(
+            "Titi doesn't have permission to send messages in {}. Please choose another channel, or ask a moderator to give Titi the Send Messages permission there.",
+            channel.id.mention()
+        ))),
+        Err(e) => Err(CommandError::detailed(format!("Unable to check permissions for {}", channel.id.mention()), e)),
+    }
+}
+
+/// If the channel is an archived thread, unarchive it so that messages can be sent to it.
+/// Channels which can't be looked up are left for sending to report on.
+async fn unarchive_thread(channel_id: ChannelId, ctx: impl CacheHttp) -> anyhow::Result<()> {
+    let Ok(Channel::Guild(mut channel)) = channel_id.to_channel(&ctx).await else {
+        return Ok(());
+    };
+
+    if channel.thread_metadata.is_some_and(|m| m.archived) {
+        info!("Unarchiving thread {} to send a scheduled message", channel_id);
+        channel.edit_thread(&ctx, EditThread::new().archived(false)).await?;
+    }
+
+    Ok(())
+}
+
+/// A unit of time that scheduled messages can repeat in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum RepeatUnit {
+    Seconds,
+    Minutes,
+    Hours,
+    Days,
+    Weeks,
+    Months,
+    Years,
+}
+
+impl RepeatUnit {
+    /// Get the unit for a repeat duration suffix, such as `h` or `days`. Note that `m` is minutes and `M` is months.
+    fn from_suffix(suffix: &str) -> Option<Self> {
+        let unit = match suffix {
+            "s" | "sec" | "secs" | "second" | "seconds" => RepeatUnit::Seconds,
+            "m" | "min" | "mins" | "minute" | "minutes" => RepeatUnit::Minutes,
+            "h" | "hr"  | "hrs"  | "hour"   | "hours" => RepeatUnit::Hours,
+            "d" | "dy"  | "dys"  | "day"    | "days" => RepeatUnit::Days,
+            "w" | "wk"  | "wks"  | "week"   | "weeks" => RepeatUnit::Weeks,
+            "M" | "mo"  | "mos"  | "month"  | "months" => RepeatUnit::Months,
+            "y" | "yr"  | "yrs"  | "year"   | "years" => RepeatUnit::Years,
+            _ => return None,
+        };
+
+        Some(unit)
+    }
+
+    /// Get the name of the unit, for the given number of them.
+    fn name(&self, count: u64) -> &'static str {
+        let (singular, plural) = match self {
+            RepeatUnit::Seconds => ("second", "seconds"),
+            RepeatUnit::Minutes => ("minute", "minutes"),
+            RepeatUnit::Hours => ("hour", "hours"),
+            RepeatUnit::Days => ("day", "days"),
+            RepeatUnit::Weeks => ("week", "weeks"),
+            RepeatUnit::Months => ("month", "months"),
+            RepeatUnit::Years => ("year", "years"),
+        };
+
+        if count == 1 { singular } else { plural }
+    }
+}
+
+/// One part of a repeat duration, such as `3d`.
+struct RepeatToken<'a> {
+    count: u64,
+    unit: RepeatUnit,
+    suffix: &'a str,
+}
+
+/// Split a repeat duration into its parts, returning an error listing any parts that aren't recognised.
+fn parse_repeat_duration(repeat: &str) -> anyhow::Result<Vec<RepeatToken<'_>>> {
     if repeat.is_empty() {
         return Err(anyhow!("The repeat duration is empty."));
     }
 
-    let mut new_datetime = current_datetime;
-
     // If this fails, this function is useless anyway and we need to rewrite the regex.
     let regex = Regex::new("([0-9]+)([a-zA-Z]+)").unwrap();
+    let mut tokens = Vec::new();
     let mut unrecognised = Vec::new();
-    let mut time_delta = TimeDelta::seconds(0);
 
     for token in repeat.split_whitespace() {
-        match regex.captures(token) {
-            Some(captures) => {
-                // If this matches, there has to be a group 0 and 1, and group 0 has to contain all numbers, so these unwraps are safe.
-                let number: u64 = captures.get(1).unwrap().as_str().parse().unwrap();
-                let time_period = captures.get(2).unwrap().as_str();
-
-                let changed_delta = match time_period {
-                    "h" | "hr"  | "hrs"  | "hour"   | "hours" => time_delta.checked_add(&TimeDelta::hours(number as i64)),
-                    "m" | "min" | "mins" | "minute" | "minutes" => time_delta.checked_add(&TimeDelta::minutes(number as i64)),
-                    "s" | "sec" | "secs" | "second" | "seconds" => time_delta.checked_add(&TimeDelta::seconds(number as i64)),
-                    _ => None,
-                };
-
-                if let Some(delta) = changed_delta {
-                    time_delta = delta;
-                } else {
-                    let changed_datetime = match time_period {
-                        "y" | "yr" | "year" | "yrs"   | "years" => new_datetime.checked_add_months(Months::new(12 * number as u32)),
-                        "d" | "dy" | "dys"  | "day"   | "days" => new_datetime.checked_add_days(Days::new(number)),
-                        "w" | "wk" | "wks"  | "week"  | "weeks" => new_datetime.checked_add_days(Days::new(number * 7)),
-                        "M" | "mo" | "mos"  | "month" | "months" => new_datetime.checked_add_months(Months::new(number as u32)),
-                        _ => None,
-                    };
-
-                    if let Some(dt) = changed_datetime {
-                        new_datetime = dt;
-                    } else {
-                        unrecognised.push(token);
-                    }
-                }
-            },
+        let parsed = regex.captures(token).and_then(|captures| {
+            // If this matches, there has to be a group 1 and 2, so these unwraps are safe.
+            let count = captures.get(1).unwrap().as_str().parse().ok()?;
+            let suffix = captures.get(2).unwrap().as_str();
+            RepeatUnit::from_suffix(suffix).map(|unit| RepeatToken { count, unit, suffix })
+        });
+
+        match parsed {
+            Some(t) => tokens.push(t),
             None => unrecognised.push(token),
         }
     }
 
+    if unrecognised.is_empty() {
+        Ok(tokens)
+    }
+    else {
+        Err(anyhow!("Unrecognised tokens in repeat duration: {}", unrecognised.join(", ")))
+    }
+}
+
+/// Describe how a repeat duration is interpreted, for example `1 year, 2 minutes`.
+fn describe_repeat_duration(repeat: &str) -> Option<String> {
+    let tokens = parse_repeat_duration(repeat).ok()?;
+    let parts: Vec<String> = tokens.iter().map(|t| format!("{} {}", t.count, t.unit.name(t.count))).collect();
+
+    Some(parts.join(", "))
+}
+
+/// Check that a repeat duration given by a user can be applied to the given datetime, and that it doesn't use a bare
+/// `m`, which is easily typed meaning months (`M`) when it means minutes. Ambiguous durations are refused with how each
+/// part would have been read, before anything is saved.
+fn validate_repeat_duration(repeat: &str, datetime: DateTime<Utc>) -> CommandResult<()> {
+    let tokens = parse_repeat_duration(repeat)?;
+
+    if tokens.iter().any(|t| t.suffix == "m") {
+        let mut message = MessageBuilder::new();
+        message.push_line(format!("The repeat duration `{}` would be read as:", repeat));
+        for token in &tokens {
+            message.push_line(format!("- `{}{}`: {} {}", token.count, token.suffix, token.count, token.unit.name(token.count)));
+        }
+
+        message.push("`m` is easily mistaken for months, so please use `min` for minutes, or `M` or `mo` for months.");
+
+        return Err(CommandError::new(message.build()));
+    }
+
+    apply_repeat_duration(repeat, datetime)?;
+
+    Ok(())
+}
+
+/// Apply the given repeat duration to the current datetime and return the resulting datetime.
+pub(crate) fn apply_repeat_duration(
+    repeat: &str,
+    current_datetime: DateTime<Utc>,
+) -> anyhow::Result<DateTime<Utc>> {
+    let tokens = parse_repeat_duration(repeat)?;
+
+    let mut new_datetime = current_datetime;
+    let mut time_delta = TimeDelta::seconds(0);
+
+    for token in tokens {
+        let number = token.count;
+        let changed_delta = match token.unit {
+            RepeatUnit::Hours => i64::try_from(number)
+                .ok()
+                .and_then(TimeDelta::try_hours)
+                .and_then(|d| time_delta.checked_add(&d)),
+            RepeatUnit::Minutes => i64::try_from(number)
+                .ok()
+                .and_then(TimeDelta::try_minutes)
+                .and_then(|d| time_delta.checked_add(&d)),
+            RepeatUnit::Seconds => i64::try_from(number)
+                .ok()
+                .and_then(TimeDelta::try_seconds)
+                .and_then(|d| time_delta.checked_add(&d)),
+            _ => Some(time_delta),
+        };
+
+        let changed_datetime = match token.unit {
+            RepeatUnit::Years => u32::try_from(number)
+                .ok()
+                .and_then(|years| years.checked_mul(12))
+                .and_then(|months| new_datetime.checked_add_months(Months::new(months))),
+            RepeatUnit::Days => new_datetime.checked_add_days(Days::new(number)),
+            RepeatUnit::Weeks => number.checked_mul(7).and_then(|days| new_datetime.checked_add_days(Days::new(days))),
+            RepeatUnit::Months => u32::try_from(number)
+                .ok()
+                .and_then(|months| new_datetime.checked_add_months(Months::new(months))),
+            _ => Some(new_datetime),
+        };
+
+        match (changed_delta, changed_datetime) {
+            (Some(delta), Some(dt)) => {
+                time_delta = delta;
+                new_datetime = dt;
+            },
+            _ => return Err(anyhow!("The repeat duration `{}{}` is too large.", token.count, token.suffix)),
+        }
+    }
+
     // Safeguard to ensure that the new scheduled time is always in the future.
     // This preserves the repeat offets precisely, while also ensuring that
     // we don't end up with a new scheduled time that happens to have already
@@ -485,11 +864,7 @@ pub(crate) fn apply_repeat_duration(
         }
     }
 
-    if unrecognised.is_empty() {
-        Ok(new_datetime)
-    } else {
-        Err(anyhow!("Unrecognised tokens in repeat duration: {}", unrecognised.join(", ")))
-    }
+    Ok(new_datetime)
 }
 
 /// Validate datetime is current or future
@@ -519,27 +894,41 @@ pub(crate) async fn send_scheduled_messages(
 
     let messages = db::get_all_scheduled_messages(&database).await?;
 
+    let now = chrono::offset::Utc::now();
+    let mut due_messages = Vec::new();
     for message in messages.iter().filter(|m| !m.archived) {
-        let scheduled_time = match DateTime::parse_from_rfc3339(&message.datetime) {
-            Ok(dt) => dt.to_utc(),
+        match DateTime::parse_from_rfc3339(&message.datetime) {
+            Ok(dt) if dt.to_utc() <= now => due_messages.push((dt.to_utc(), message)),
+            Ok(_) => {},
             Err(e) => {
                 error!(
                     "Error parsing scheduled message's timestamp '{}' with title '{}': {}",
                     &message.datetime, &message.title, e
                 );
-                continue;
             },
         };
+    }
 
-        if scheduled_time > chrono::offset::Utc::now() {
-            continue;
-        }
+    due_messages.sort_by_key(|(scheduled_time, _)| *scheduled_time);
+
+    // Space out the sends so that a lot of messages coming due at once doesn't run into rate limits.
+    let mut stagger_interval = tokio::time::interval(SCHEDULED_MESSAGE_STAGGER);
+    let mut sent = 0;
+    for (scheduled_time, message) in due_messages {
+        stagger_interval.tick().await;
 
         info!(
             "Sending out scheduled message {} with title '{}', scheduled for {}",
             message.id, message.title, message.datetime
         );
 
+        // Archived threads can't be sent to. If the thread can't be unarchived, leave the message as-is so that
+        // it's tried again next time, rather than archiving a repeating message.
+        if let Err(e) = unarchive_thread(message.channel_id(), &ctx).await {
+            warn!("Unable to unarchive the target thread for scheduled message {}, will retry: {}", message.id, e);
+            continue;
+        }
+
         if message.repeat.is_empty() || message.repeat == "None" {
             info!("Flagging message {} as sent/archived.", message.id);
             archive_scheduled_message(&database, message.id).await;
@@ -581,7 +970,47 @@ pub(crate) async fn send_scheduled_messages(
             error!("Unable to send scheduled message, archiving it instead: {}", e);
             archive_scheduled_message(&database, message.id).await;
         }
+        else {
+            sent += 1;
+        }
     }
 
+    info!("Sent {} scheduled message(s).", sent);
+
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn in_an_hour() -> DateTime<Utc> {
+        Utc::now() + TimeDelta::hours(1)
+    }
+
+    #[test]
+    fn oversized_repeats_are_rejected_instead_of_overflowing() {
+        for repeat in ["4294967296M", "400000000y", "3000000000000000000w", "18446744073709551615h"] {
+            let result = apply_repeat_duration(repeat, in_an_hour());
+            let error = result.expect_err(repeat).to_string();
+            assert!(error.contains("too large"), "{}: {}", repeat, error);
+        }
+    }
+
+    #[test]
+    fn bare_m_is_refused_with_how_each_part_is_read() {
+        for (repeat, parts) in [("1m", vec!["`1m`: 1 minute"]), ("1d 30m", vec!["`1d`: 1 day", "`30m`: 30 minutes"])] {
+            let error = validate_repeat_duration(repeat, in_an_hour()).expect_err(repeat).to_string();
+            for part in parts {
+                assert!(error.contains(part), "{}: {}", repeat, error);
+            }
+        }
+    }
+
+    #[test]
+    fn unambiguous_repeats_are_accepted() {
+        for repeat in ["30min", "1d 30min", "1M", "2mo", "1w 12h"] {
+            assert!(validate_repeat_duration(repeat, in_an_hour()).is_ok(), "{}", repeat);
+        }
+    }
+}
@@ -0,0 +1,142 @@
+use serenity::utils::{ContentModifier::*, EmbedMessageBuilding, MessageBuilder};
+use tracing::{error, info};
+
+use crate::{
+    commands::{CommandContext, CommandError, CommandResult},
+    consts::MAX_SEARCH_RESULTS,
+    db,
+    messaging::reply,
+    utils::{escape_link_text, get_channel_name},
+};
+
+/// Search your todo list and tracked thread names.
+#[poise::command(slash_command, guild_only, rename = "tt_find", category = "Thread tracking")]
+pub(crate) async fn find(
+    ctx: CommandContext<'_>,
+    #[description = "The text to search for"] query: String,
+    #[description = "Which page of results to show (default: 1)"]
+    #[min = 1]
+    page: Option<u32>,
+) -> CommandResult<()> {
+    let guild_id = match ctx.guild_id() {
+        Some(id) => id,
+        None => return Err(CommandError::new("Unable to search tracked threads outside of a server")),
+    };
+
+    let query = query.trim();
+    if query.is_empty() {
+        return Err(CommandError::new("Please enter some text to search for."));
+    }
+
+    ctx.defer().await?;
+
+    let user = ctx.author();
+    let database = &ctx.data().database;
+    info!("searching for `{}` for {} ({})", query, user.name, user.id);
+
+    let todos = db::search_todos(database, guild_id.get(), user.id.get(), query).await?;
+
+    let lowercase_query = query.to_lowercase();
+    let mut threads = Vec::new();
+    for thread in db::list_threads(database, guild_id.get(), user.id.get(), None).await? {
+        let name = match &thread.thread_name {
+            Some(name) => Some(name.clone()),
+            None => {
+                let name = get_channel_name(thread.channel_id(), ctx).await;
+                if let Some(name) = &name {
+                    let result =
+                        db::update_thread_name(database, thread.guild_id, thread.channel_id, name).await;
+                    if let Err(e) = result {
+                        error!("Error storing thread name for {}: {}", thread.channel_id, e);
+                    }
+                }
+
+                name
+            },
+        };
+
+        if let Some(name) = name.filter(|n| n.to_lowercase().contains(&lowercase_query)) {
+            threads.push((name, thread));
+        }
+    }
+
+    let page = page.unwrap_or(1).max(1) as usize;
+    let page_count = threads.len().max(todos.len()).div_ceil(MAX_SEARCH_RESULTS);
+    if page > 1 && page > page_count {
+        return Err(CommandError::new(format!(
+            "There are only {} page(s) of results for `{}`.",
+            page_count, query
+        )));
+    }
+
+    let mut message = MessageBuilder::new();
+
+    let threads = page_of(&threads, page);
+    if !threads.is_empty() {
+        message.push_line("## Threads");
+        for (name, thread) in threads {
+            let url = format!("https://discord.com/channels/{}/{}", thread.guild_id, thread.channel_id);
+            message.push("- ").push_named_link(Bold + format!("#{}", escape_link_text(name)), url);
+            if let Some(category) = &thread.category {
+                message.push(" (").push_safe(category).push(")");
+            }
+
+            message.push_line("");
+        }
+    }
+
+    let todos = page_of(&todos, page);
+    if !todos.is_empty() {
+        message.push_line("## To do list");
+        for todo in todos {
+            message.push("- ").push_safe(&todo.content);
+            if let Some(category) = &todo.category {
+                message.push(" (").push_safe(category).push(")");
+            }
+
+            message.push_line("");
+        }
+    }
+
+    if message.0.is_empty() {
+        message.push("No threads or to do list entries matched ").push(Italic + query);
+    }
+    else if page_count > 1 {
+        message.push_line(format!("Page {} of {}. Use the `page` option to see more results.", page, page_count));
+    }
+
+    reply(&ctx, "Search results", &message.build()).await?;
+
+    Ok(())
+}
+
+/// Get the given page of results, counting pages from 1. Pages past the end are empty.
+fn page_of<T>(results: &[T], page: usize) -> &[T] {
+    let start = page.saturating_sub(1).saturating_mul(MAX_SEARCH_RESULTS).min(results.len());
+    let end = start.saturating_add(MAX_SEARCH_RESULTS).min(results.len());
+
+    &results[start..end]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pages_split_results_evenly() {
+        let results: Vec<usize> = (0..MAX_SEARCH_RESULTS * 2 + 3).collect();
+
+        assert_eq!(page_of(&results, 1), &results[..MAX_SEARCH_RESULTS]);
+        assert_eq!(page_of(&results, 2), &results[MAX_SEARCH_RESULTS..MAX_SEARCH_RESULTS * 2]);
+        assert_eq!(page_of(&results, 3), &results[MAX_SEARCH_RESULTS * 2..]);
+    }
+
+    #[test]
+    fn pages_past_the_end_are_empty() {
+        let results = [1, 2, 3];
+
+        assert!(page_of(&results, 2).is_empty());
+        assert!(page_of(&results, usize::MAX).is_empty());
+        assert!(page_of::<u8>(&[], 1).is_empty());
+    }
+}
@@ -0,0 +1,366 @@
+use serenity::{model::prelude::*, utils::MessageBuilder};
+use tracing::info;
+
+use crate::{
+    commands::{CommandContext, CommandError, CommandResult},
+    consts::{setting_names::*, DEFAULT_PREFIX, MAX_DELETE_EMOJI, MAX_PREFIX_LENGTH},
+    db::{self, Database},
+    messaging::{reply, whisper},
+    utils::register_guild_commands,
+};
+
+/// Post reply notifications for this server in a channel instead of as DMs, optionally pinging a role.
+#[poise::command(
+    slash_command,
+    guild_only,
+    required_permissions = "MANAGE_GUILD",
+    rename = "tt_notifychannel",
+    category = "Server settings"
+)]
+pub(crate) async fn set_notification_channel(
+    ctx: CommandContext<'_>,
+    #[description = "The channel to post reply notifications in; leave empty to go back to DMs"]
+    #[channel_types("NewsThread", "PrivateThread", "PublicThread", "Text")]
+    channel: Option<GuildChannel>,
+    #[description = "The role to ping when posting reply notifications"] role: Option<Role>,
+) -> CommandResult<()> {
+    const REPLY_TITLE: &str = "Reply notification channel";
+    let guild_id = match ctx.guild_id() {
+        Some(id) => id,
+        None => return Err(CommandError::new("Unable to manage server settings outside of a server")),
+    };
+
+    let database = &ctx.data().database;
+    let mut message = MessageBuilder::new();
+
+    match channel {
+        Some(channel) => {
+            info!("setting reply notification channel for guild {} to {}", guild_id, channel.id);
+            db::update_guild_setting(
+                database,
+                guild_id,
+                GUILD_NOTIFICATION_CHANNEL,
+                &channel.id.to_string(),
+            )
+            .await?;
+
+            message.push("Reply notifications will be posted in ").mention(&channel.id);
+
+            match role {
+                Some(role) => {
+                    db::update_guild_setting(
+                        database,
+                        guild_id,
+                        GUILD_NOTIFICATION_ROLE,
+                        &role.id.to_string(),
+                    )
+                    .await?;
+                    message.push(", pinging ").role(role.id);
+                },
+                None => {
+                    db::remove_guild_setting(database, guild_id, GUILD_NOTIFICATION_ROLE).await?;
+                },
+            }
+
+            message.push_line(".");
+        },
+        None => {
+            info!("clearing reply notification channel for guild {}", guild_id);
+            db::remove_guild_setting(database, guild_id, GUILD_NOTIFICATION_CHANNEL).await?;
+            db::remove_guild_setting(database, guild_id, GUILD_NOTIFICATION_ROLE).await?;
+
+            message.push_line("Reply notifications will be sent as DMs to subscribed users.");
+        },
+    }
+
+    reply(&ctx, REPLY_TITLE, &message.build()).await?;
+
+    Ok(())
+}
+
+/// Set the prefix for text commands in this server, or reset it to the default.
+#[poise::command(
+    slash_command,
+    guild_only,
+    required_permissions = "MANAGE_GUILD",
+    rename = "tt_prefix",
+    category = "Server settings"
+)]
+pub(crate) async fn set_prefix(
+    ctx: CommandContext<'_>,
+    #[description = "The new prefix for text commands; leave empty to reset it to tt!"] prefix: Option<String>,
+) -> CommandResult<()> {
+    const REPLY_TITLE: &str = "Command prefix";
+    let guild_id = match ctx.guild_id() {
+        Some(id) => id,
+        None => return Err(CommandError::new("Unable to manage server settings outside of a server")),
+    };
+
+    let data = ctx.data();
+    let mut message = MessageBuilder::new();
+
+    match prefix.as_deref().map(str::trim) {
+        Some(prefix) if prefix.is_empty() || prefix.chars().count() > MAX_PREFIX_LENGTH || prefix.contains(char::is_whitespace) => {
+            return Err(CommandError::new(format!(
+                "Command prefixes must be between 1 and {} characters long, with no spaces.",
+                MAX_PREFIX_LENGTH
+            )))
+        },
+        Some(prefix) => {
+            info!("setting command prefix for guild {} to `{}`", guild_id, prefix);
+            db::update_guild_setting(&data.database, guild_id, GUILD_PREFIX, prefix).await?;
+            data.guild_prefixes.store(guild_id, prefix.to_owned()).await;
+
+            message.push("Text commands in this server now use the prefix ").push_mono_line_safe(prefix);
+        },
+        None => {
+            info!("resetting command prefix for guild {}", guild_id);
+            db::remove_guild_setting(&data.database, guild_id, GUILD_PREFIX).await?;
+            data.guild_prefixes.store(guild_id, DEFAULT_PREFIX.to_owned()).await;
+
+            message.push("Text commands in this server now use the default prefix ").push_mono_line(DEFAULT_PREFIX);
+        },
+    }
+
+    reply(&ctx, REPLY_TITLE, &message.build()).await?;
+
+    Ok(())
+}
+
+/// Choose which emojis can be used to delete Titi's messages in this server, or turn that off entirely.
+#[poise::command(
+    slash_command,
+    guild_only,
+    required_permissions = "MANAGE_GUILD",
+    rename = "tt_deleteemoji",
+    category = "Server settings"
+)]
+pub(crate) async fn set_delete_emoji(
+    ctx: CommandContext<'_>,
+    #[description = "The emojis which delete Titi's messages, separated by spaces"] emojis: Option<String>,
+    #[description = "Whether reacting to Titi's messages can delete them"] enabled: Option<bool>,
+) -> CommandResult<()> {
+    const REPLY_TITLE: &str = "Delete reactions";
+    let guild_id = match ctx.guild_id() {
+        Some(id) => id,
+        None => return Err(CommandError::new("Unable to manage server settings outside of a server")),
+    };
+
+    let data = ctx.data();
+    let database = &data.database;
+
+    if emojis.is_none() && enabled.is_none() {
+        info!("resetting delete emojis for guild {}", guild_id);
+        db::remove_guild_setting(database, guild_id, GUILD_DELETE_EMOJI).await?;
+        db::remove_guild_setting(database, guild_id, GUILD_DELETE_BY_REACTION).await?;
+    }
+
+    if let Some(emojis) = &emojis {
+        let emojis: Vec<&str> = emojis.split_whitespace().collect();
+        if emojis.is_empty() || emojis.len() > MAX_DELETE_EMOJI {
+            return Err(CommandError::new(format!("Please provide between 1 and {} emojis.", MAX_DELETE_EMOJI)));
+        }
+
+        info!("setting delete emojis for guild {} to {:?}", guild_id, emojis);
+        db::update_guild_setting(database, guild_id, GUILD_DELETE_EMOJI, &emojis.join(" ")).await?;
+    }
+
+    if let Some(enabled) = enabled {
+        info!("setting delete by reaction for guild {} to {}", guild_id, enabled);
+        db::update_guild_setting(database, guild_id, GUILD_DELETE_BY_REACTION, &enabled.to_string()).await?;
+    }
+
+    data.delete_emojis.remove(&guild_id).await;
+    let emojis = data.guild_delete_emojis(Some(guild_id)).await;
+
+    let message = if emojis.is_empty() {
+        "Titi's messages in this server can no longer be deleted by reacting to them.".to_owned()
+    }
+    else {
+        format!("Titi's messages in this server can be deleted by reacting with {}.", emojis.join(" or "))
+    };
+
+    reply(&ctx, REPLY_TITLE, &message).await?;
+
+    Ok(())
+}
+
+/// Set or show your own nickname for this server, used to identify it in notifications.
+#[poise::command(slash_command, guild_only, rename = "tt_servername", category = "Server settings")]
+pub(crate) async fn server_nickname(
+    ctx: CommandContext<'_>,
+    #[description = "The nickname to use for this server; use 'none' to clear it, or leave empty to show it"]
+    nickname: Option<String>,
+) -> CommandResult<()> {
+    const REPLY_TITLE: &str = "Server nickname";
+    let guild_id = match ctx.guild_id() {
+        Some(id) => id,
+        None => return Err(CommandError::new("Unable to manage server nicknames outside of a server")),
+    };
+
+    let user = ctx.author();
+    let database = &ctx.data().database;
+    let mut message = MessageBuilder::new();
+
+    match nickname.as_deref().map(str::trim) {
+        Some("") => return Err(CommandError::new("Server nicknames cannot be empty.")),
+        Some(nickname) if nickname.eq_ignore_ascii_case("none") => {
+            info!("clearing nickname for guild {} for {} ({})", guild_id, user.name, user.id);
+            match db::remove_server_nickname(database, user.id.get(), guild_id.get()).await? {
+                true => message.push_line("Your nickname for this server has been cleared."),
+                false => message.push_line("You have not set a nickname for this server."),
+            };
+        },
+        Some(nickname) => {
+            if let Some(existing) =
+                db::get_server_id_from_nickname(database, user.id.get(), nickname).await?
+            {
+                if existing.guild_id() != guild_id {
+                    return Err(CommandError::new(format!(
+                        "You are already using the nickname '{}' for another server.",
+                        nickname
+                    )));
+                }
+            }
+
+            info!("setting nickname for guild {} to `{}` for {} ({})", guild_id, nickname, user.name, user.id);
+            db::set_server_nickname(database, user.id.get(), guild_id.get(), nickname).await?;
+            message.push("Your nickname for this server is now ").push_bold_line_safe(nickname);
+        },
+        None => {
+            match db::get_server_nickname(database, user.id.get(), guild_id.get()).await? {
+                Some(entry) => message
+                    .push("Your nickname for this server is ")
+                    .push_bold_line_safe(entry.nickname),
+                None => message.push_line("You have not set a nickname for this server."),
+            };
+        },
+    }
+
+    whisper(&ctx, REPLY_TITLE, &message.build()).await?;
+
+    Ok(())
+}
+
+/// List every thread tracked in this server and how many users are tracking it.
+#[poise::command(
+    slash_command,
+    guild_only,
+    required_permissions = "MANAGE_GUILD",
+    rename = "tt_auditthreads",
+    category = "Server settings"
+)]
+pub(crate) async fn audit_threads(ctx: CommandContext<'_>) -> CommandResult<()> {
+    const REPLY_TITLE: &str = "Tracked threads in this server";
+    let guild_id = match ctx.guild_id() {
+        Some(id) => id,
+        None => return Err(CommandError::new("Unable to audit tracked threads outside of a server")),
+    };
+
+    ctx.defer().await?;
+
+    let user = ctx.author();
+    info!("auditing tracked threads in guild {} for {} ({})", guild_id, user.name, user.id);
+
+    let summary = db::guild_thread_summary(&ctx.data().database, guild_id.get()).await?;
+
+    let mut message = MessageBuilder::new();
+    let mut unavailable = MessageBuilder::new();
+    for entry in summary {
+        let trackers = match entry.trackers {
+            1 => "1 tracker".to_owned(),
+            n => format!("{} trackers", n),
+        };
+
+        match entry.channel_id().to_channel(ctx).await {
+            Ok(_) => message.push("- ").mention(&entry.channel_id()).push(" — ").push_line(trackers),
+            Err(_) => unavailable
+                .push("- ")
+                .push_mono(entry.channel_id.to_string())
+                .push(" — ")
+                .push_line(trackers),
+        };
+    }
+
+    if !unavailable.0.is_empty() {
+        message.push_line("").push_bold_line("Unavailable channels").push_line(unavailable.build());
+    }
+
+    if message.0.is_empty() {
+        message.push_line("No threads are currently being tracked in this server.");
+    }
+
+    reply(&ctx, REPLY_TITLE, &message.build()).await?;
+
+    Ok(())
+}
+
+/// Register Titi's latest slash commands in this server right away.
+#[poise::command(slash_command, guild_only, rename = "tt_register", category = "Server settings")]
+pub(crate) async fn register_commands(
+    ctx: CommandContext<'_>,
+    #[description = "Remove the commands registered in this server, leaving only the ones available everywhere"]
+    remove: Option<bool>,
+) -> CommandResult<()> {
+    const REPLY_TITLE: &str = "Slash commands";
+    let guild_id = match ctx.guild_id() {
+        Some(id) => id,
+        None => return Err(CommandError::new("Unable to register commands outside of a server")),
+    };
+
+    // Bot owners can use this anywhere; `required_permissions` would also apply to them, so check by hand instead.
+    let user = ctx.author();
+    let is_owner = ctx.framework().options().owners.contains(&user.id);
+    let can_manage_guild =
+        ctx.author_member().await.and_then(|m| m.permissions).is_some_and(|p| p.manage_guild());
+    if !is_owner && !can_manage_guild {
+        return Err(CommandError::new("You need the Manage Server permission to register commands in this server."));
+    }
+
+    if remove.unwrap_or(false) {
+        info!("removing commands registered in guild {} for {} ({})", guild_id, user.name, user.id);
+        guild_id
+            .set_commands(ctx, Vec::new())
+            .await
+            .map_err(|e| CommandError::detailed("Unable to remove the commands registered in this server", e))?;
+
+        whisper(&ctx, REPLY_TITLE, "Removed the commands registered in this server.").await?;
+        return Ok(());
+    }
+
+    info!("registering commands in guild {} for {} ({})", guild_id, user.name, user.id);
+    register_guild_commands(&ctx.framework().options().commands, guild_id, ctx.http())
+        .await
+        .map_err(|e| CommandError::detailed("Unable to register commands in this server", e))?;
+
+    whisper(
+        &ctx,
+        REPLY_TITLE,
+        "Titi's latest commands are now registered in this server. If commands show up twice, use `/tt_register remove:True` once the update has reached every server.",
+    )
+    .await?;
+
+    Ok(())
+}
+
+/// Get the channel and optional role that reply notifications should be posted to for a server, if configured.
+pub(crate) async fn get_notification_target(
+    database: &Database,
+    guild_id: GuildId,
+) -> Option<(ChannelId, Option<RoleId>)> {
+    let channel = db::get_guild_setting(database, guild_id, GUILD_NOTIFICATION_CHANNEL)
+        .await
+        .ok()??
+        .value
+        .parse::<u64>()
+        .ok()?;
+
+    let role = db::get_guild_setting(database, guild_id, GUILD_NOTIFICATION_ROLE)
+        .await
+        .ok()
+        .flatten()
+        .and_then(|setting| setting.value.parse::<u64>().ok())
+        .map(RoleId::new);
+
+    Some((ChannelId::new(channel), role))
+}
@@ -1,39 +1,132 @@
-use serenity::utils::{Content, MessageBuilder};
+use chrono::Utc;
+use poise::CreateReply;
+use serde::Serialize;
+use serenity::builder::CreateAttachment;
 use tracing::info;
 
 use crate::{
-    commands::{CommandContext, CommandResult},
-    db,
-    messaging::reply,
+    commands::{CommandContext, CommandError, CommandResult},
+    db::{self, ServerStatistics, Statistics},
+    messaging::reply_fields,
+    utils::format_duration,
 };
 
+/// The bot's statistics in a machine-readable form, for comparing over time.
+#[derive(Serialize)]
+struct StatisticsDump {
+    generated_at: String,
+    servers_connected: usize,
+    totals: Statistics,
+    servers: Vec<ServerStatistics>,
+}
+
 /// Send the bot's statistics as a reply to the input context
 #[poise::command(prefix_command, dm_only, discard_spare_arguments, rename = "stats")]
 pub(crate) async fn send_statistics(ctx: CommandContext<'_>) -> CommandResult<()> {
     let data = ctx.data();
     let stats = db::statistics(&data.database).await?;
 
-    let mut message = MessageBuilder::new();
+    let (cache_hits, cache_misses, cache_entries) = data.message_cache.stats().await;
+    let cache_hit_rate = match cache_hits + cache_misses {
+        0 => "n/a".to_owned(),
+        lookups => format!("{:.1}%", cache_hits as f64 / lookups as f64 * 100.0),
+    };
 
-    write_stats_line(&mut message, "Unique Users", stats.users);
-    write_stats_line(&mut message, "Servers (Active)", stats.servers);
-    write_stats_line(&mut message, "Servers (Total)", data.guilds());
-    write_stats_line(&mut message, "Threads (Unique)", stats.threads_distinct);
-    write_stats_line(&mut message, "Threads (Total)", stats.threads_total);
-    write_stats_line(&mut message, "Muses", stats.muses);
-    write_stats_line(&mut message, "To Dos", stats.todos);
-    write_stats_line(&mut message, "Watchers", stats.watchers);
-    write_stats_line(&mut message, "Scheduled Messages", stats.scheduled_messages);
+    let fields = [
+        ("Unique Users", stats.users.to_string()),
+        ("Servers (Active)", stats.servers.to_string()),
+        ("Servers (Total)", data.guilds().to_string()),
+        ("Threads (Unique)", stats.threads_distinct.to_string()),
+        ("Threads (Total)", stats.threads_total.to_string()),
+        ("Muses", stats.muses.to_string()),
+        ("To Dos", stats.todos.to_string()),
+        ("Watchers", stats.watchers.to_string()),
+        ("Scheduled Messages", stats.scheduled_messages.to_string()),
+        ("Cache Entries", cache_entries.to_string()),
+        ("Cache Hits", cache_hits.to_string()),
+        ("Cache Misses", cache_misses.to_string()),
+        ("Cache Hit Rate", cache_hit_rate),
+    ];
 
     let user = ctx.author();
     info!("sending bot statistics to {} ({})", &user.name, user.id);
 
-    reply(&ctx, "Statistics", &message.build()).await?;
+    reply_fields(&ctx, "Statistics", fields).await?;
+
+    Ok(())
+}
+
+/// Send the bot's statistics, along with statistics for each server, as a JSON file
+#[poise::command(slash_command, prefix_command, dm_only, owners_only, hide_in_help, rename = "tt_statsdump")]
+pub(crate) async fn send_statistics_dump(ctx: CommandContext<'_>) -> CommandResult<()> {
+    let data = ctx.data();
+    let dump = StatisticsDump {
+        generated_at: Utc::now().to_rfc3339(),
+        servers_connected: data.guilds(),
+        totals: db::statistics(&data.database).await?,
+        servers: db::all_guild_statistics(&data.database).await?,
+    };
+
+    let json = match serde_json::to_vec_pretty(&dump) {
+        Ok(json) => json,
+        Err(e) => return Err(CommandError::detailed("Unable to serialize statistics", e)),
+    };
+
+    let user = ctx.author();
+    info!("sending bot statistics dump to {} ({})", &user.name, user.id);
+
+    let file_name = format!("statistics-{}.json", Utc::now().format("%Y-%m-%d"));
+    ctx.send(CreateReply::default().attachment(CreateAttachment::bytes(json, file_name))).await?;
+
+    Ok(())
+}
+
+/// Check that Titi is responding, and show the connection latency and uptime.
+#[poise::command(slash_command, prefix_command, rename = "tt_ping", category = "Bugs")]
+pub(crate) async fn ping(ctx: CommandContext<'_>) -> CommandResult<()> {
+    let latency = match ctx.ping().await {
+        latency if latency.is_zero() => "not yet measured".to_owned(),
+        latency => format!("{} ms", latency.as_millis()),
+    };
+
+    let fields = [
+        ("Shard", ctx.serenity_context().shard_id.to_string()),
+        ("Latency", latency),
+        ("Uptime", format_duration(ctx.data().uptime().as_secs() as i64)),
+        ("Database", db::pool_summary(&ctx.data().database)),
+    ];
+
+    let user = ctx.author();
+    info!("sending ping response to {} ({})", &user.name, user.id);
+
+    reply_fields(&ctx, "Pong!", fields).await?;
 
     Ok(())
 }
 
-/// Write a line to the message being built, in the format `- **<name>** - <value>`
-fn write_stats_line(msg: &mut MessageBuilder, name: impl Into<Content>, value: impl ToString) {
-    msg.push("- **").push(name).push("** — ").push_line(value.to_string());
+/// Show usage statistics for the current server.
+#[poise::command(slash_command, guild_only, rename = "tt_serverstats", category = "Server settings")]
+pub(crate) async fn send_guild_statistics(ctx: CommandContext<'_>) -> CommandResult<()> {
+    let guild_id = match ctx.guild_id() {
+        Some(id) => id,
+        None => return Err(CommandError::new("Unable to show server statistics outside of a server")),
+    };
+
+    let stats = db::guild_statistics(&ctx.data().database, guild_id.get()).await?;
+
+    let fields = [
+        ("Users", stats.users.to_string()),
+        ("Threads (Unique)", stats.threads_distinct.to_string()),
+        ("Threads (Total)", stats.threads_total.to_string()),
+        ("Muses", stats.muses.to_string()),
+        ("To Dos", stats.todos.to_string()),
+        ("Watchers", stats.watchers.to_string()),
+    ];
+
+    let user = ctx.author();
+    info!("sending statistics for guild {} to {} ({})", guild_id, &user.name, user.id);
+
+    reply_fields(&ctx, "Server statistics", fields).await?;
+
+    Ok(())
 }
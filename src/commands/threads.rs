@@ -1,25 +1,103 @@
-use std::{collections::{BTreeMap, BTreeSet, HashMap}, sync::Arc, cmp::Reverse};
+use std::{
+    borrow::Borrow,
+    cmp::Reverse,
+    collections::{BTreeMap, BTreeSet, HashMap, HashSet},
+    sync::{
+        atomic::{AtomicU8, Ordering},
+        Arc,
+    },
+};
 
+use chrono::{Datelike, Days, NaiveTime, TimeDelta, TimeZone, Utc, Weekday};
+use chrono_tz::Tz;
+use futures::stream::{self, StreamExt};
+use poise::ChoiceParameter;
 use rand::Rng;
 use serenity::{
     http::CacheHttp,
     model::prelude::*,
     prelude::*,
-    utils::{ContentModifier::*, EmbedMessageBuilding, MessageBuilder}, builder::GetMessages,
+    utils::{ContentModifier::*, EmbedMessageBuilding, MessageBuilder},
+    builder::{
+        CreateActionRow,
+        CreateButton,
+        CreateInteractionResponse,
+        CreateInteractionResponseMessage,
+        EditThread,
+        GetMessages,
+    },
 };
-use tracing::{error, info};
+use tokio::time::Instant;
+use tracing::{debug, error, info};
 
 use crate::{
-    cache::MessageCache,
-    commands::{muses, todos, CommandContext, CommandError, CommandResult, SortResultsBy},
-    consts::{setting_names::USER_SHOW_TIMESTAMPS, MAX_EMBED_CHARS, THREAD_NAME_LENGTH},
+    cache::{ActiveThreadsCache, MessageCache, UnavailableChannelCache},
+    category::Category,
+    commands::{
+        muses,
+        scheduling::{apply_repeat_duration, get_date_format, get_user_timezone},
+        server::get_notification_target,
+        todos,
+        CommandContext,
+        CommandError,
+        CommandResult,
+        SortResultsBy,
+        ThreadChannel,
+    },
+    consts::{
+        setting_names::{
+            USER_PREVIEW_LENGTH,
+            USER_QUIET_HOURS_END,
+            USER_QUIET_HOURS_START,
+            USER_DEFAULT_SORT,
+            USER_SHOW_TIMESTAMPS,
+            USER_BOLD_PENDING_ONLY,
+            USER_THREAD_NAME_LENGTH,
+            USER_WEEKLY_DIGEST,
+            USER_WEEKLY_DIGEST_LAST_SENT,
+        },
+        DEFAULT_WEEKLY_DIGEST_TIME,
+        LAST_REPLY_LOOKBACK,
+        MAX_LAST_REPLY_LOOKBACK,
+        MAX_ARCHIVED_THREADS_FETCHED,
+        MAX_CLEANUP_THREADS_LISTED,
+        MAX_CONCURRENT_THREAD_LOOKUPS,
+        MAX_EMBED_CHARS,
+        MAX_MESSAGE_CHARS,
+        MAX_TRACKED_THREADS_PER_USER,
+        MAX_TRACKERS_LISTED,
+        MIN_PREVIEW_LENGTH,
+        MAX_THREAD_NAME_LENGTH,
+        MIN_THREAD_NAME_LENGTH,
+        THREAD_NAME_LENGTH,
+        THREAD_LABEL_LENGTH,
+        THREAD_NOTE_LENGTH,
+        THREAD_UNDO_WINDOW,
+        TRACK_THREAD_BUTTON_ID,
+    },
     db::{self, add_subscriber, get_user_setting, remove_subscriber, Todo, TrackedThread},
-    messaging::{dm, reply, reply_error, send_invalid_command_call_error, whisper, whisper_error},
+    messaging::{
+        dm,
+        reply,
+        reply_error,
+        send_confirmation_prompt,
+        send_invalid_command_call_error,
+        send_role_mention_message,
+        whisper,
+        whisper_error,
+        ConfirmationResponse,
+    },
     utils::*,
     Data,
     Database,
 };
 
+/// Display and input format for quiet hours times.
+const QUIET_HOURS_FORMAT: &str = "%H:%M";
+
+/// Shown in place of the pending threads list when nothing is awaiting a reply.
+const NO_PENDING_THREADS: &str = "No tracked threads are currently awaiting replies.";
+
 struct LastReplyInfo {
     author: User,
     author_nick: String,
@@ -47,6 +125,71 @@ pub(crate) struct UserData {
     pub guild_id: GuildId,
     pub muses: Vec<String>,
     pub show_timestamps: bool,
+    pub bold_pending_only: bool,
+    pub thread_name_length: usize,
+    /// When the user last saw each thread in a list, as Unix timestamps.
+    pub last_seen: HashMap<ChannelId, i64>,
+}
+
+impl UserData {
+    /// Determine whether the reply was made by this user or one of their muses.
+    fn is_own_reply(&self, reply_info: &LastReplyInfo) -> bool {
+        reply_info.author.id == self.id
+            || muses::muse_matches(&reply_info.author, &reply_info.author_nick, &self.muses)
+    }
+
+    /// Determine whether someone else has replied to the thread since the user last saw it in a list.
+    fn is_unread(&self, channel_id: ChannelId, reply_info: &LastReplyInfo) -> bool {
+        self.last_seen.get(&channel_id).is_some_and(|&last_seen| {
+            !self.is_own_reply(reply_info) && reply_info.timestamp.unix_timestamp() > last_seen
+        })
+    }
+}
+
+/// Get when the user last saw each of the given threads in a list. Threads they haven't seen are left out.
+pub(crate) async fn get_last_seen(
+    database: &Database,
+    user_id: UserId,
+    channel_ids: &[i64],
+) -> HashMap<ChannelId, i64> {
+    match db::list_thread_views(database, user_id.get(), channel_ids).await {
+        Ok(views) => views.into_iter().map(|v| (v.channel_id(), v.last_seen)).collect(),
+        Err(e) => {
+            error!("Error looking up when {} last saw their threads: {}", user_id, e);
+            HashMap::new()
+        },
+    }
+}
+
+/// Get the positions the user has given their categories in a server, keyed by lowercase category name.
+async fn get_category_order(database: &Database, user_id: UserId, guild_id: GuildId) -> HashMap<String, i32> {
+    match db::list_category_order(database, guild_id.get(), user_id.get()).await {
+        Ok(order) => order.into_iter().map(|o| (o.category.to_lowercase(), o.sort_order)).collect(),
+        Err(e) => {
+            error!("Error looking up category order for {}: {}", user_id, e);
+            HashMap::new()
+        },
+    }
+}
+
+/// Sort key for categories in a list: threads without a category come first, then categories with a position in
+/// order, then the rest alphabetically.
+fn category_sort_key(name: &Option<String>, order: &HashMap<String, i32>) -> (bool, bool, i32, String) {
+    match name {
+        None => (false, false, 0, String::new()),
+        Some(name) => {
+            let name = name.to_lowercase();
+            let position = order.get(&name).copied();
+            (true, position.is_none(), position.unwrap_or_default(), name)
+        },
+    }
+}
+
+/// Record that the user has just seen the given threads in a list.
+async fn mark_threads_seen(database: &Database, user_id: UserId, channel_ids: &[i64]) {
+    if let Err(e) = db::mark_threads_seen(database, user_id.get(), channel_ids).await {
+        error!("Error recording that {} saw their threads: {}", user_id, e);
+    }
 }
 
 /// Get an iterator for the entries from the threads table for the given user.
@@ -69,26 +212,44 @@ pub(crate) async fn enumerate_tracked_channel_ids(
 }
 
 /// Add thread(s) to tracking.
-#[poise::command(slash_command, guild_only, rename = "tt_track", category = "Thread tracking")]
+#[poise::command(
+    slash_command,
+    prefix_command,
+    guild_only,
+    rename = "tt_track",
+    aliases("track"),
+    category = "Thread tracking"
+)]
 pub(crate) async fn add(
     ctx: CommandContext<'_>,
     #[description = "The threads or channel to track"]
     #[channel_types("NewsThread", "PrivateThread", "PublicThread", "Text")]
-    thread: GuildChannel,
+    thread: ThreadChannel,
     #[description = "The category to track the thread under"] category: Option<String>,
 ) -> CommandResult<()> {
     const ERROR_TITLE: &str = "Error adding tracked thread";
+    let ThreadChannel(thread) = thread;
 
     let guild_id = match ctx.guild_id() {
         Some(id) => id,
         None => return Err(CommandError::new("Unable to track threads outside of a server")),
     };
 
+    let category = Category::from_argument(category.as_deref())?;
+
     let user = ctx.author();
 
     let data = ctx.data();
     let (database, message_cache) = (&data.database, &data.message_cache);
 
+    let tracked_count = db::count_threads(database, user.id.get()).await?;
+    if tracked_count >= MAX_TRACKED_THREADS_PER_USER {
+        return Err(CommandError::new(format!(
+            "You are already tracking {} threads, which is the maximum of {}. Please untrack some threads before adding more.",
+            tracked_count, MAX_TRACKED_THREADS_PER_USER
+        )));
+    }
+
     let mut threads_added = MessageBuilder::new();
     let mut errors = MessageBuilder::new();
 
@@ -98,8 +259,7 @@ pub(crate) async fn add(
             cache_last_channel_message(channel.guild().as_ref(), ctx, message_cache).await;
 
             let result =
-                db::add_thread(database, guild_id.get(), thread.id.get(), user.id.get(), category.as_deref())
-                    .await;
+                db::add_thread(database, guild_id.get(), thread.id.get(), user.id.get(), category.as_ref()).await;
             match result {
                 Ok(true) => {
                     data.add_tracked_thread(thread.id).await;
@@ -138,98 +298,162 @@ pub(crate) async fn add(
     Ok(())
 }
 
-/// Change the category of an already tracked thread.
-#[poise::command(slash_command, guild_only, rename = "tt_category", category = "Thread tracking")]
-pub(crate) async fn set_category(
+/// Track every thread in a forum or channel at once.
+#[poise::command(
+    slash_command,
+    guild_only,
+    user_cooldown = 5,
+    rename = "tt_trackforum",
+    category = "Thread tracking"
+)]
+pub(crate) async fn track_forum(
     ctx: CommandContext<'_>,
-    #[description = "The thread or channel to update category for"]
-    #[channel_types("NewsThread", "PrivateThread", "PublicThread", "Text")]
-    thread: GuildChannel,
-    #[description = "The category to assign to the thread, if any"] category: Option<String>,
+    #[description = "The forum or channel whose threads should be tracked"]
+    #[channel_types("Forum", "Text", "News")]
+    channel: GuildChannel,
+    #[description = "The category to track the threads under"] category: Option<String>,
+    #[description = "Also track recently archived threads"] include_archived: Option<bool>,
 ) -> CommandResult<()> {
-    const ERROR_TITLE: &str = "Error updating tracked thread category";
     let guild_id = match ctx.guild_id() {
         Some(id) => id,
-        None => {
-            return Err(CommandError::new("Unable to managed tracked threads outside of a server"))
-        },
+        None => return Err(CommandError::new("Unable to track threads outside of a server")),
     };
 
+    let category = Category::from_argument(category.as_deref())?;
+
     let user = ctx.author();
-    let database = &ctx.data().database;
+    let data = ctx.data();
+    let database = &data.database;
 
-    let mut threads_updated = MessageBuilder::new();
-    let mut errors = MessageBuilder::new();
+    info!("tracking all threads in channel {} for {} ({})", channel.id, user.name, user.id);
+
+    let active_threads = data
+        .active_threads_cache
+        .get_or_else(&guild_id, || async {
+            get_active_threads(guild_id, ctx).await
+        })
+        .await?;
+    let mut thread_ids: Vec<ChannelId> =
+        active_threads.iter().filter(|t| t.parent_id == Some(channel.id)).map(|t| t.id).collect();
+
+    let mut more_archived = false;
+    if include_archived.unwrap_or(false) {
+        let archived =
+            channel.id.get_archived_public_threads(ctx.http(), None, Some(MAX_ARCHIVED_THREADS_FETCHED)).await?;
+        more_archived = archived.has_more;
+        thread_ids.extend(archived.threads.iter().map(|t| t.id));
+    }
 
-    info!(
-        "updating category for thread `{}` to `{}`",
-        thread.id,
-        category.as_deref().unwrap_or("none")
-    );
-    match thread.id.to_channel(ctx).await {
-        Ok(_) => match db::update_thread_category(
-            database,
-            guild_id.get(),
-            thread.id.get(),
-            user.id.get(),
-            category.as_deref(),
-        )
-        .await
-        {
-            Ok(true) => threads_updated.push("- ").mention(&thread.id).push_line(""),
-            Ok(false) => {
-                errors.push("- ").mention(&thread.id).push_line(" is not currently being tracked")
-            },
-            Err(e) => errors
-                .push("- Failed to update thread category for ")
-                .mention(&thread.id)
-                .push_line_safe(format!(": {}", e)),
+    let tracked: HashSet<ChannelId> = db::list_threads(database, guild_id.get(), user.id.get(), None)
+        .await?
+        .iter()
+        .map(|t| t.channel_id())
+        .collect();
+    let (already_tracked, mut to_track): (Vec<ChannelId>, Vec<ChannelId>) =
+        thread_ids.into_iter().partition(|id| tracked.contains(id));
+
+    let remaining = (MAX_TRACKED_THREADS_PER_USER - db::count_threads(database, user.id.get()).await?).max(0);
+    let over_limit = to_track.len().saturating_sub(remaining as usize);
+    to_track.truncate(remaining as usize);
+
+    let ids: Vec<u64> = to_track.iter().map(|id| id.get()).collect();
+    let added = db::add_threads_bulk(database, guild_id.get(), user.id.get(), &ids, category.as_ref()).await?;
+    for &id in &added {
+        data.add_tracked_thread(ChannelId::new(id)).await;
+    }
+
+    let mut message = MessageBuilder::new();
+    message.push(format!("Tracked {} new thread(s) from ", added.len())).mention(&channel.id);
+    match &category {
+        Some(name) => message.push(" in ").push(Bold + name.as_str()).push_line("."),
+        None => message.push_line("."),
+    };
+
+    if !already_tracked.is_empty() {
+        message.push_line(format!("Skipped {} thread(s) you were already tracking.", already_tracked.len()));
+    }
+
+    if over_limit > 0 {
+        message.push_line(format!(
+            "Skipped {} thread(s) as you can track at most {} threads.",
+            over_limit, MAX_TRACKED_THREADS_PER_USER
+        ));
+    }
+
+    if more_archived {
+        message.push_line(format!(
+            "Only the {} most recently archived threads were checked.",
+            MAX_ARCHIVED_THREADS_FETCHED
+        ));
+    }
+
+    reply(&ctx, "Tracked threads added", &message.build()).await?;
+
+    Ok(())
+}
+
+/// Find and remove duplicate entries in your tracked threads.
+#[poise::command(slash_command, guild_only, rename = "tt_dedupe", category = "Thread tracking")]
+pub(crate) async fn dedupe(ctx: CommandContext<'_>) -> CommandResult<()> {
+    const REPLY_TITLE: &str = "Duplicate threads";
+    let guild_id = match ctx.guild_id() {
+        Some(id) => id,
+        None => {
+            return Err(CommandError::new("Unable to manage tracked threads outside of a server"))
         },
-        Err(e) => errors
-            .push("- Cannot access channel ")
-            .mention(&thread.id)
-            .push_line(format!(": {}", e)),
     };
 
-    if !errors.0.is_empty() {
-        error!("Errors updating thread categories:\n{}", errors);
-        reply_error(&ctx, ERROR_TITLE, &errors.build()).await?;
+    let user = ctx.author();
+    let database = &ctx.data().database;
+
+    info!("removing duplicate threads for {} ({})", user.name, user.id);
+
+    let duplicates = db::find_duplicate_threads(database, guild_id.get(), user.id.get()).await?;
+    if duplicates.is_empty() {
+        whisper(&ctx, REPLY_TITLE, "None of your tracked threads have duplicate entries.").await?;
+        return Ok(());
     }
 
-    if !threads_updated.0.is_empty() {
-        let title = match category {
-            Some(name) => format!("Tracked threads' category set to `{}`", name),
-            None => String::from("Tracked threads' categories removed"),
-        };
+    let removed = db::dedupe_threads(database, guild_id.get(), user.id.get()).await?;
 
-        reply(&ctx, &title, &threads_updated.build()).await?;
+    let mut message = MessageBuilder::new();
+    for duplicate in duplicates.iter().take(MAX_CLEANUP_THREADS_LISTED) {
+        message.push("- ").mention(&duplicate.channel_id()).push_line(format!(" ({} entries)", duplicate.copies));
+    }
+
+    if duplicates.len() > MAX_CLEANUP_THREADS_LISTED {
+        message.push_line(format!("...and {} more", duplicates.len() - MAX_CLEANUP_THREADS_LISTED));
     }
 
+    message.push_line("").push(format!("Removed {} duplicate entries.", removed));
+
+    reply(&ctx, REPLY_TITLE, &message.build()).await?;
+
     Ok(())
 }
 
-/// Remove threads from tracking.
+/// Move tracked threads to a different category.
 #[poise::command(
     slash_command,
     guild_only,
-    rename = "tt_untrack",
+    rename = "tt_movethread",
     category = "Thread tracking",
-    subcommands("untrack_thread", "untrack_category")
+    subcommands("move_thread", "move_category")
 )]
-pub(crate) async fn untrack(ctx: CommandContext<'_>) -> CommandResult<()> {
+pub(crate) async fn move_threads(ctx: CommandContext<'_>) -> CommandResult<()> {
     send_invalid_command_call_error(ctx).await
 }
 
-/// Remove an individual thread from tracking.
+/// Move a single tracked thread to a different category.
 #[poise::command(slash_command, guild_only, rename = "thread")]
-pub(crate) async fn untrack_thread(
+pub(crate) async fn move_thread(
     ctx: CommandContext<'_>,
-    #[description = "The thread or channel to remove from tracking"]
+    #[description = "The tracked thread or channel to move"]
     #[channel_types("NewsThread", "PrivateThread", "PublicThread", "Text")]
     thread: GuildChannel,
+    #[description = "The category to move the thread to; leave empty to remove its category"]
+    destination: Option<String>,
 ) -> CommandResult<()> {
-    const ERROR_TITLE: &str = "Error adding tracked thread";
-
     let guild_id = match ctx.guild_id() {
         Some(id) => id,
         None => {
@@ -237,52 +461,55 @@ pub(crate) async fn untrack_thread(
         },
     };
 
-    let data = ctx.data();
-    let database = &data.database;
     let user = ctx.author();
+    let database = &ctx.data().database;
+    let destination = Category::from_argument(destination.as_deref())?;
 
-    let mut threads_removed = MessageBuilder::new();
-    let mut errors = MessageBuilder::new();
+    info!(
+        "moving thread {} to category `{}` for {} ({})",
+        thread.id,
+        destination.as_ref().map_or("none", Category::as_str),
+        user.name,
+        user.id
+    );
 
-    info!("removing tracked thread `{}` for {} ({})", thread.id, user.name, user.id);
-    let result = db::remove_thread(database, guild_id.get(), thread.id.get(), user.id.get()).await;
+    let moved = db::update_thread_category(
+        database,
+        guild_id.get(),
+        thread.id.get(),
+        user.id.get(),
+        destination.as_ref(),
+    )
+    .await?;
+
+    if moved == 0 {
+        return Err(CommandError::new(format!(
+            "{} is not currently being tracked, so it can't be moved.",
+            thread.id.mention()
+        )));
+    }
 
-    match result {
-        Ok(0) => {
-            errors.push_line(format!("- {} is not currently being tracked", thread.id.mention()))
-        },
-        Ok(_) => {
-            data.remove_tracked_thread(thread.id).await.ok();
-            threads_removed.push_line(format!("- {:}", thread.id.mention()))
-        },
-        Err(e) => errors.push_line(format!(
-            "- Failed to unregister thread {}: {}",
-            thread.id.mention(),
-            e
-        )),
+    let mut message = MessageBuilder::new();
+    message.mention(&thread.id);
+    match &destination {
+        Some(category) => message.push(" moved to ").push(Bold + category.as_str()),
+        None => message.push(" is no longer in a category."),
     };
 
-    if !errors.0.is_empty() {
-        error!("Errors handling thread removal:\n{}", errors);
-        reply_error(&ctx, ERROR_TITLE, &errors.build()).await?;
-    }
-
-    if !threads_removed.0.is_empty() {
-        reply(&ctx, "Tracked threads removed", &threads_removed.build()).await?;
-    }
+    reply(&ctx, "Tracked thread moved", &message.build()).await?;
 
     Ok(())
 }
 
-/// Remove all threads in the selected category from tracking.
+/// Move every thread in one category to another category.
 #[poise::command(slash_command, guild_only, rename = "category")]
-pub(crate) async fn untrack_category(
+pub(crate) async fn move_category(
     ctx: CommandContext<'_>,
-    #[description = "Category to untrack all threads from; use 'all' to untrack everything"]
-    name: String,
+    #[description = "The category to move threads from; leave empty to move uncategorised threads"]
+    source: Option<String>,
+    #[description = "The category to move threads to; leave empty to remove their category"]
+    destination: Option<String>,
 ) -> CommandResult<()> {
-    const ERROR_TITLE: &str = "Error adding tracked thread";
-
     let guild_id = match ctx.guild_id() {
         Some(id) => id,
         None => {
@@ -290,56 +517,49 @@ pub(crate) async fn untrack_category(
         },
     };
 
-    let data = ctx.data();
-    let database = &data.database;
     let user = ctx.author();
+    let database = &ctx.data().database;
 
-    let mut threads_removed = MessageBuilder::new();
-    let mut errors = MessageBuilder::new();
-
-    let (category, category_message) = match name.to_lowercase().as_str() {
-        "all" => (None, String::new()),
-        _ => (Some(name.as_str()), format!(" in category {}", name)),
-    };
+    let source = Category::from_argument(source.as_deref())?;
+    let destination = Category::from_argument(destination.as_deref())?;
+    let source_name = source.as_ref().map_or("uncategorised", Category::as_str);
+    let destination_name = destination.as_ref().map_or("uncategorised", Category::as_str);
 
-    info!("removing all tracked threads{} for {} ({})", category_message, user.name, user.id);
-    match db::remove_all_threads(database, guild_id.get(), user.id.get(), category).await {
-        Ok(0) => threads_removed
-            .push_line(format!("No threads are currently being tracked{}.", category_message)),
-        Ok(count) => threads_removed
-            .push_line(format!("All {} threads{} removed from tracking.", count, category_message)),
-        Err(e) => {
-            error!(
-                "Error untracking all threads{} for user {} ({}): {}",
-                category_message, user.name, user.id, e
-            );
-            errors.push_line(format!("Error untracking all threads{}: {}", category_message, e))
-        },
-    };
+    if source == destination {
+        return Err(CommandError::new("The source and destination categories are the same."));
+    }
 
-    if let Err(e) = data.update_tracked_threads().await {
-        error!("Error updating in-memory list of tracked threads: {}", e)
-    };
+    info!(
+        "moving threads from `{}` to `{}` for {} ({})",
+        source_name, destination_name, user.name, user.id
+    );
 
-    if !errors.0.is_empty() {
-        error!("Errors handling thread removal:\n{}", errors);
-        reply_error(&ctx, ERROR_TITLE, &errors.build()).await?;
+    let moved = db::move_category_threads(
+        database,
+        guild_id.get(),
+        user.id.get(),
+        source.as_ref().map(Category::as_str),
+        destination.as_ref(),
+    )
+    .await?;
+
+    if moved == 0 {
+        return Err(CommandError::new(format!("No tracked threads found in `{}`.", source_name)));
     }
 
-    if !threads_removed.0.is_empty() {
-        reply(&ctx, "Tracked threads removed", &threads_removed.build()).await?;
-    }
+    reply(
+        &ctx,
+        "Tracked threads moved",
+        &format!("Moved {} thread(s) from `{}` to `{}`.", moved, source_name, destination_name),
+    )
+    .await?;
 
     Ok(())
 }
 
-/// Show the list of all tracked threads.
-#[poise::command(slash_command, guild_only, rename = "tt_threads", category = "Thread tracking")]
-pub(crate) async fn send_list(
-    ctx: CommandContext<'_>,
-    #[description = "Only show threads from this category"] category: Option<String>,
-    #[description = "How to sort the threads in the list, based on the most recent reply"] sort: Option<SortResultsBy>,
-) -> CommandResult<()> {
+/// Restore threads you untracked in the last few minutes.
+#[poise::command(slash_command, guild_only, rename = "tt_undo", category = "Thread tracking")]
+pub(crate) async fn undo_untrack(ctx: CommandContext<'_>) -> CommandResult<()> {
     let guild_id = match ctx.guild_id() {
         Some(id) => id,
         None => {
@@ -347,26 +567,61 @@ pub(crate) async fn send_list(
         },
     };
 
-    ctx.defer().await?;
+    let user = ctx.author();
+    let data = ctx.data();
 
-    let title = "Currently tracked threads";
+    let remaining = (MAX_TRACKED_THREADS_PER_USER - db::count_threads(&data.database, user.id.get()).await?).max(0);
 
-    let threads_list =
-        get_threads_and_todos(ctx.author(), guild_id, category.as_deref(), sort, ctx.data(), &ctx)
-            .await?;
+    info!("restoring recently untracked threads for {} ({})", user.name, user.id);
+    let restored =
+        db::restore_threads(&data.database, guild_id.get(), user.id.get(), THREAD_UNDO_WINDOW, remaining).await?;
+
+    if restored.is_empty() {
+        if remaining == 0 {
+            return Err(CommandError::new(format!(
+                "You are already tracking the maximum of {} threads. Please untrack some threads before restoring any.",
+                MAX_TRACKED_THREADS_PER_USER
+            )));
+        }
+
+        return Err(CommandError::new(format!(
+            "You haven't untracked any threads in the last {} minutes.",
+            THREAD_UNDO_WINDOW.as_secs() / 60
+        )));
+    }
+
+    let limit_reached = restored.len() as i64 >= remaining;
+
+    let mut message = MessageBuilder::new();
+    for channel_id in restored {
+        let channel_id = ChannelId::new(channel_id);
+        data.add_tracked_thread(channel_id).await;
+        message.push("- ").mention(&channel_id).push_line("");
+    }
+
+    if limit_reached {
+        message.push_line("").push_line(format!(
+            "You can track at most {} threads, so any others you untracked were not restored.",
+            MAX_TRACKED_THREADS_PER_USER
+        ));
+    }
 
-    reply(&ctx, title, &threads_list).await?;
+    reply(&ctx, "Tracked threads restored", &message.build()).await?;
 
     Ok(())
 }
 
-/// Show the list of tracked threads currently pending replies.
-#[poise::command(slash_command, guild_only, rename = "tt_replies", category = "Thread tracking")]
-pub(crate) async fn send_pending_list(
+/// Find tracked threads which can no longer be accessed, and untrack them once you confirm.
+#[poise::command(slash_command, guild_only, rename = "tt_cleanup", category = "Thread tracking")]
+pub(crate) async fn cleanup(
     ctx: CommandContext<'_>,
-    #[description = "Only show threads from this category"] category: Option<String>,
-    #[description = "How to sort the threads in the list, based on the most recent reply"] sort: Option<SortResultsBy>,
+    #[description = "Only list the threads that would be untracked, without removing anything"] dry_run: Option<bool>,
+    #[description = "Also untrack archived threads (default: off)"] include_archived: Option<bool>,
+    #[description = "Also untrack threads Titi doesn't have permission to see (default: off)"]
+    include_forbidden: Option<bool>,
 ) -> CommandResult<()> {
+    const REPLY_TITLE: &str = "Clean up tracked threads";
+
     let guild_id = match ctx.guild_id() {
         Some(id) => id,
         None => {
@@ -376,157 +631,224 @@ pub(crate) async fn send_pending_list(
 
     ctx.defer().await?;
 
-    let threads_list =
-        get_pending_thread_list(ctx.author(), guild_id, category.as_deref(), sort, ctx.data(), &ctx)
-            .await?;
+    let user = ctx.author();
+    let data = ctx.data();
+    let dry_run = dry_run.unwrap_or(false);
 
-    reply(&ctx, "Threads awaiting replies", &threads_list).await?;
+    info!("checking for inaccessible threads for {} ({}), dry run: {}", user.name, user.id, dry_run);
 
-    Ok(())
-}
+    let threads = db::list_threads(&data.database, guild_id.get(), user.id.get(), None).await?;
+    let mut lookups = Vec::new();
+    for thread in threads {
+        lookups.push(check_thread_access(thread, &ctx));
+    }
 
-/// Get the list of threads and todos.
-pub(crate) async fn get_threads_and_todos(
-    user: &User,
-    guild_id: GuildId,
-    category: Option<&str>,
-    sort: Option<SortResultsBy>,
-    data: &Data,
-    context: &impl CacheHttp,
-) -> CommandResult<String> {
-    info!("Getting tracked threads and todo list for {} ({})", user.name, user.id);
+    let results: Vec<(ThreadAccess, TrackedThread)> =
+        stream::iter(lookups).buffered(MAX_CONCURRENT_THREAD_LOOKUPS).collect().await;
+    let mut results = partition_into_map(results, |(access, _)| *access);
+    results.remove(&ThreadAccess::Accessible);
 
-    let guild_user = GuildUser { user_id: user.id, guild_id };
+    if results.is_empty() {
+        whisper(&ctx, REPLY_TITLE, "All of your tracked threads can still be found.").await?;
+        return Ok(());
+    }
 
-    let mut threads: Vec<TrackedThread> = Vec::new();
-    let mut todos: Vec<Todo> = Vec::new();
+    let mut selected = vec![ThreadAccess::Deleted];
+    if include_archived.unwrap_or(false) {
+        selected.push(ThreadAccess::Archived);
+    }
 
-    match enumerate(&data.database, &guild_user, category).await {
-        Ok(t) => threads.extend(t),
-        Err(e) => {
-            error!("Error listing tracked threads for {}: {}", user.name, e);
-            return Err(CommandError::detailed(
-                format!("Error listing tracked threads for {}", user.name),
-                e,
-            ));
-        },
+    if include_forbidden.unwrap_or(false) {
+        selected.push(ThreadAccess::Forbidden);
     }
 
-    match todos::enumerate(&data.database, &guild_user, category).await {
-        Ok(t) => todos.extend(t),
-        Err(e) => {
-            error!("Error listing todos for {}: {}", user.name, e);
-            return Err(CommandError::detailed(
-                format!("Error listing todos for {}", user.name),
-                e,
-            ));
+    let mut message = MessageBuilder::new();
+    for (access, threads) in &results {
+        message.push("### ").push_line(access.heading());
+        if !selected.contains(access) {
+            message.push_line(format!("-# {}", access.opt_in_hint()));
+        }
+
+        for (_, thread) in threads.iter().take(MAX_CLEANUP_THREADS_LISTED) {
+            message.push("- ").mention(&thread.channel_id()).push_line("");
+        }
+
+        if threads.len() > MAX_CLEANUP_THREADS_LISTED {
+            message.push_line(format!("…and {} more", threads.len() - MAX_CLEANUP_THREADS_LISTED));
+        }
+
+        message.push_line("");
+    }
+
+    let to_remove: Vec<&TrackedThread> = selected
+        .iter()
+        .filter_map(|access| results.get(access))
+        .flatten()
+        .map(|(_, thread)| thread)
+        .collect();
+
+    if dry_run || to_remove.is_empty() {
+        whisper(&ctx, REPLY_TITLE, &message.build()).await?;
+        return Ok(());
+    }
+
+    message.push(format!("Would you like to untrack {} thread(s)?", to_remove.len()));
+
+    match send_confirmation_prompt(&ctx, REPLY_TITLE, &message.build(), user.id).await? {
+        ConfirmationResponse::Confirmed => {
+            let mut removed = 0;
+            for thread in to_remove {
+                removed += db::remove_thread(&data.database, guild_id.get(), thread.channel_id, user.id.get()).await?;
+                data.remove_tracked_thread(thread.channel_id()).await.ok();
+            }
+
+            info!("cleaned up {} inaccessible threads for {} ({})", removed, user.name, user.id);
+            reply(
+                &ctx,
+                "Tracked threads cleaned up",
+                &format!("Untracked {} thread(s). Use `/tt_undo` to restore them if needed.", removed),
+            )
+            .await?;
+        },
+        ConfirmationResponse::Denied => {
+            whisper(&ctx, REPLY_TITLE, "No threads were untracked.").await?;
+        },
+        ConfirmationResponse::TimedOut => {
+            whisper(&ctx, REPLY_TITLE, "The cleanup timed out. No threads were untracked.").await?;
         },
     }
 
-    let muses = match muses::get_list(&data.database, guild_user.user_id, guild_user.guild_id).await
-    {
-        Ok(m) => m,
-        Err(e) => {
-            error!("Error finding muse list for {}: {}", user.name, e);
-            return Err(CommandError::detailed(
-                format!("Error finding muse list for {}", user.name),
-                e,
-            ));
+    Ok(())
+}
+
+/// Unarchive an archived thread and make sure you're tracking it.
+#[poise::command(slash_command, guild_only, rename = "tt_revive", category = "Thread tracking")]
+pub(crate) async fn revive(
+    ctx: CommandContext<'_>,
+    #[description = "The archived thread to unarchive"]
+    #[channel_types("NewsThread", "PrivateThread", "PublicThread")]
+    mut thread: GuildChannel,
+    #[description = "The category to track the thread under, if you aren't already tracking it"] category: Option<String>,
+) -> CommandResult<()> {
+    const REPLY_TITLE: &str = "Thread revived";
+
+    let guild_id = match ctx.guild_id() {
+        Some(id) => id,
+        None => {
+            return Err(CommandError::new("Unable to manage tracked threads outside of a server"))
         },
     };
 
-    let user_data = UserData {
-        id: guild_user.user_id,
-        guild_id: guild_user.guild_id,
-        muses,
-        show_timestamps: show_timestamps(&data.database, guild_user.user_id).await,
-    };
+    if !thread.thread_metadata.is_some_and(|m| m.archived) {
+        return Err(CommandError::new(format!("{} isn't archived.", thread.id.mention())));
+    }
 
-    let message =
-        match get_formatted_list(threads, todos, sort, context, &data.message_cache, &user_data)
-            .await
-        {
-            Ok(m) => m,
-            Err(e) => {
-                error!("Error collating tracked threads for {}: {}", user.name, e);
-                return Err(CommandError::detailed(
-                    format!("Error collating tracked threads for {}", user.name),
-                    e,
-                ));
-            },
-        };
+    let category = Category::from_argument(category.as_deref())?;
+    let user = ctx.author();
+    let data = ctx.data();
 
-    Ok(message)
-}
+    let tracked = db::get_thread(&data.database, guild_id.get(), thread.id.get(), user.id.get()).await?.is_some();
+    if !tracked {
+        let tracked_count = db::count_threads(&data.database, user.id.get()).await?;
+        if tracked_count >= MAX_TRACKED_THREADS_PER_USER {
+            return Err(CommandError::new(format!(
+                "You are already tracking {} threads, which is the maximum of {}. Please untrack some threads before adding more.",
+                tracked_count, MAX_TRACKED_THREADS_PER_USER
+            )));
+        }
+    }
 
-/// Get the list of threads pending reply.
-pub(crate) async fn get_pending_thread_list(
-    user: &User,
-    guild_id: GuildId,
-    category: Option<&str>,
-    sort_threads: Option<SortResultsBy>,
-    data: &Data,
-    context: &impl CacheHttp,
-) -> CommandResult<String> {
-    info!("Getting pending threads list for {} ({})", user.name, user.id);
+    info!("reviving thread {} for {} ({})", thread.id, user.name, user.id);
 
-    let pending_threads = get_pending_threads(category, user, guild_id, context, data).await?;
+    match thread.edit_thread(ctx, EditThread::new().archived(false)).await {
+        Ok(_) => {},
+        Err(e) if is_forbidden(&e) => {
+            return Err(CommandError::new(format!(
+                "Titi doesn't have permission to unarchive {}. Ask a moderator to unarchive it, or to give Titi the Manage Threads permission.",
+                thread.id.mention()
+            )))
+        },
+        Err(e) => return Err(CommandError::detailed(format!("Unable to unarchive {}", thread.id.mention()), e)),
+    }
 
-    let categorised_threads = partition_into_map(pending_threads, |item| item.1.category.clone());
+    let mut message = MessageBuilder::new();
+    message.mention(&thread.id).push(" has been unarchived");
 
-    let show_timestamps: bool = show_timestamps(&data.database, user.id).await;
+    let added =
+        db::add_thread(&data.database, guild_id.get(), thread.id.get(), user.id.get(), category.as_ref()).await?;
+    if added {
+        data.add_tracked_thread(thread.id).await;
+        message.push(" and added to your tracked threads");
+    }
 
-    let mut message = MessageBuilder::new();
+    message.push_line(".");
+    reply(&ctx, REPLY_TITLE, &message.build()).await?;
 
-    for (name, mut threads) in categorised_threads {
-        if let Some(n) = name {
-            message.push("### ").push_line(n).push_line("");
-        }
+    Ok(())
+}
 
-        if let Some(sort_order) = sort_threads {
-            match sort_order {
-                SortResultsBy::NewestFirst => {
-                    threads.sort_by_key(|item| Reverse(item.0.timestamp));
-                },
-                SortResultsBy::OldestFirst => {
-                    threads.sort_by_key(|item| item.0.timestamp);
-                }
-            }
-        }
+/// How a tracked thread's channel can be accessed, if at all.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum ThreadAccess {
+    /// The thread was found and is open.
+    Accessible,
+    /// Discord reports the thread no longer exists.
+    Deleted,
+    /// The thread was found, but it's archived. It may be unarchived later.
+    Archived,
+    /// Titi doesn't have permission to see the thread.
+    Forbidden,
+    /// The thread couldn't be checked, most likely due to a temporary Discord issue.
+    Unknown,
+}
 
-        for (reply_info, thread) in threads {
-            let link = get_thread_link(&thread, None, context).await;
-            message.push("- ").push(link.to_string()).push(" — ").push(Bold + &reply_info.author_nick);
-            if show_timestamps {
-                message.push(" (")
-                    .push_timestamp(reply_info.timestamp)
-                    .push_line(")");
-            }
-            else {
-                message.push_line("");
-            }
+impl ThreadAccess {
+    /// The heading for a group of threads with this access in the cleanup report.
+    fn heading(&self) -> &'static str {
+        match self {
+            ThreadAccess::Accessible => "Available",
+            ThreadAccess::Deleted => "Deleted",
+            ThreadAccess::Archived => "Archived",
+            ThreadAccess::Forbidden => "No access",
+            ThreadAccess::Unknown => "Could not be checked",
         }
-
-        message.push_line("");
     }
 
-    if message.0.is_empty() {
-        message.push_line("No tracked threads are currently awaiting replies.");
+    /// Explains how to include a group of threads with this access in the cleanup.
+    fn opt_in_hint(&self) -> &'static str {
+        match self {
+            ThreadAccess::Archived => "Not untracked unless `include_archived` is set. Use `/tt_revive` to unarchive them.",
+            ThreadAccess::Forbidden => "Not untracked unless `include_forbidden` is set.",
+            _ => "Not untracked. Try again later.",
+        }
     }
+}
 
-    Ok(message.build())
+/// Check whether a tracked thread's channel can still be accessed.
+async fn check_thread_access(thread: TrackedThread, context: &impl CacheHttp) -> (ThreadAccess, TrackedThread) {
+    let access = match thread.channel_id().to_channel(context).await {
+        Ok(Channel::Guild(channel)) if channel.thread_metadata.is_some_and(|m| m.archived) => ThreadAccess::Archived,
+        Ok(_) => ThreadAccess::Accessible,
+        Err(e) if is_not_found(&e) => ThreadAccess::Deleted,
+        Err(e) if is_forbidden(&e) => ThreadAccess::Forbidden,
+        Err(e) => {
+            debug!("unable to check access to thread {}: {}", thread.channel_id, e);
+            ThreadAccess::Unknown
+        },
+    };
+
+    (access, thread)
 }
 
-/// Select and send a random thread to the user that is awaiting their reply.
-#[poise::command(slash_command, guild_only, category = "Thread tracking", rename = "tt_random")]
-pub(crate) async fn send_random_thread(
+/// Set or clear a private note for a tracked thread.
+#[poise::command(slash_command, guild_only, rename = "tt_note", category = "Thread tracking")]
+pub(crate) async fn set_note(
     ctx: CommandContext<'_>,
-    #[description = "Only pick from threads in this category"] category: Option<String>,
+    #[description = "The thread or channel to add a note to"]
+    #[channel_types("NewsThread", "PrivateThread", "PublicThread", "Text")]
+    thread: GuildChannel,
+    #[description = "The note to show with the thread; leave empty to clear it"] note: Option<String>,
 ) -> CommandResult<()> {
-    const ERROR_TITLE: &str = "Error fetching tracked threads";
-
-    ctx.defer().await?;
-
     let guild_id = match ctx.guild_id() {
         Some(id) => id,
         None => {
@@ -535,224 +857,2278 @@ pub(crate) async fn send_random_thread(
     };
 
     let user = ctx.author();
+    let database = &ctx.data().database;
 
-    let mut message = MessageBuilder::new();
-    let mut errors = MessageBuilder::new();
+    let note = note.as_deref().map(str::trim).filter(|n| !n.is_empty());
+    if let Some(note) = note {
+        if note.chars().count() > THREAD_NOTE_LENGTH {
+            return Err(CommandError::new(format!(
+                "Notes can be at most {} characters long.",
+                THREAD_NOTE_LENGTH
+            )));
+        }
+    }
 
-    info!("sending a random thread for {} ({})", user.name, user.id);
+    info!("setting note for thread {} for {} ({})", thread.id, user.name, user.id);
+    let updated =
+        db::set_thread_note(database, guild_id.get(), thread.id.get(), user.id.get(), note).await?;
 
-    match get_random_thread(category.as_deref(), user, guild_id, &ctx).await {
-        Ok(None) => {
-            message.push("Congrats! You don't seem to have any threads that are waiting on your reply! :tada:");
-        },
-        Ok(Some((reply_info, thread))) => {
-            message.push("Titi has chosen... this thread");
+    if !updated {
+        return Err(CommandError::new(format!("{} is not currently being tracked", thread.id.mention())));
+    }
 
-            if let Some(category) = &thread.category {
-                message
-                    .push(" from your ")
-                    .push(Bold + Underline + category)
-                    .push_line(" threads!");
-            }
-            else {
-                message.push_line("!");
-            }
+    let mut message = MessageBuilder::new();
+    match note {
+        Some(note) => message.push("Note for ").mention(&thread.id).push(" set to: ").push_safe(note),
+        None => message.push("Note for ").mention(&thread.id).push(" cleared."),
+    };
 
-            message.push_line("");
-            message
-                .push_quote(get_thread_link(&thread, None, &ctx).await.build())
-                .push(" — ")
-                .push_line(Bold + reply_info.author_nick);
-        },
-        Err(e) => {
-            errors.push("- ").push_line(e.to_string());
+    whisper(&ctx, "Thread note", &message.build()).await?;
+
+    Ok(())
+}
+
+/// Set or clear the name shown for a tracked thread in your lists.
+#[poise::command(slash_command, guild_only, rename = "tt_label", category = "Thread tracking")]
+pub(crate) async fn set_label(
+    ctx: CommandContext<'_>,
+    #[description = "The thread or channel to label"]
+    #[channel_types("NewsThread", "PrivateThread", "PublicThread", "Text")]
+    thread: GuildChannel,
+    #[description = "The name to show for the thread; leave empty to show its real name"] label: Option<String>,
+) -> CommandResult<()> {
+    let guild_id = match ctx.guild_id() {
+        Some(id) => id,
+        None => {
+            return Err(CommandError::new("Unable to manage tracked threads outside of a server"))
         },
     };
 
-    if !errors.0.is_empty() {
-        error!("Errors encountered getting a random thread for {}: {}", user.name, errors);
-        reply_error(&ctx, ERROR_TITLE, &errors.build()).await?;
+    let user = ctx.author();
+    let database = &ctx.data().database;
+
+    let label = label.as_deref().map(str::trim).filter(|l| !l.is_empty());
+    if let Some(label) = label {
+        if label.chars().count() > THREAD_LABEL_LENGTH {
+            return Err(CommandError::new(format!(
+                "Labels can be at most {} characters long.",
+                THREAD_LABEL_LENGTH
+            )));
+        }
     }
 
-    if !message.0.is_empty() {
-        reply(&ctx, "Random thread", &message.build()).await?;
+    info!("setting label for thread {} for {} ({})", thread.id, user.name, user.id);
+    let updated =
+        db::set_thread_label(database, guild_id.get(), thread.id.get(), user.id.get(), label).await?;
+
+    if !updated {
+        return Err(CommandError::new(format!("{} is not currently being tracked", thread.id.mention())));
     }
 
+    let mut message = MessageBuilder::new();
+    match label {
+        Some(label) => message.mention(&thread.id).push(" will be shown as ").push_bold_safe(label).push(" in your lists."),
+        None => message.mention(&thread.id).push(" will be shown with its own name in your lists again."),
+    };
+
+    whisper(&ctx, "Thread label", &message.build()).await?;
+
     Ok(())
 }
 
-/// Manage notification status for thread replies.
-#[poise::command(
-    slash_command,
-    category = "Thread tracking",
-    rename = "tt_notify",
-    subcommands("notify_replies_on", "notify_replies_off")
-)]
-pub(crate) async fn notify_replies(ctx: CommandContext<'_>) -> CommandResult<()> {
-    send_invalid_command_call_error(ctx).await
-}
+/// Hide a tracked thread from your pending threads for a while.
+#[poise::command(slash_command, guild_only, rename = "tt_snooze", category = "Thread tracking")]
+pub(crate) async fn snooze(
+    ctx: CommandContext<'_>,
+    #[description = "The thread or channel to snooze"]
+    #[channel_types("NewsThread", "PrivateThread", "PublicThread", "Text")]
+    thread: GuildChannel,
+    #[description = "How long to snooze for, in minutes (m), hours (h), days (d), or weeks (w)"] duration: String,
+) -> CommandResult<()> {
+    let guild_id = match ctx.guild_id() {
+        Some(id) => id,
+        None => {
+            return Err(CommandError::new("Unable to manage tracked threads outside of a server"))
+        },
+    };
 
-/// Subscribe for DMs whenever there's a reply to one of your tracked threads.
-#[poise::command(slash_command, category = "Thread tracking", rename = "on")]
-pub(crate) async fn notify_replies_on(ctx: CommandContext<'_>) -> CommandResult<()> {
     let user = ctx.author();
-    let data = ctx.data();
+    let database = &ctx.data().database;
 
-    if add_subscriber(&data.database, user.id).await? {
-        whisper(&ctx, "Subscription", "Subscribed to thread replies successfully!").await?;
-    }
-    else {
-        whisper_error(&ctx, "Subscription", "You are already subscribed to thread replies.")
-            .await?;
+    // Start a second ahead so that a zero-length duration can't keep the result from ever being in the future.
+    let now = Utc::now();
+    let until = match apply_repeat_duration(&duration, now + TimeDelta::seconds(1)) {
+        Ok(until) if until - now >= TimeDelta::minutes(1) => until,
+        Ok(_) => return Err(CommandError::new("Threads must be snoozed for at least a minute.")),
+        Err(e) => return Err(CommandError::detailed(format!("Unable to understand the duration `{}`", duration), e)),
+    };
+
+    info!("snoozing thread {} until {} for {} ({})", thread.id, until, user.name, user.id);
+    let updated = db::set_thread_snooze(
+        database,
+        guild_id.get(),
+        thread.id.get(),
+        user.id.get(),
+        Some(until.timestamp()),
+    )
+    .await?;
+
+    if !updated {
+        return Err(CommandError::new(format!("{} is not currently being tracked", thread.id.mention())));
     }
 
+    let message = MessageBuilder::new()
+        .mention(&thread.id)
+        .push(" won't be shown in your pending threads until ")
+        .push_timestamp(Timestamp::from(until))
+        .push(".")
+        .build();
+
+    whisper(&ctx, "Thread snoozed", &message).await?;
+
     Ok(())
 }
 
-/// Unsubscribe from DMs for thread replies.
-#[poise::command(slash_command, category = "Thread tracking", rename = "off")]
-pub(crate) async fn notify_replies_off(ctx: CommandContext<'_>) -> CommandResult<()> {
+/// Show a snoozed thread in your pending threads again.
+#[poise::command(slash_command, guild_only, rename = "tt_unsnooze", category = "Thread tracking")]
+pub(crate) async fn unsnooze(
+    ctx: CommandContext<'_>,
+    #[description = "The thread or channel to unsnooze"]
+    #[channel_types("NewsThread", "PrivateThread", "PublicThread", "Text")]
+    thread: GuildChannel,
+) -> CommandResult<()> {
+    let guild_id = match ctx.guild_id() {
+        Some(id) => id,
+        None => {
+            return Err(CommandError::new("Unable to manage tracked threads outside of a server"))
+        },
+    };
+
     let user = ctx.author();
-    let data = ctx.data();
+    let database = &ctx.data().database;
 
-    if remove_subscriber(&data.database, user.id).await? {
-        whisper(&ctx, "Subscription", "Unsubscribed from thread replies successfully!").await?;
+    info!("unsnoozing thread {} for {} ({})", thread.id, user.name, user.id);
+    let updated =
+        db::set_thread_snooze(database, guild_id.get(), thread.id.get(), user.id.get(), None).await?;
+
+    if !updated {
+        return Err(CommandError::new(format!("{} is not currently being tracked", thread.id.mention())));
     }
-    else {
-        whisper_error(&ctx, "Subscription", "You are not currently subscribed to thread replies.")
-            .await?;
+
+    let message =
+        MessageBuilder::new().mention(&thread.id).push(" will be shown in your pending threads again.").build();
+
+    whisper(&ctx, "Thread unsnoozed", &message).await?;
+
+    Ok(())
+}
+
+/// List the other users in this server who are tracking a thread.
+#[poise::command(slash_command, guild_only, rename = "tt_trackers", category = "Thread tracking")]
+pub(crate) async fn list_trackers(
+    ctx: CommandContext<'_>,
+    #[description = "The thread or channel to list the trackers of"]
+    #[channel_types("NewsThread", "PrivateThread", "PublicThread", "Text")]
+    thread: GuildChannel,
+) -> CommandResult<()> {
+    let guild_id = match ctx.guild_id() {
+        Some(id) => id,
+        None => {
+            return Err(CommandError::new("Unable to manage tracked threads outside of a server"))
+        },
+    };
+
+    let user = ctx.author();
+    info!("listing trackers of {} for {} ({})", thread.id, user.name, user.id);
+
+    let mut trackers =
+        db::get_users_tracking_thread(&ctx.data().database, guild_id, thread.id).await?;
+    trackers.retain(|&id| id != user.id);
+    trackers.dedup();
+
+    let mut names = Vec::new();
+    let mut hidden = 0;
+    for tracker in trackers {
+        // Only show users who are still members of this server.
+        match guild_id.member(ctx, tracker).await {
+            Ok(member) if names.len() < MAX_TRACKERS_LISTED => {
+                names.push(get_nick_or_name(&member.user, guild_id, ctx).await);
+            },
+            Ok(_) => hidden += 1,
+            Err(_) => {},
+        }
+    }
+
+    let mut message = MessageBuilder::new();
+    if names.is_empty() {
+        message.push("Nobody else is tracking ").mention(&thread.id).push(".");
+    }
+    else {
+        for name in names {
+            message.push("- ").push_line_safe(name);
+        }
+
+        if hidden > 0 {
+            message.push_line(format!("…and {} more", hidden));
+        }
+    }
+
+    reply(&ctx, &format!("Users tracking #{}", thread.name), &message.build()).await?;
+
+    Ok(())
+}
+
+/// Discard any cached messages for a thread and look up its last reply again.
+#[poise::command(slash_command, guild_only, rename = "tt_refreshthread", category = "Thread tracking")]
+pub(crate) async fn refresh_thread(
+    ctx: CommandContext<'_>,
+    #[description = "The thread or channel to refresh"]
+    #[channel_types("NewsThread", "PrivateThread", "PublicThread", "Text")]
+    thread: GuildChannel,
+) -> CommandResult<()> {
+    const REPLY_TITLE: &str = "Thread refreshed";
+
+    let guild_id = match ctx.guild_id() {
+        Some(id) => id,
+        None => {
+            return Err(CommandError::new("Unable to manage tracked threads outside of a server"))
+        },
+    };
+
+    ctx.defer_ephemeral().await?;
+
+    let user = ctx.author();
+    let message_cache = &ctx.data().message_cache;
+
+    let removed = message_cache.remove_where(|m| m.channel_id == thread.id).await;
+    info!(
+        "refreshing thread {} for {} ({}), removed {} cached messages",
+        thread.id, user.name, user.id, removed
+    );
+
+    let mut message = MessageBuilder::new();
+    match get_last_channel_message(thread.id, ctx).await {
+        Some(last_message) => {
+            let nick = get_nick_or_name(&last_message.author, guild_id, ctx).await;
+            let timestamp = last_message.timestamp;
+            message_cache.store((last_message.id, thread.id).into(), last_message).await;
+
+            message
+                .push("The last reply in ")
+                .mention(&thread.id)
+                .push(" is from ")
+                .push_bold_safe(nick)
+                .push(" (")
+                .push_timestamp(timestamp)
+                .push(").");
+        },
+        None => {
+            message.push("Unable to find any messages in ").mention(&thread.id).push(".");
+        },
+    };
+
+    whisper(&ctx, REPLY_TITLE, &message.build()).await?;
+
+    Ok(())
+}
+
+/// Show the details of one of your tracked threads.
+#[poise::command(slash_command, guild_only, rename = "tt_thread", category = "Thread tracking")]
+pub(crate) async fn show_thread(
+    ctx: CommandContext<'_>,
+    #[description = "The tracked thread or channel to show"]
+    #[channel_types("NewsThread", "PrivateThread", "PublicThread", "Text")]
+    thread: GuildChannel,
+) -> CommandResult<()> {
+    let guild_id = match ctx.guild_id() {
+        Some(id) => id,
+        None => {
+            return Err(CommandError::new("Unable to manage tracked threads outside of a server"))
+        },
+    };
+
+    ctx.defer_ephemeral().await?;
+
+    let user = ctx.author();
+    let data = ctx.data();
+    let database = &data.database;
+
+    info!("showing thread {} for {} ({})", thread.id, user.name, user.id);
+
+    let tracked = match db::get_thread(database, guild_id.get(), thread.id.get(), user.id.get()).await? {
+        Some(t) => t,
+        None => {
+            return Err(CommandError::new(format!("You aren't tracking {}.", thread.id.mention())));
+        },
+    };
+
+    let snoozed = db::list_snoozed_thread_ids(database, guild_id.get(), user.id.get())
+        .await?
+        .contains(&tracked.channel_id);
+    let user_data = UserData {
+        id: user.id,
+        guild_id,
+        muses: muses::get_list(database, user.id, guild_id).await?,
+        show_timestamps: show_timestamps(database, user.id).await,
+        bold_pending_only: bold_pending_only(database, user.id).await,
+        thread_name_length: thread_name_length(database, user.id).await,
+        last_seen: get_last_seen(database, user.id, &[tracked.channel_id as i64]).await,
+    };
+
+    let mut message = MessageBuilder::new();
+    message
+        .push_bold("Thread: ")
+        .push_line(thread.id.mention().to_string())
+        .push_bold("Category: ")
+        .push_line_safe(tracked.category.as_deref().unwrap_or("none"));
+
+    if let Some(label) = &tracked.label {
+        message.push_bold("Label: ").push_line_safe(label);
+    }
+
+    if let Some(note) = &tracked.note {
+        message.push_bold("Note: ").push_line_safe(note);
+    }
+
+    message.push_bold("Last reply: ");
+    let awaiting_reply =
+        match get_last_responder(&tracked, ctx, &data.message_cache, &data.unavailable_channels).await {
+            Ok(Some(reply_info)) => {
+                message
+                    .push_safe(reply_info.author_nick.as_str())
+                    .push(" (")
+                    .push_timestamp(reply_info.timestamp)
+                    .push_line(")");
+
+                !user_data.is_own_reply(&reply_info)
+            },
+            // Threads without replies aren't pending in `/tt_replies` or thread lists either
+            Ok(None) => {
+                message.push_line("no replies yet");
+                false
+            },
+            Err(e) => {
+                debug!("Unable to look up thread {}: {}", tracked.channel_id, e);
+                message.push_line(Italic + "unavailable");
+                false
+            },
+        };
+
+    message.push_bold("Awaiting your reply: ").push_line(if awaiting_reply { "yes" } else { "no" });
+
+    if snoozed {
+        message.push_bold("Snoozed: ").push_line("yes, it won't show up in `/tt_replies` until the snooze ends");
+    }
+
+    whisper(&ctx, "Tracked thread", &message.build()).await?;
+
+    Ok(())
+}
+
+/// Hand your tracked threads over to another user, once they confirm.
+#[poise::command(slash_command, guild_only, rename = "tt_transfer", category = "Thread tracking")]
+pub(crate) async fn transfer(
+    ctx: CommandContext<'_>,
+    #[description = "The user to transfer your tracked threads to"] target: User,
+    #[description = "Only transfer threads in this category"] category: Option<String>,
+) -> CommandResult<()> {
+    let guild_id = match ctx.guild_id() {
+        Some(id) => id,
+        None => return Err(CommandError::new("Unable to manage tracked threads outside of a server")),
+    };
+
+    let user = ctx.author();
+    if target.id == user.id {
+        return Err(CommandError::new("You can't transfer threads to yourself."));
+    }
+    else if target.bot {
+        return Err(CommandError::new("Threads can't be transferred to bots."));
+    }
+
+    let database = &ctx.data().database;
+
+    let threads =
+        db::list_threads(database, guild_id.get(), user.id.get(), category.as_deref()).await?;
+    if threads.is_empty() {
+        return Err(CommandError::new("You don't have any tracked threads to transfer."));
+    }
+
+    if db::count_threads(database, target.id.get()).await? >= MAX_TRACKED_THREADS_PER_USER {
+        return Err(CommandError::new(format!(
+            "{} is already tracking the maximum of {} threads.",
+            target.mention(),
+            MAX_TRACKED_THREADS_PER_USER
+        )));
+    }
+
+    let category_text = match &category {
+        Some(c) => format!(" from the `{}` category", c),
+        None => String::new(),
+    };
+    let prompt = format!(
+        "{} would like to share {} tracked thread(s){} with {}. {}, do you accept?",
+        user.mention(),
+        threads.len(),
+        category_text,
+        target.mention(),
+        target.mention(),
+    );
+
+    info!(
+        "requesting thread transfer from {} ({}) to {} ({})",
+        user.name, user.id, target.name, target.id
+    );
+
+    match send_confirmation_prompt(&ctx, "Thread transfer", &prompt, target.id).await? {
+        ConfirmationResponse::Confirmed => {
+            // Check again, as the target's thread count may have changed while the prompt was waiting
+            let remaining = (MAX_TRACKED_THREADS_PER_USER - db::count_threads(database, target.id.get()).await?).max(0);
+            let already_tracked: HashSet<ChannelId> = db::list_threads(database, guild_id.get(), target.id.get(), None)
+                .await?
+                .iter()
+                .map(|t| t.channel_id())
+                .collect();
+            let new_threads = threads.iter().filter(|t| !already_tracked.contains(&t.channel_id())).count();
+            let over_limit = new_threads.saturating_sub(remaining as usize);
+
+            let transferred = db::copy_threads_to_user(
+                database,
+                guild_id.get(),
+                user.id.get(),
+                target.id.get(),
+                category.as_deref(),
+                remaining,
+            )
+            .await?;
+
+            info!(
+                "transferred {} threads from {} ({}) to {} ({})",
+                transferred, user.name, user.id, target.name, target.id
+            );
+
+            let mut message = format!(
+                "Transferred {} thread(s) to {}. Threads they were already tracking were skipped.",
+                transferred,
+                target.mention()
+            );
+            if over_limit > 0 {
+                message.push_str(&format!(
+                    "\nSkipped {} thread(s) as they can track at most {} threads.",
+                    over_limit, MAX_TRACKED_THREADS_PER_USER
+                ));
+            }
+
+            reply(&ctx, "Threads transferred", &message).await?;
+        },
+        ConfirmationResponse::Denied => {
+            let message = format!("{} declined the thread transfer.", target.mention());
+            whisper(&ctx, "Transfer declined", &message).await?;
+        },
+        ConfirmationResponse::TimedOut => {
+            whisper(&ctx, "Transfer expired", "The thread transfer was not accepted in time.").await?;
+        },
+    }
+
+    Ok(())
+}
+
+/// Change the category of an already tracked thread.
+#[poise::command(slash_command, guild_only, rename = "tt_category", category = "Thread tracking")]
+pub(crate) async fn set_category(
+    ctx: CommandContext<'_>,
+    #[description = "The thread or channel to update category for"]
+    #[channel_types("NewsThread", "PrivateThread", "PublicThread", "Text")]
+    thread: GuildChannel,
+    #[description = "The category to assign to the thread, if any"] category: Option<String>,
+) -> CommandResult<()> {
+    const ERROR_TITLE: &str = "Error updating tracked thread category";
+    let guild_id = match ctx.guild_id() {
+        Some(id) => id,
+        None => {
+            return Err(CommandError::new("Unable to managed tracked threads outside of a server"))
+        },
+    };
+
+    let user = ctx.author();
+    let database = &ctx.data().database;
+    let category = Category::from_argument(category.as_deref())?;
+
+    let mut threads_updated = MessageBuilder::new();
+    let mut errors = MessageBuilder::new();
+
+    info!(
+        "updating category for thread `{}` to `{}`",
+        thread.id,
+        category.as_ref().map_or("none", Category::as_str)
+    );
+    match thread.id.to_channel(ctx).await {
+        Ok(_) => match db::update_thread_category(
+            database,
+            guild_id.get(),
+            thread.id.get(),
+            user.id.get(),
+            category.as_ref(),
+        )
+        .await
+        {
+            Ok(0) => errors.push("- ").mention(&thread.id).push_line(" is not currently being tracked"),
+            Ok(1) => threads_updated.push("- ").mention(&thread.id).push_line(""),
+            Ok(count) => threads_updated
+                .push("- ")
+                .mention(&thread.id)
+                .push_line(format!(" ({} entries updated)", count)),
+            Err(e) => errors
+                .push("- Failed to update thread category for ")
+                .mention(&thread.id)
+                .push_line_safe(format!(": {}", e)),
+        },
+        Err(e) => errors
+            .push("- Cannot access channel ")
+            .mention(&thread.id)
+            .push_line(format!(": {}", e)),
+    };
+
+    if !errors.0.is_empty() {
+        error!("Errors updating thread categories:\n{}", errors);
+        reply_error(&ctx, ERROR_TITLE, &errors.build()).await?;
+    }
+
+    if !threads_updated.0.is_empty() {
+        let title = match category {
+            Some(name) => format!("Tracked threads' category set to `{}`", name),
+            None => String::from("Tracked threads' categories removed"),
+        };
+
+        reply(&ctx, &title, &threads_updated.build()).await?;
+    }
+
+    Ok(())
+}
+
+/// Set where a category is shown in your thread lists, or clear its position to sort it alphabetically.
+#[poise::command(slash_command, guild_only, rename = "tt_catorder", category = "Thread tracking")]
+pub(crate) async fn set_category_order(
+    ctx: CommandContext<'_>,
+    #[description = "The category to move"] category: String,
+    #[description = "The category's position; lower numbers are shown first. Leave empty to clear it"]
+    #[min = 1]
+    #[max = 1000]
+    position: Option<i32>,
+) -> CommandResult<()> {
+    const REPLY_TITLE: &str = "Category order";
+    let guild_id = match ctx.guild_id() {
+        Some(id) => id,
+        None => {
+            return Err(CommandError::new("Unable to manage tracked threads outside of a server"))
+        },
+    };
+
+    let user = ctx.author();
+    let database = &ctx.data().database;
+    let category = match Category::from_argument(Some(&category))? {
+        Some(c) => c,
+        None => {
+            return Err(CommandError::new(
+                "Threads without a category are always listed first, so they can't be given a position.",
+            ))
+        },
+    };
+
+    info!("setting position of category `{}` to {:?} for {} ({})", category, position, user.name, user.id);
+    let changed = db::set_category_order(database, guild_id.get(), user.id.get(), &category, position).await?;
+
+    let mut message = MessageBuilder::new();
+    match (position, changed) {
+        (Some(position), _) => message.push_line(format!("`{}` is now at position {}.", category, position)),
+        (None, true) => message.push_line(format!("`{}` will be sorted alphabetically again.", category)),
+        (None, false) => message.push_line(format!("`{}` didn't have a position set.", category)),
+    };
+
+    let order = db::list_category_order(database, guild_id.get(), user.id.get()).await?;
+    if !order.is_empty() {
+        message.push_line("").push_bold_line("Current order:");
+        for entry in order {
+            message.push(format!("{}. ", entry.sort_order)).push_line_safe(entry.category);
+        }
+        message.push_line(Italic + "Categories without a position follow these, in alphabetical order.");
+    }
+
+    whisper(&ctx, REPLY_TITLE, &message.build()).await?;
+
+    Ok(())
+}
+
+/// Remove threads from tracking.
+#[poise::command(
+    slash_command,
+    prefix_command,
+    guild_only,
+    rename = "tt_untrack",
+    aliases("untrack", "remove"),
+    category = "Thread tracking",
+    subcommands("untrack_thread", "untrack_category")
+)]
+pub(crate) async fn untrack(ctx: CommandContext<'_>) -> CommandResult<()> {
+    send_invalid_command_call_error(ctx).await
+}
+
+/// Remove an individual thread from tracking.
+#[poise::command(slash_command, prefix_command, guild_only, rename = "thread")]
+pub(crate) async fn untrack_thread(
+    ctx: CommandContext<'_>,
+    #[description = "The thread or channel to remove from tracking"]
+    #[channel_types("NewsThread", "PrivateThread", "PublicThread", "Text")]
+    thread: ThreadChannel,
+) -> CommandResult<()> {
+    const ERROR_TITLE: &str = "Error adding tracked thread";
+    let ThreadChannel(thread) = thread;
+
+    let guild_id = match ctx.guild_id() {
+        Some(id) => id,
+        None => {
+            return Err(CommandError::new("Unable to manage tracked threads outside of a server"))
+        },
+    };
+
+    let data = ctx.data();
+    let database = &data.database;
+    let user = ctx.author();
+
+    let mut threads_removed = MessageBuilder::new();
+    let mut errors = MessageBuilder::new();
+
+    info!("removing tracked thread `{}` for {} ({})", thread.id, user.name, user.id);
+    let result = db::remove_thread(database, guild_id.get(), thread.id.get(), user.id.get()).await;
+
+    match result {
+        Ok(0) => {
+            errors.push_line(format!("- {} is not currently being tracked", thread.id.mention()))
+        },
+        Ok(_) => {
+            data.remove_tracked_thread(thread.id).await.ok();
+            threads_removed.push_line(format!("- {:}", thread.id.mention()))
+        },
+        Err(e) => errors.push_line(format!(
+            "- Failed to unregister thread {}: {}",
+            thread.id.mention(),
+            e
+        )),
+    };
+
+    if !errors.0.is_empty() {
+        error!("Errors handling thread removal:\n{}", errors);
+        reply_error(&ctx, ERROR_TITLE, &errors.build()).await?;
+    }
+
+    if !threads_removed.0.is_empty() {
+        reply(&ctx, "Tracked threads removed", &threads_removed.build()).await?;
+    }
+
+    Ok(())
+}
+
+/// Remove all threads in the selected category from tracking.
+#[poise::command(slash_command, prefix_command, guild_only, rename = "category")]
+pub(crate) async fn untrack_category(
+    ctx: CommandContext<'_>,
+    #[description = "Category to untrack all threads from; use 'all' to untrack everything"]
+    name: String,
+) -> CommandResult<()> {
+    const ERROR_TITLE: &str = "Error adding tracked thread";
+
+    let guild_id = match ctx.guild_id() {
+        Some(id) => id,
+        None => {
+            return Err(CommandError::new("Unable to manage tracked threads outside of a server"))
+        },
+    };
+
+    let data = ctx.data();
+    let database = &data.database;
+    let user = ctx.author();
+
+    let mut threads_removed = MessageBuilder::new();
+    let mut errors = MessageBuilder::new();
+
+    let (category, category_message) = match name.to_lowercase().as_str() {
+        "all" => (None, String::new()),
+        _ => (Some(name.as_str()), format!(" in category {}", name)),
+    };
+
+    let count = db::count_threads_in_category(database, guild_id.get(), user.id.get(), category).await?;
+    if count == 0 {
+        let message = format!("No threads are currently being tracked{}.", category_message);
+        reply(&ctx, "Tracked threads removed", &message).await?;
+        return Ok(());
+    }
+
+    let prompt = format!("Would you like to untrack all {} thread(s){}?", count, category_message);
+    match send_confirmation_prompt(&ctx, "Untrack threads", &prompt, user.id).await? {
+        ConfirmationResponse::Confirmed => {},
+        ConfirmationResponse::Denied => {
+            whisper(&ctx, "Untrack threads", "No threads were untracked.").await?;
+            return Ok(());
+        },
+        ConfirmationResponse::TimedOut => {
+            whisper(&ctx, "Untrack threads", "The prompt timed out. No threads were untracked.").await?;
+            return Ok(());
+        },
+    }
+
+    info!("removing all tracked threads{} for {} ({})", category_message, user.name, user.id);
+    match db::remove_all_threads(database, guild_id.get(), user.id.get(), category).await {
+        Ok(0) => threads_removed
+            .push_line(format!("No threads are currently being tracked{}.", category_message)),
+        Ok(count) => threads_removed
+            .push_line(format!("All {} threads{} removed from tracking.", count, category_message))
+            .push_line(format!(
+                "Use `/tt_undo` within the next {} minutes to restore them.",
+                THREAD_UNDO_WINDOW.as_secs() / 60
+            )),
+        Err(e) => {
+            error!(
+                "Error untracking all threads{} for user {} ({}): {}",
+                category_message, user.name, user.id, e
+            );
+            errors.push_line(format!("Error untracking all threads{}: {}", category_message, e))
+        },
+    };
+
+    if let Err(e) = data.update_tracked_threads().await {
+        error!("Error updating in-memory list of tracked threads: {}", e)
+    };
+
+    if !errors.0.is_empty() {
+        error!("Errors handling thread removal:\n{}", errors);
+        reply_error(&ctx, ERROR_TITLE, &errors.build()).await?;
+    }
+
+    if !threads_removed.0.is_empty() {
+        reply(&ctx, "Tracked threads removed", &threads_removed.build()).await?;
+    }
+
+    Ok(())
+}
+
+/// Show the list of all tracked threads.
+#[poise::command(
+    slash_command,
+    prefix_command,
+    guild_only,
+    user_cooldown = 5,
+    rename = "tt_threads",
+    aliases("threads", "list"),
+    category = "Thread tracking"
+)]
+pub(crate) async fn send_list(
+    ctx: CommandContext<'_>,
+    #[description = "Only show threads from this category"] category: Option<String>,
+    #[description = "How to sort the threads in the list, based on the most recent reply"] sort: Option<SortResultsBy>,
+    #[description = "List only the thread URLs, without formatting, so they can be copied"] plain_links: Option<bool>,
+    #[description = "Only show the list to you (default: off)"] ephemeral: Option<bool>,
+) -> CommandResult<()> {
+    let guild_id = match ctx.guild_id() {
+        Some(id) => id,
+        None => {
+            return Err(CommandError::new("Unable to manage tracked threads outside of a server"))
+        },
+    };
+
+    let ephemeral = ephemeral.unwrap_or(false);
+    let title = "Currently tracked threads";
+    if plain_links.unwrap_or(false) {
+        let guild_user = GuildUser { user_id: ctx.author().id, guild_id };
+        let threads = enumerate(&ctx.data().database, &guild_user, category.as_deref()).await?.collect();
+
+        for chunk in get_plain_link_list(threads) {
+            send_list_reply(&ctx, title, &chunk, ephemeral).await?;
+        }
+
+        return Ok(());
+    }
+
+    let _render_guard = ctx.data().begin_list_render(ctx.author().id)?;
+
+    defer_list_reply(&ctx, ephemeral).await?;
+
+    let sort = match sort {
+        Some(sort) => Some(sort),
+        None => default_sort(&ctx.data().database, ctx.author().id).await,
+    };
+
+    let threads_list =
+        get_threads_and_todos(ctx.author(), guild_id, category.as_deref(), sort, ctx.data(), &ctx)
+            .await?;
+
+    send_list_reply(&ctx, title, &threads_list, ephemeral).await?;
+
+    Ok(())
+}
+
+/// Defer a thread list command's response, privately if the list is only to be shown to the user who asked for it.
+async fn defer_list_reply(ctx: &CommandContext<'_>, ephemeral: bool) -> CommandResult<()> {
+    if ephemeral {
+        ctx.defer_ephemeral().await?;
+    }
+    else {
+        ctx.defer().await?;
+    }
+
+    Ok(())
+}
+
+/// Send a thread list, either publicly or only to the user who asked for it.
+async fn send_list_reply(ctx: &CommandContext<'_>, title: &str, list: &str, ephemeral: bool) -> CommandResult<()> {
+    if ephemeral {
+        whisper(ctx, title, list).await?;
+    }
+    else {
+        reply(ctx, title, list).await?;
+    }
+
+    Ok(())
+}
+
+/// Show your tracked threads from every server in one list.
+#[poise::command(
+    slash_command,
+    prefix_command,
+    user_cooldown = 5,
+    rename = "tt_allthreads",
+    aliases("allthreads"),
+    category = "Thread tracking"
+)]
+pub(crate) async fn send_all_list(ctx: CommandContext<'_>) -> CommandResult<()> {
+    let _render_guard = ctx.data().begin_list_render(ctx.author().id)?;
+
+    // The list includes threads from other servers, so it's only shown to the user who asked for it.
+    ctx.defer_ephemeral().await?;
+
+    let threads_list = get_all_servers_thread_list(ctx.author(), ctx.data(), &ctx).await?;
+
+    whisper(&ctx, "Tracked threads in all servers", &threads_list).await?;
+
+    Ok(())
+}
+
+/// Get the list of a user's tracked threads across every server they track threads in, grouped by server.
+async fn get_all_servers_thread_list(user: &User, data: &Data, context: &impl CacheHttp) -> CommandResult<String> {
+    info!("Getting tracked threads in all servers for {} ({})", user.name, user.id);
+
+    let threads = match db::list_all_threads_for_user(&data.database, user.id.get()).await {
+        Ok(t) => t,
+        Err(e) => {
+            error!("Error listing tracked threads for {}: {}", user.name, e);
+            return Err(CommandError::detailed(format!("Error listing tracked threads for {}", user.name), e));
+        },
+    };
+
+    if threads.is_empty() {
+        return Ok("No threads are currently being tracked.".to_owned());
+    }
+
+    let mut message = MessageBuilder::new();
+    for (guild_id, threads) in partition_into_map(threads, |t| t.guild_id) {
+        let guild_id = GuildId::new(guild_id);
+        let name = get_guild_display_name(&data.database, Some(user.id), guild_id, context).await;
+
+        let channel_ids: Vec<i64> = threads.iter().map(|t| t.channel_id as i64).collect();
+        let user_data = UserData {
+            id: user.id,
+            guild_id,
+            muses: muses::get_list(&data.database, user.id, guild_id).await?,
+            show_timestamps: show_timestamps(&data.database, user.id).await,
+            bold_pending_only: bold_pending_only(&data.database, user.id).await,
+            thread_name_length: thread_name_length(&data.database, user.id).await,
+            last_seen: get_last_seen(&data.database, user.id, &channel_ids).await,
+        };
+
+        let list = get_formatted_list(
+            threads,
+            Vec::new(),
+            None,
+            &data.database,
+            context,
+            &data.message_cache,
+            &data.active_threads_cache,
+            &data.unavailable_channels,
+            &user_data,
+        )
+        .await
+        .map_err(|e| CommandError::detailed(format!("Error collating tracked threads in {}", name), e))?;
+
+        message.push("# ").push_line_safe(name).push_line(list);
+        mark_threads_seen(&data.database, user.id, &channel_ids).await;
+    }
+
+    Ok(message.build())
+}
+
+/// Show the list of tracked threads currently pending replies.
+#[poise::command(slash_command, guild_only, user_cooldown = 5, rename = "tt_replies", category = "Thread tracking")]
+pub(crate) async fn send_pending_list(
+    ctx: CommandContext<'_>,
+    #[description = "Only show threads from this category"] category: Option<String>,
+    #[description = "How to sort the threads in the list, based on the most recent reply"] sort: Option<SortResultsBy>,
+    #[description = "Only show the list to you (default: off)"] ephemeral: Option<bool>,
+) -> CommandResult<()> {
+    let guild_id = match ctx.guild_id() {
+        Some(id) => id,
+        None => {
+            return Err(CommandError::new("Unable to manage tracked threads outside of a server"))
+        },
+    };
+
+    let ephemeral = ephemeral.unwrap_or(false);
+    let _render_guard = ctx.data().begin_list_render(ctx.author().id)?;
+
+    defer_list_reply(&ctx, ephemeral).await?;
+
+    let sort = match sort {
+        Some(sort) => Some(sort),
+        None => default_sort(&ctx.data().database, ctx.author().id).await,
+    };
+
+    let threads_list =
+        get_pending_thread_list(ctx.author(), guild_id, category.as_deref(), sort, ctx.data(), &ctx)
+            .await?;
+
+    send_list_reply(&ctx, "Threads awaiting replies", &threads_list, ephemeral).await?;
+
+    Ok(())
+}
+
+/// Show your tracked threads where a particular person or muse has the last reply.
+#[poise::command(slash_command, guild_only, user_cooldown = 5, rename = "tt_by", category = "Thread tracking")]
+pub(crate) async fn send_last_reply_by_list(
+    ctx: CommandContext<'_>,
+    #[description = "The user with the last reply"] user: Option<User>,
+    #[description = "The name with the last reply, such as a muse's name or someone's nickname"] name: Option<String>,
+) -> CommandResult<()> {
+    let guild_id = match ctx.guild_id() {
+        Some(id) => id,
+        None => {
+            return Err(CommandError::new("Unable to manage tracked threads outside of a server"))
+        },
+    };
+
+    let name = name.as_deref().map(str::trim).filter(|n| !n.is_empty());
+    let who: Vec<&str> = user.as_ref().map(|u| u.name.as_str()).into_iter().chain(name).collect();
+    if who.is_empty() {
+        return Err(CommandError::new("Please give a user or a name to look for."));
+    }
+
+    let who = who.join(" or ");
+    let author = ctx.author();
+    let data = ctx.data();
+    let _render_guard = data.begin_list_render(author.id)?;
+
+    ctx.defer().await?;
+
+    info!("listing threads with the last reply by {} for {} ({})", who, author.name, author.id);
+
+    let guild_user = GuildUser { user_id: author.id, guild_id };
+    let threads: Vec<TrackedThread> = enumerate(&data.database, &guild_user, None).await?.collect();
+    let names: Vec<String> = name.map(str::to_owned).into_iter().collect();
+
+    let matching: Vec<(LastReplyInfo, TrackedThread)> =
+        get_last_responders(threads, &ctx, &data.message_cache, &data.unavailable_channels)
+            .await
+            .into_iter()
+            .filter_map(|(reply_info, thread)| match reply_info {
+                Ok(Some(r)) => Some((r, thread)),
+                _ => None,
+            })
+            .filter(|(r, _)| {
+                user.as_ref().is_some_and(|u| r.author.id == u.id)
+                    || muses::muse_matches(&r.author, &r.author_nick, &names)
+            })
+            .collect();
+
+    let title = format!("Threads with the last reply from {}", who);
+    if matching.is_empty() {
+        let message = format!("None of your tracked threads have the last reply from {}.", who);
+        reply(&ctx, &title, &message).await?;
+        return Ok(());
+    }
+
+    let show_timestamps = show_timestamps(&data.database, author.id).await;
+    let name_length = thread_name_length(&data.database, author.id).await;
+
+    let mut message = MessageBuilder::new();
+    for (category, threads) in partition_into_map(matching, |item| item.1.category.clone()) {
+        if let Some(c) = category {
+            message.push("### ").push_line(c).push_line("");
+        }
+
+        for (reply_info, thread) in threads {
+            let link = get_thread_link(&thread, None, name_length, true, &data.database, &ctx).await;
+            message.push("- ").push(link.to_string()).push(" — ").push(reply_info.author_nick);
+
+            if show_timestamps {
+                message.push(" (").push_timestamp(reply_info.timestamp).push_line(")");
+            }
+            else {
+                message.push_line("");
+            }
+        }
+
+        message.push_line("");
+    }
+
+    reply(&ctx, &title, &message.build()).await?;
+
+    Ok(())
+}
+
+/// Post a list of the threads in one of your categories for others to see.
+#[poise::command(slash_command, guild_only, user_cooldown = 5, rename = "tt_share", category = "Thread tracking")]
+pub(crate) async fn share(
+    ctx: CommandContext<'_>,
+    #[description = "The category to share; use none for threads without a category"] category: String,
+    #[description = "Say whose threads these are in the title (default: off)"] show_owner: Option<bool>,
+) -> CommandResult<()> {
+    let guild_id = match ctx.guild_id() {
+        Some(id) => id,
+        None => {
+            return Err(CommandError::new("Unable to manage tracked threads outside of a server"))
+        },
+    };
+
+    let user = ctx.author();
+    let database = &ctx.data().database;
+    let category = category.trim();
+
+    info!("sharing {} category threads for {} ({})", category, user.name, user.id);
+    let threads = db::list_threads(database, guild_id.get(), user.id.get(), Some(category)).await?;
+    if threads.is_empty() {
+        return Err(CommandError::new(format!("You aren't tracking any threads in the `{}` category.", category)));
+    }
+
+    ctx.defer().await?;
+
+    // Use the category's name as it was saved, rather than as it was typed.
+    let category_name = match threads[0].category.as_deref() {
+        Some(name) => name.to_owned(),
+        None => "uncategorised".to_owned(),
+    };
+    let title = if show_owner.unwrap_or(false) {
+        let nick = get_nick_or_name(user, guild_id, &ctx).await;
+        format!("{}'s {} threads", nick, category_name)
+    }
+    else {
+        format!("Threads: {}", category_name)
+    };
+
+    let name_length = thread_name_length(database, user.id).await;
+    let mut message = MessageBuilder::new();
+    for mut thread in threads {
+        // Labels are only for the user's own lists, so others see the real thread names.
+        thread.label = None;
+        let link = get_thread_link(&thread, None, name_length, false, database, &ctx).await;
+        message.push("- ").push_line(link.to_string());
+    }
+
+    reply(&ctx, &title, &message.build()).await?;
+
+    Ok(())
+}
+
+/// Build a list of the URLs of the given threads, one per line, in code blocks of up to one message each.
+fn get_plain_link_list(threads: Vec<TrackedThread>) -> Vec<String> {
+    const CODE_BLOCK_FENCES: usize = "```\n\n```".len();
+
+    let links: Vec<String> = categorise(threads).into_values().flatten().map(|t| thread_url(&t)).collect();
+    if links.is_empty() {
+        return vec!["No threads are currently being tracked.".to_owned()];
+    }
+
+    split_into_chunks(&links.join("\n"), MAX_EMBED_CHARS - CODE_BLOCK_FENCES)
+        .into_iter()
+        .map(|chunk| format!("```\n{}\n```", chunk.trim()))
+        .collect()
+}
+
+/// Get the list of threads and todos.
+pub(crate) async fn get_threads_and_todos(
+    user: &User,
+    guild_id: GuildId,
+    category: Option<&str>,
+    sort: Option<SortResultsBy>,
+    data: &Data,
+    context: &impl CacheHttp,
+) -> CommandResult<String> {
+    info!("Getting tracked threads and todo list for {} ({})", user.name, user.id);
+
+    let guild_user = GuildUser { user_id: user.id, guild_id };
+
+    let mut threads: Vec<TrackedThread> = Vec::new();
+    let mut todos: Vec<Todo> = Vec::new();
+
+    match enumerate(&data.database, &guild_user, category).await {
+        Ok(t) => threads.extend(t),
+        Err(e) => {
+            error!("Error listing tracked threads for {}: {}", user.name, e);
+            return Err(CommandError::detailed(
+                format!("Error listing tracked threads for {}", user.name),
+                e,
+            ));
+        },
+    }
+
+    match todos::enumerate(&data.database, &guild_user, category).await {
+        Ok(t) => todos.extend(t),
+        Err(e) => {
+            error!("Error listing todos for {}: {}", user.name, e);
+            return Err(CommandError::detailed(
+                format!("Error listing todos for {}", user.name),
+                e,
+            ));
+        },
+    }
+
+    let muses = match muses::get_list(&data.database, guild_user.user_id, guild_user.guild_id).await
+    {
+        Ok(m) => m,
+        Err(e) => {
+            error!("Error finding muse list for {}: {}", user.name, e);
+            return Err(CommandError::detailed(
+                format!("Error finding muse list for {}", user.name),
+                e,
+            ));
+        },
+    };
+
+    let channel_ids: Vec<i64> = threads.iter().map(|t| t.channel_id as i64).collect();
+    let user_data = UserData {
+        id: guild_user.user_id,
+        guild_id: guild_user.guild_id,
+        muses,
+        show_timestamps: show_timestamps(&data.database, guild_user.user_id).await,
+        bold_pending_only: bold_pending_only(&data.database, guild_user.user_id).await,
+        thread_name_length: thread_name_length(&data.database, guild_user.user_id).await,
+        last_seen: get_last_seen(&data.database, guild_user.user_id, &channel_ids).await,
+    };
+
+    let message =
+        match get_formatted_list(
+            threads,
+            todos,
+            sort,
+            &data.database,
+            context,
+            &data.message_cache,
+            &data.active_threads_cache,
+            &data.unavailable_channels,
+            &user_data,
+        )
+        .await
+        {
+            Ok(m) => m,
+            Err(e) => {
+                error!("Error collating tracked threads for {}: {}", user.name, e);
+                return Err(CommandError::detailed(
+                    format!("Error collating tracked threads for {}", user.name),
+                    e,
+                ));
+            },
+        };
+
+    mark_threads_seen(&data.database, user.id, &channel_ids).await;
+
+    Ok(message)
+}
+
+/// Get the list of threads pending reply, and mark the listed threads as seen.
+pub(crate) async fn get_pending_thread_list(
+    user: &User,
+    guild_id: GuildId,
+    category: Option<&str>,
+    sort_threads: Option<SortResultsBy>,
+    data: &Data,
+    context: &impl CacheHttp,
+) -> CommandResult<String> {
+    let (list, channel_ids) = render_pending_thread_list(user, guild_id, category, sort_threads, data, context).await?;
+    mark_threads_seen(&data.database, user.id, &channel_ids).await;
+
+    Ok(list)
+}
+
+/// Render the list of threads pending reply, without marking them as seen.
+/// Returns the list along with the channel IDs of the threads in it.
+async fn render_pending_thread_list(
+    user: &User,
+    guild_id: GuildId,
+    category: Option<&str>,
+    sort_threads: Option<SortResultsBy>,
+    data: &Data,
+    context: &impl CacheHttp,
+) -> CommandResult<(String, Vec<i64>)> {
+    info!("Getting pending threads list for {} ({})", user.name, user.id);
+
+    let pending_threads = get_pending_threads(category, user, guild_id, context, data).await?;
+    let channel_ids: Vec<i64> = pending_threads.iter().map(|(_, t)| t.channel_id as i64).collect();
+
+    let category_order = get_category_order(&data.database, user.id, guild_id).await;
+    let mut categorised_threads: Vec<_> =
+        partition_into_map(pending_threads, |item| item.1.category.clone()).into_iter().collect();
+    categorised_threads.sort_by_cached_key(|(name, _)| category_sort_key(name, &category_order));
+
+    let show_timestamps: bool = show_timestamps(&data.database, user.id).await;
+    let name_length = thread_name_length(&data.database, user.id).await;
+    let last_seen = get_last_seen(&data.database, user.id, &channel_ids).await;
+
+    let mut message = MessageBuilder::new();
+
+    for (name, mut threads) in categorised_threads {
+        if let Some(n) = name {
+            message.push("### ").push_line(n).push_line("");
+        }
+
+        if let Some(sort_order) = sort_threads {
+            match sort_order {
+                SortResultsBy::NewestFirst => {
+                    threads.sort_by_key(|item| Reverse(item.0.timestamp));
+                },
+                // Every thread in this list is awaiting a reply, so show the longest-waiting first.
+                SortResultsBy::OldestFirst | SortResultsBy::PendingFirst => {
+                    threads.sort_by_key(|item| item.0.timestamp);
+                }
+            }
+        }
+
+        for (reply_info, thread) in threads {
+            let link = get_thread_link(&thread, None, name_length, true, &data.database, context).await;
+            message.push("- ").push(link.to_string()).push(" — ").push(Bold + &reply_info.author_nick);
+
+            // Every thread here is awaiting a reply, so any reply since it was last seen is new.
+            if last_seen.get(&thread.channel_id()).is_some_and(|&seen| reply_info.timestamp.unix_timestamp() > seen) {
+                message.push(" ").push(Italic + "(new)");
+            }
+
+            if show_timestamps {
+                message.push(" (")
+                    .push_timestamp(reply_info.timestamp)
+                    .push(", ")
+                    .push_elapsed(reply_info.timestamp)
+                    .push_line(")");
+            }
+            else {
+                message.push_line("");
+            }
+        }
+
+        message.push_line("");
+    }
+
+    if message.0.is_empty() {
+        message.push_line(NO_PENDING_THREADS);
+    }
+
+    Ok((message.build(), channel_ids))
+}
+
+/// Select and send a random thread to the user that is awaiting their reply.
+#[poise::command(slash_command, guild_only, category = "Thread tracking", rename = "tt_random")]
+pub(crate) async fn send_random_thread(
+    ctx: CommandContext<'_>,
+    #[description = "Only pick from threads in this category"] category: Option<String>,
+) -> CommandResult<()> {
+    const ERROR_TITLE: &str = "Error fetching tracked threads";
+
+    ctx.defer().await?;
+
+    let guild_id = match ctx.guild_id() {
+        Some(id) => id,
+        None => {
+            return Err(CommandError::new("Unable to manage tracked threads outside of a server"))
+        },
+    };
+
+    let user = ctx.author();
+    let database = &ctx.data().database;
+    let name_length = thread_name_length(database, user.id).await;
+
+    let mut message = MessageBuilder::new();
+    let mut errors = MessageBuilder::new();
+
+    info!("sending a random thread for {} ({})", user.name, user.id);
+
+    match get_random_thread(category.as_deref(), user, guild_id, &ctx).await {
+        Ok(None) => {
+            message.push("Congrats! You don't seem to have any threads that are waiting on your reply! :tada:");
+        },
+        Ok(Some((reply_info, thread))) => {
+            message.push("Titi has chosen... this thread");
+
+            if let Some(category) = &thread.category {
+                message
+                    .push(" from your ")
+                    .push(Bold + Underline + category)
+                    .push_line(" threads!");
+            }
+            else {
+                message.push_line("!");
+            }
+
+            message.push_line("");
+            message
+                .push_quote(get_thread_link(&thread, None, name_length, true, database, &ctx).await.build())
+                .push(" — ")
+                .push_line(Bold + reply_info.author_nick);
+        },
+        Err(e) => {
+            errors.push("- ").push_line(e.to_string());
+        },
+    };
+
+    if !errors.0.is_empty() {
+        error!("Errors encountered getting a random thread for {}: {}", user.name, errors);
+        reply_error(&ctx, ERROR_TITLE, &errors.build()).await?;
+    }
+
+    if !message.0.is_empty() {
+        reply(&ctx, "Random thread", &message.build()).await?;
+    }
+
+    Ok(())
+}
+
+/// Manage notification status for thread replies.
+#[poise::command(
+    slash_command,
+    category = "Thread tracking",
+    rename = "tt_notify",
+    subcommands(
+        "notify_replies_on",
+        "notify_replies_off",
+        "notify_quiet_hours",
+        "notify_preview_length",
+        "notify_category",
+        "notify_weekly_digest"
+    )
+)]
+pub(crate) async fn notify_replies(ctx: CommandContext<'_>) -> CommandResult<()> {
+    send_invalid_command_call_error(ctx).await
+}
+
+/// Subscribe for DMs whenever there's a reply to one of your tracked threads.
+#[poise::command(slash_command, category = "Thread tracking", rename = "on")]
+pub(crate) async fn notify_replies_on(ctx: CommandContext<'_>) -> CommandResult<()> {
+    let user = ctx.author();
+    let data = ctx.data();
+
+    if add_subscriber(&data.database, user.id).await? {
+        whisper(&ctx, "Subscription", "Subscribed to thread replies successfully!").await?;
+    }
+    else {
+        whisper_error(&ctx, "Subscription", "You are already subscribed to thread replies.")
+            .await?;
+    }
+
+    Ok(())
+}
+
+/// Unsubscribe from DMs for thread replies.
+#[poise::command(slash_command, category = "Thread tracking", rename = "off")]
+pub(crate) async fn notify_replies_off(ctx: CommandContext<'_>) -> CommandResult<()> {
+    let user = ctx.author();
+    let data = ctx.data();
+
+    if remove_subscriber(&data.database, user.id).await? {
+        whisper(&ctx, "Subscription", "Unsubscribed from thread replies successfully!").await?;
+    }
+    else {
+        whisper_error(&ctx, "Subscription", "You are not currently subscribed to thread replies.")
+            .await?;
+    }
+
+    Ok(())
+}
+
+/// Choose which categories of threads you receive reply notifications for.
+#[poise::command(
+    slash_command,
+    category = "Thread tracking",
+    rename = "category",
+    subcommands("notify_category_on", "notify_category_off")
+)]
+pub(crate) async fn notify_category(ctx: CommandContext<'_>) -> CommandResult<()> {
+    send_invalid_command_call_error(ctx).await
+}
+
+/// Only receive reply notifications for threads in the given categories.
+#[poise::command(slash_command, category = "Thread tracking", rename = "on")]
+pub(crate) async fn notify_category_on(
+    ctx: CommandContext<'_>,
+    #[description = "The category to be notified about; use `none` for threads without a category"] category: String,
+) -> CommandResult<()> {
+    const REPLY_TITLE: &str = "Notification categories";
+    let user = ctx.author();
+    let data = ctx.data();
+    let category = category.trim();
+
+    if db::add_subscription_category(&data.database, user.id, category).await? {
+        info!("adding notification category `{}` for {} ({})", category, user.name, user.id);
+        let categories = db::list_subscription_categories(&data.database, user.id).await?;
+        let message = format!(
+            "You will now be notified of replies to threads in these categories: {}",
+            categories.join(", ")
+        );
+        whisper(&ctx, REPLY_TITLE, &message).await?;
+    }
+    else {
+        let message = format!("You are already notified of replies to threads in `{}`.", category);
+        whisper_error(&ctx, REPLY_TITLE, &message).await?;
+    }
+
+    Ok(())
+}
+
+/// Stop receiving reply notifications for threads in a category.
+#[poise::command(slash_command, category = "Thread tracking", rename = "off")]
+pub(crate) async fn notify_category_off(
+    ctx: CommandContext<'_>,
+    #[description = "The category to stop being notified about"] category: String,
+) -> CommandResult<()> {
+    const REPLY_TITLE: &str = "Notification categories";
+    let user = ctx.author();
+    let data = ctx.data();
+    let category = category.trim();
+
+    if db::remove_subscription_category(&data.database, user.id, category).await? {
+        info!("removing notification category `{}` for {} ({})", category, user.name, user.id);
+        let categories = db::list_subscription_categories(&data.database, user.id).await?;
+        let message = if categories.is_empty() {
+            "You will now be notified of replies to threads in every category.".to_owned()
+        }
+        else {
+            format!("You will now be notified of replies to threads in these categories: {}", categories.join(", "))
+        };
+        whisper(&ctx, REPLY_TITLE, &message).await?;
+    }
+    else {
+        let message = format!("`{}` is not one of your notification categories.", category);
+        whisper_error(&ctx, REPLY_TITLE, &message).await?;
+    }
+
+    Ok(())
+}
+
+/// Hold back reply notifications during the given hours in your local timezone, or clear quiet hours.
+#[poise::command(slash_command, category = "Thread tracking", rename = "quiethours")]
+pub(crate) async fn notify_quiet_hours(
+    ctx: CommandContext<'_>,
+    #[description = "When quiet hours start, in your local time (format: hh:mm)"] start: Option<String>,
+    #[description = "When quiet hours end, in your local time (format: hh:mm)"] end: Option<String>,
+) -> CommandResult<()> {
+    const REPLY_TITLE: &str = "Quiet hours";
+    let user = ctx.author();
+    let data = ctx.data();
+
+    match (start, end) {
+        (Some(start), Some(end)) => {
+            let start = parse_quiet_hours_time(&start)?.format(QUIET_HOURS_FORMAT).to_string();
+            let end = parse_quiet_hours_time(&end)?.format(QUIET_HOURS_FORMAT).to_string();
+
+            info!("setting quiet hours {} - {} for {} ({})", start, end, user.name, user.id);
+            db::update_user_setting(&data.database, user.id, USER_QUIET_HOURS_START, &start).await?;
+            db::update_user_setting(&data.database, user.id, USER_QUIET_HOURS_END, &end).await?;
+
+            let message = format!(
+                "Reply notifications between {} and {} (your local time) will be held and sent once quiet hours end.",
+                start, end
+            );
+            whisper(&ctx, REPLY_TITLE, &message).await?;
+        },
+        (None, None) => {
+            info!("clearing quiet hours for {} ({})", user.name, user.id);
+            db::remove_user_setting(&data.database, user.id, USER_QUIET_HOURS_START).await?;
+            db::remove_user_setting(&data.database, user.id, USER_QUIET_HOURS_END).await?;
+
+            whisper(&ctx, REPLY_TITLE, "Quiet hours cleared.").await?;
+        },
+        _ => {
+            return Err(CommandError::new(
+                "Both a start and an end time are required to set quiet hours.",
+            ))
+        },
+    }
+
+    Ok(())
+}
+
+/// Get a weekly DM listing the threads awaiting your reply in every server, or turn it off.
+#[poise::command(slash_command, category = "Thread tracking", rename = "digest")]
+pub(crate) async fn notify_weekly_digest(
+    ctx: CommandContext<'_>,
+    #[description = "The day of the week to send the summary on, for example 'Sunday'"] day: Option<String>,
+    #[description = "When to send the summary, in your local time (format: hh:mm)"] time: Option<String>,
+) -> CommandResult<()> {
+    const REPLY_TITLE: &str = "Weekly summary";
+    let user = ctx.author();
+    let data = ctx.data();
+
+    let message = match (day, time) {
+        (Some(day), time) => {
+            let day: Weekday = day
+                .trim()
+                .parse()
+                .map_err(|e| CommandError::detailed(format!("Unable to parse day '{}'", day), e))?;
+            let time = parse_quiet_hours_time(time.as_deref().unwrap_or(DEFAULT_WEEKLY_DIGEST_TIME))?;
+            let schedule = format!("{} {}", day, time.format(QUIET_HOURS_FORMAT));
+
+            info!("setting weekly digest to {} for {} ({})", schedule, user.name, user.id);
+            db::update_user_setting(&data.database, user.id, USER_WEEKLY_DIGEST, &schedule).await?;
+            // Start counting from now, so a time earlier today doesn't send a summary straight away.
+            let now = Utc::now().timestamp().to_string();
+            db::update_user_setting(&data.database, user.id, USER_WEEKLY_DIGEST_LAST_SENT, &now).await?;
+
+            format!(
+                "A summary of the threads awaiting your reply will be sent to you every {} at {} (your local time).",
+                weekday_name(day),
+                time.format(QUIET_HOURS_FORMAT)
+            )
+        },
+        (None, None) => {
+            info!("turning off weekly digest for {} ({})", user.name, user.id);
+            db::remove_user_setting(&data.database, user.id, USER_WEEKLY_DIGEST).await?;
+            db::remove_user_setting(&data.database, user.id, USER_WEEKLY_DIGEST_LAST_SENT).await?;
+
+            "Weekly summaries turned off.".to_owned()
+        },
+        (None, Some(_)) => {
+            return Err(CommandError::new("A day is required to set up a weekly summary."));
+        },
+    };
+
+    whisper(&ctx, REPLY_TITLE, &message).await?;
+
+    Ok(())
+}
+
+/// Set how many characters of a reply to preview in notifications, or reset to the default.
+#[poise::command(slash_command, category = "Thread tracking", rename = "preview")]
+pub(crate) async fn notify_preview_length(
+    ctx: CommandContext<'_>,
+    #[description = "Number of characters to preview; 0 to disable previews, or leave empty to reset"]
+    #[min = 0]
+    #[max = 2048]
+    length: Option<u32>,
+) -> CommandResult<()> {
+    const REPLY_TITLE: &str = "Reply preview length";
+    let user = ctx.author();
+    let data = ctx.data();
+
+    let message = match length {
+        Some(length) => {
+            let length = length as usize;
+            if length != 0 && !(MIN_PREVIEW_LENGTH..=MAX_EMBED_CHARS).contains(&length) {
+                return Err(CommandError::new(format!(
+                    "Preview length must be 0, or between {} and {} characters.",
+                    MIN_PREVIEW_LENGTH, MAX_EMBED_CHARS
+                )));
+            }
+
+            info!("setting reply preview length to {} for {} ({})", length, user.name, user.id);
+            db::update_user_setting(&data.database, user.id, USER_PREVIEW_LENGTH, &length.to_string())
+                .await?;
+
+            match length {
+                0 => "Reply notifications will no longer include a preview.".to_owned(),
+                _ => format!("Reply notifications will preview up to {} characters.", length),
+            }
+        },
+        None => {
+            info!("resetting reply preview length for {} ({})", user.name, user.id);
+            db::remove_user_setting(&data.database, user.id, USER_PREVIEW_LENGTH).await?;
+
+            format!("Reply preview length reset to the default of {} characters.", MAX_EMBED_CHARS)
+        },
+    };
+
+    whisper(&ctx, REPLY_TITLE, &message).await?;
+
+    Ok(())
+}
+
+#[poise::command(slash_command, category = "Thread tracking", rename = "tt_timestamps", subcommands("set_timestamps_on", "set_timestamps_off"))]
+pub(crate) async fn set_timestamps(ctx: CommandContext<'_>) -> CommandResult<()> {
+    send_invalid_command_call_error(ctx).await
+}
+
+#[poise::command(slash_command, category = "Thread tracking", rename = "on")]
+pub(crate) async fn set_timestamps_on(ctx: CommandContext<'_>) -> CommandResult<()> {
+    const REPLY_TITLE: &str = "Enable timestamps";
+    let data = ctx.data();
+    let author = ctx.author();
+
+    let result = db::update_user_setting(&data.database, author.id, USER_SHOW_TIMESTAMPS, "true").await?;
+
+    let mut message = MessageBuilder::new();
+    if result {
+        message.push("Timestamps successfully enabled");
+    }
+    else {
+        message.push("Timestamps are already enabled");
+    }
+
+    whisper(&ctx, REPLY_TITLE, &message.build()).await?;
+
+    Ok(())
+}
+
+#[poise::command(slash_command, category = "Thread tracking", rename = "off")]
+pub(crate) async fn set_timestamps_off(ctx: CommandContext<'_>) -> CommandResult<()> {
+    const REPLY_TITLE: &str = "Disable timestamps";
+    let data = ctx.data();
+    let author = ctx.author();
+
+    let result = db::update_user_setting(&data.database, author.id, USER_SHOW_TIMESTAMPS, "false").await?;
+
+    let mut message = MessageBuilder::new();
+    if result {
+        message.push("Timestamps successfully disabled");
+    }
+    else {
+        message.push("Timestamps are already disabled");
+    }
+
+    whisper(&ctx, REPLY_TITLE, &message.build()).await?;
+
+    Ok(())
+}
+
+#[poise::command(slash_command, category = "Thread tracking", rename = "tt_boldpending", subcommands("set_bold_pending_on", "set_bold_pending_off"))]
+pub(crate) async fn set_bold_pending(ctx: CommandContext<'_>) -> CommandResult<()> {
+    send_invalid_command_call_error(ctx).await
+}
+
+#[poise::command(slash_command, category = "Thread tracking", rename = "on")]
+pub(crate) async fn set_bold_pending_on(ctx: CommandContext<'_>) -> CommandResult<()> {
+    const REPLY_TITLE: &str = "Bold pending threads only";
+    let data = ctx.data();
+    let author = ctx.author();
+
+    let result = db::update_user_setting(&data.database, author.id, USER_BOLD_PENDING_ONLY, "true").await?;
+
+    let mut message = MessageBuilder::new();
+    if result {
+        message.push("Only threads awaiting your reply will be shown in bold");
+    }
+    else {
+        message.push("Only threads awaiting your reply are already shown in bold");
+    }
+
+    whisper(&ctx, REPLY_TITLE, &message.build()).await?;
+
+    Ok(())
+}
+
+#[poise::command(slash_command, category = "Thread tracking", rename = "off")]
+pub(crate) async fn set_bold_pending_off(ctx: CommandContext<'_>) -> CommandResult<()> {
+    const REPLY_TITLE: &str = "Bold all threads";
+    let data = ctx.data();
+    let author = ctx.author();
+
+    let result = db::update_user_setting(&data.database, author.id, USER_BOLD_PENDING_ONLY, "false").await?;
+
+    let mut message = MessageBuilder::new();
+    if result {
+        message.push("All thread names will be shown in bold again");
+    }
+    else {
+        message.push("All thread names are already shown in bold");
+    }
+
+    whisper(&ctx, REPLY_TITLE, &message.build()).await?;
+
+    Ok(())
+}
+
+/// Show all of your current settings.
+#[poise::command(slash_command, category = "Thread tracking", rename = "tt_settings")]
+pub(crate) async fn show_settings(ctx: CommandContext<'_>) -> CommandResult<()> {
+    let user = ctx.author();
+    let database = &ctx.data().database;
+
+    info!("listing settings for {} ({})", user.name, user.id);
+
+    let timezone = get_user_timezone(database, user.id).await?;
+    let notifications = db::get_subscriber(database, user.id).await?.is_some();
+    let quiet_hours = match get_quiet_hours(database, user.id).await {
+        Some((start, end)) => {
+            format!("{} – {}", start.format(QUIET_HOURS_FORMAT), end.format(QUIET_HOURS_FORMAT))
+        },
+        None => "not set".to_owned(),
+    };
+    let preview_length = match get_preview_length(database, user.id).await {
+        0 => "previews disabled".to_owned(),
+        length => format!("{} characters", length),
+    };
+    let categories = db::list_subscription_categories(database, user.id).await?;
+    let categories = if categories.is_empty() {
+        "all".to_owned()
+    }
+    else {
+        categories.join(", ")
+    };
+    let sort = match default_sort(database, user.id).await {
+        Some(sort) => sort.name().to_owned(),
+        None => "not set".to_owned(),
+    };
+    let digest = match get_weekly_digest_schedule(database, user.id).await {
+        Some((day, time)) => format!("{} at {}", weekday_name(day), time.format(QUIET_HOURS_FORMAT)),
+        None => "off".to_owned(),
+    };
+
+    let on_off = |enabled: bool| if enabled { "on" } else { "off" };
+
+    let mut message = MessageBuilder::new();
+    message
+        .push_bold("Timezone: ")
+        .push_line(timezone.name())
+        .push_bold("Date format: ")
+        .push_line(get_date_format(database, user.id).await.name())
+        .push_bold("Reply notifications: ")
+        .push_line(on_off(notifications))
+        .push_bold("Notification categories: ")
+        .push_line(categories)
+        .push_bold("Quiet hours: ")
+        .push_line(quiet_hours)
+        .push_bold("Reply preview length: ")
+        .push_line(preview_length)
+        .push_bold("Weekly summary: ")
+        .push_line(digest)
+        .push_bold("Timestamps: ")
+        .push_line(on_off(show_timestamps(database, user.id).await))
+        .push_bold("Bold pending threads only: ")
+        .push_line(on_off(bold_pending_only(database, user.id).await))
+        .push_bold("Thread name length: ")
+        .push_line(format!("{} characters", thread_name_length(database, user.id).await))
+        .push_bold("Default sort order: ")
+        .push_line(sort);
+
+    whisper(&ctx, "Your settings", &message.build()).await?;
+
+    Ok(())
+}
+
+/// Set the sort order used for thread lists when no sort order is given.
+#[poise::command(slash_command, category = "Thread tracking", rename = "tt_sortdefault")]
+pub(crate) async fn set_default_sort(
+    ctx: CommandContext<'_>,
+    #[description = "The default sort order for thread lists; leave empty to clear it"] sort: Option<SortResultsBy>,
+) -> CommandResult<()> {
+    const REPLY_TITLE: &str = "Default sort order";
+    let user = ctx.author();
+    let database = &ctx.data().database;
+
+    let message = match sort {
+        Some(sort) => {
+            info!("setting default sort to `{}` for {} ({})", sort.name(), user.name, user.id);
+            db::update_user_setting(database, user.id, USER_DEFAULT_SORT, sort.name()).await?;
+
+            format!("Thread lists will be sorted **{}** by default.", sort.name().to_lowercase())
+        },
+        None => {
+            info!("clearing default sort for {} ({})", user.name, user.id);
+            db::remove_user_setting(database, user.id, USER_DEFAULT_SORT).await?;
+
+            "Thread lists will no longer be sorted by default.".to_owned()
+        },
+    };
+
+    whisper(&ctx, REPLY_TITLE, &message).await?;
+
+    Ok(())
+}
+
+/// Set how many characters of thread names to show in thread lists, or reset to the default.
+#[poise::command(slash_command, category = "Thread tracking", rename = "tt_namelength")]
+pub(crate) async fn set_thread_name_length(
+    ctx: CommandContext<'_>,
+    #[description = "Number of characters of thread names to show, or leave empty to reset"]
+    #[min = 8]
+    #[max = 100]
+    length: Option<u32>,
+) -> CommandResult<()> {
+    const REPLY_TITLE: &str = "Thread name length";
+    let user = ctx.author();
+    let data = ctx.data();
+
+    let message = match length {
+        Some(length) => {
+            let length = length as usize;
+            if !(MIN_THREAD_NAME_LENGTH..=MAX_THREAD_NAME_LENGTH).contains(&length) {
+                return Err(CommandError::new(format!(
+                    "Thread name length must be between {} and {} characters.",
+                    MIN_THREAD_NAME_LENGTH, MAX_THREAD_NAME_LENGTH
+                )));
+            }
+
+            info!("setting thread name length to {} for {} ({})", length, user.name, user.id);
+            db::update_user_setting(&data.database, user.id, USER_THREAD_NAME_LENGTH, &length.to_string())
+                .await?;
+
+            format!("Thread names will be shortened to {} characters.", length)
+        },
+        None => {
+            info!("resetting thread name length for {} ({})", user.name, user.id);
+            db::remove_user_setting(&data.database, user.id, USER_THREAD_NAME_LENGTH).await?;
+
+            format!("Thread name length reset to the default of {} characters.", THREAD_NAME_LENGTH)
+        },
+    };
+
+    whisper(&ctx, REPLY_TITLE, &message).await?;
+
+    Ok(())
+}
+
+/// Send reply notification DMs to all users tracking the thread a new reply was posted in.
+pub(crate) async fn send_reply_notification(
+    reply: Message,
+    database: Database,
+    context: impl CacheHttp,
+) {
+    let guild_id = match reply.guild_id {
+        Some(id) => id,
+        None => return,
+    };
+
+    let author = reply.author;
+
+    match db::get_users_tracking_thread(&database, guild_id, reply.channel_id).await {
+        Ok(users) => {
+            let subscribers: Vec<UserId> = match db::list_subscribers(&database).await {
+                Ok(s) => s.into_iter().map(|s| s.user_id()).collect(),
+                Err(_) => return,
+            };
+
+            let full_preview = if reply.content.is_empty() {
+                reply
+                    .embeds
+                    .iter()
+                    .filter_map(|embed| embed.description.as_deref())
+                    .find(|description| !description.is_empty())
+                    .unwrap_or_default()
+            }
+            else {
+                reply.content.as_str()
+            };
+
+            let mut content = MessageBuilder::new();
+            let link = format!(
+                "https://discord.com/channels/{}/{}/{}",
+                guild_id, reply.channel_id, reply.id
+            );
+            content.push("New reply from ").mention(&author).push(" in thread ").push(link);
+
+            let content = content.build();
+            let author_nick = get_nick_or_name(&author, guild_id, &context).await;
+
+            if let Some((channel_id, role_id)) = get_notification_target(&database, guild_id).await {
+                if !has_other_tracker(&database, guild_id, &users, &author, &author_nick).await {
+                    // Nobody else is waiting on this reply, so there's nothing to tell the channel about
+                    return;
+                }
+
+                info!(
+                    "Posting reply notification for thread {} in channel {}",
+                    reply.channel_id, channel_id
+                );
+
+                let mut channel_content = MessageBuilder::new();
+                if let Some(role_id) = role_id {
+                    channel_content.role(role_id).push(" ");
+                }
+                channel_content.push(content.as_str());
+
+                let result = send_role_mention_message(
+                    &context,
+                    channel_id,
+                    &channel_content.build(),
+                    role_id,
+                    track_thread_button(guild_id, reply.channel_id),
+                )
+                .await;
+                if let Err(e) = result {
+                    error!("Unable to post reply notification in channel {}: {}", channel_id, e);
+                }
+
+                return;
+            }
+
+            for user in users {
+                if user == author.id {
+                    // Don't notify people of their own replies
+                    continue;
+                }
+
+                let muses = match muses::get_list(&database, user, guild_id).await {
+                    Ok(m) => m,
+                    Err(e) => {
+                        error!("Unable to get muses for user {}: {}", user, e);
+                        Vec::new()
+                    },
+                };
+
+                if subscribers.contains(&user) && !muses::muse_matches(&author, &author_nick, &muses) {
+                    match db::is_subscribed_to_thread_category(&database, user, guild_id, reply.channel_id).await {
+                        Ok(true) => {},
+                        Ok(false) => continue,
+                        Err(e) => error!("Unable to check notification categories for user {}: {}", user, e),
+                    }
+
+                    let server_name =
+                        get_guild_display_name(&database, Some(user), guild_id, &context).await;
+                    // Each user may track the thread under a different category, so use the recipient's own entry
+                    let category =
+                        match db::get_thread(&database, guild_id.get(), reply.channel_id.get(), user.get()).await {
+                            Ok(thread) => thread.and_then(|t| t.category),
+                            Err(e) => {
+                                error!("Unable to look up thread category for user {}: {}", user, e);
+                                None
+                            },
+                        };
+
+                    let mut user_content = MessageBuilder::new();
+                    user_content.push(content.as_str());
+                    if let Some(category) = category {
+                        user_content.push(" (category: ").push_safe(category).push(")");
+                    }
+
+                    let content = user_content.push(" on ").push_bold_safe(server_name).build();
+
+                    let (content, reply_preview) =
+                        cap_notification(&content, full_preview, get_preview_length(&database, user).await);
+                    let preview_title = reply_preview.as_ref().map(|_| "Reply preview");
+
+                    if in_quiet_hours(&database, user).await {
+                        info!("Queueing reply notification for user ID {} during quiet hours", user);
+
+                        let result = db::add_queued_notification(
+                            &database,
+                            user,
+                            guild_id,
+                            reply.channel_id,
+                            &content,
+                            preview_title,
+                            reply_preview.as_deref(),
+                        )
+                        .await;
+                        if let Err(e) = result {
+                            error!("Unable to queue reply notification for user {}: {}", user, e);
+                        }
+
+                        continue;
+                    }
+
+                    info!("Sending reply notification to user ID {}", user);
+
+                    let button = track_thread_button(guild_id, reply.channel_id);
+                    let result = retry_transient(|| {
+                        dm(&context, user, &content, preview_title, reply_preview.as_deref(), button.clone())
+                    })
+                    .await;
+                    if let Err(e) = result {
+                        error!("Unable to DM user {} for thread reply notification: {}", user, e);
+                    }
+                }
+            }
+        },
+        Err(e) => {
+            let server_name = get_guild_display_name(&database, None, guild_id, &context).await;
+            error!(
+                "Error getting users tracking thread {} in `{}` ({}): {}",
+                reply.channel_id, server_name, guild_id, e
+            );
+        },
+    };
+}
+
+/// Check whether anyone other than the author of a reply tracks its thread, without the author being one of their
+/// own muses.
+async fn has_other_tracker(
+    database: &Database,
+    guild_id: GuildId,
+    users: &[UserId],
+    author: &User,
+    author_nick: &str,
+) -> bool {
+    for &user in users.iter().filter(|&&user| user != author.id) {
+        match muses::get_list(database, user, guild_id).await {
+            Ok(muses) if muses::muse_matches(author, author_nick, &muses) => {},
+            Ok(_) => return true,
+            Err(e) => {
+                error!("Unable to get muses for user {}: {}", user, e);
+                return true;
+            },
+        }
+    }
+
+    false
+}
+
+/// Cap a reply notification's content and preview to what Discord allows in a message and embed. The preview is
+/// also cut to the user's preferred length, and left out if that is 0 or there's nothing to preview.
+fn cap_notification(content: &str, full_preview: &str, preview_length: usize) -> (String, Option<String>) {
+    let content = truncate_string(content, MAX_MESSAGE_CHARS);
+    let preview = match preview_length.min(MAX_EMBED_CHARS) {
+        0 => None,
+        length => Some(truncate_string(full_preview, length)),
+    }
+    .filter(|preview| !preview.is_empty());
+
+    (content, preview)
+}
+
+/// Build the button for reply notifications which tracks the thread for whoever clicks it.
+fn track_thread_button(guild_id: GuildId, channel_id: ChannelId) -> Vec<CreateActionRow> {
+    let custom_id = format!("{}:{}:{}", TRACK_THREAD_BUTTON_ID, guild_id, channel_id);
+
+    vec![CreateActionRow::Buttons(vec![
+        CreateButton::new(custom_id).label("Track this thread").style(ButtonStyle::Secondary),
+    ])]
+}
+
+/// Track the thread from a reply notification for the user who clicked its track button.
+pub(crate) async fn track_from_notification(
+    ctx: &Context,
+    interaction: &ComponentInteraction,
+    data: &Data,
+) -> anyhow::Result<()> {
+    let custom_id = &interaction.data.custom_id;
+    let ids = custom_id
+        .strip_prefix(TRACK_THREAD_BUTTON_ID)
+        .and_then(|ids| ids.strip_prefix(':'))
+        .and_then(|ids| ids.split_once(':'))
+        .and_then(|(guild_id, channel_id)| Some((guild_id.parse::<u64>().ok()?, channel_id.parse::<u64>().ok()?)));
+    let Some((guild_id, channel_id)) = ids else {
+        return Err(anyhow::anyhow!("Invalid track button ID `{}`", custom_id));
+    };
+
+    let user = &interaction.user;
+    let channel_id = ChannelId::new(channel_id);
+    let database = &data.database;
+
+    let tracked_count = db::count_threads(database, user.id.get()).await?;
+    let message = if tracked_count >= MAX_TRACKED_THREADS_PER_USER {
+        format!(
+            "You are already tracking {} threads, which is the maximum of {}. Please untrack some threads before adding more.",
+            tracked_count, MAX_TRACKED_THREADS_PER_USER
+        )
+    }
+    else if db::add_thread(database, guild_id, channel_id.get(), user.id.get(), None).await? {
+        info!("Adding tracked thread {} for user `{}` ({}) from a notification", channel_id, user.name, user.id);
+        data.add_tracked_thread(channel_id).await;
+
+        let channel = channel_id.to_channel(ctx).await.ok().and_then(|c| c.guild());
+        cache_last_channel_message(channel.as_ref(), ctx, &data.message_cache).await;
+
+        format!("Now tracking {}.", channel_id.mention())
     }
+    else {
+        format!("You are already tracking {}.", channel_id.mention())
+    };
+
+    let response = CreateInteractionResponseMessage::new().content(message).ephemeral(true);
+    interaction.create_response(ctx, CreateInteractionResponse::Message(response)).await?;
 
     Ok(())
 }
 
-#[poise::command(slash_command, category = "Thread tracking", rename = "tt_timestamps", subcommands("set_timestamps_on", "set_timestamps_off"))]
-pub(crate) async fn set_timestamps(ctx: CommandContext<'_>) -> CommandResult<()> {
-    send_invalid_command_call_error(ctx).await
-}
+/// Send any queued reply notifications for users whose quiet hours have ended.
+pub(crate) async fn send_queued_notifications(
+    database: Database,
+    context: impl CacheHttp,
+) -> anyhow::Result<()> {
+    let queued = db::list_queued_notifications(&database).await?;
 
-#[poise::command(slash_command, category = "Thread tracking", rename = "on")]
-pub(crate) async fn set_timestamps_on(ctx: CommandContext<'_>) -> CommandResult<()> {
-    const REPLY_TITLE: &str = "Enable timestamps";
-    let data = ctx.data();
-    let author = ctx.author();
+    for (user, notifications) in partition_into_map(queued, |n| n.user_id()) {
+        let subscribed = db::get_subscriber(&database, user).await?.is_some();
+        if subscribed && in_quiet_hours(&database, user).await {
+            continue;
+        }
 
-    let result = db::update_user_setting(&data.database, author.id, USER_SHOW_TIMESTAMPS, "true").await?;
+        info!("Sending {} queued reply notifications to user ID {}", notifications.len(), user);
+        for notification in notifications {
+            if subscribed {
+                let result = dm(
+                    &context,
+                    user,
+                    &notification.content,
+                    notification.preview_title.as_deref(),
+                    notification.preview.as_deref(),
+                    notification
+                        .thread()
+                        .map(|(guild_id, channel_id)| track_thread_button(guild_id, channel_id))
+                        .unwrap_or_default(),
+                )
+                .await;
+                if let Err(e) = result {
+                    error!("Unable to DM user {} for queued reply notification: {}", user, e);
+                }
+            }
 
-    let mut message = MessageBuilder::new();
-    if result {
-        message.push("Timestamps successfully enabled");
-    }
-    else {
-        message.push("Timestamps are already enabled");
+            db::remove_queued_notification(&database, notification.id).await?;
+        }
     }
 
-    whisper(&ctx, REPLY_TITLE, &message.build()).await?;
-
     Ok(())
 }
 
-#[poise::command(slash_command, category = "Thread tracking", rename = "off")]
-pub(crate) async fn set_timestamps_off(ctx: CommandContext<'_>) -> CommandResult<()> {
-    const REPLY_TITLE: &str = "Disable timestamps";
-    let data = ctx.data();
-    let author = ctx.author();
+/// Send a summary of their pending threads to each user whose weekly digest is due.
+pub(crate) async fn send_weekly_digests(data: &Data, context: impl CacheHttp) -> anyhow::Result<()> {
+    let database = &data.database;
 
-    let result = db::update_user_setting(&data.database, author.id, USER_SHOW_TIMESTAMPS, "false").await?;
+    for setting in db::list_user_settings(database, USER_WEEKLY_DIGEST).await? {
+        let user_id = setting.user_id();
+        let Some((day, time)) = parse_weekly_digest_schedule(&setting.value) else {
+            continue;
+        };
 
-    let mut message = MessageBuilder::new();
-    if result {
-        message.push("Timestamps successfully disabled");
+        let timezone = get_user_timezone(database, user_id).await.unwrap_or(Tz::UTC);
+        let Some(due) = last_weekly_occurrence(day, time, timezone) else {
+            continue;
+        };
+
+        let last_sent = get_user_setting(database, user_id, USER_WEEKLY_DIGEST_LAST_SENT)
+            .await?
+            .and_then(|s| s.value.parse::<i64>().ok())
+            .unwrap_or(0);
+        if last_sent >= due {
+            continue;
+        }
+
+        // Record the digest as sent first, so that a failure doesn't resend it every time this task runs.
+        let now = Utc::now().timestamp().to_string();
+        db::update_user_setting(database, user_id, USER_WEEKLY_DIGEST_LAST_SENT, &now).await?;
+
+        info!("Sending weekly digest to user ID {}", user_id);
+        if let Err(e) = send_weekly_digest(user_id, data, &context).await {
+            error!("Unable to send weekly digest to user {}: {}", user_id, e);
+        }
     }
-    else {
-        message.push("Timestamps are already disabled");
+
+    Ok(())
+}
+
+/// Send a user one DM per server listing the tracked threads there which are awaiting their reply.
+async fn send_weekly_digest(user_id: UserId, data: &Data, context: &impl CacheHttp) -> anyhow::Result<()> {
+    let user = user_id.to_user(context).await?;
+    let threads = db::list_all_threads_for_user(&data.database, user_id.get()).await?;
+    let sort = default_sort(&data.database, user_id).await;
+
+    let mut intro = Some("Here's your weekly summary of the threads awaiting your reply.");
+    for guild_id in threads.iter().map(|t| t.guild_id).collect::<BTreeSet<_>>() {
+        let guild_id = GuildId::new(guild_id);
+        // The digest isn't a sign the user has looked at their threads, so leave them unseen
+        let list = match render_pending_thread_list(&user, guild_id, None, sort, data, context).await {
+            Ok((list, _)) if list.trim() != NO_PENDING_THREADS => list,
+            Ok(_) => continue,
+            Err(e) => {
+                error!("Unable to list pending threads in guild {} for weekly digest: {}", guild_id, e);
+                continue;
+            },
+        };
+
+        let name = get_guild_display_name(&data.database, Some(user_id), guild_id, context).await;
+        let title = format!("Awaiting your reply in {}", name);
+        dm(context, user_id, intro.take().unwrap_or_default(), Some(&title), Some(&list), Vec::new()).await?;
     }
 
-    whisper(&ctx, REPLY_TITLE, &message.build()).await?;
+    if intro.is_some() {
+        dm(context, user_id, "No tracked threads are awaiting your reply this week.", None, None, Vec::new()).await?;
+    }
 
     Ok(())
 }
 
-/// Send reply notification DMs to all users tracking the thread a new reply was posted in.
-pub(crate) async fn send_reply_notification(
-    reply: Message,
-    database: Database,
-    context: impl CacheHttp,
-) {
-    let guild_id = match reply.guild_id {
-        Some(id) => id,
-        None => return,
-    };
+/// Get the day and local time the user's weekly digest is sent at, if they've turned it on.
+async fn get_weekly_digest_schedule(database: &Database, user_id: UserId) -> Option<(Weekday, NaiveTime)> {
+    let setting = get_user_setting(database, user_id, USER_WEEKLY_DIGEST).await.ok()??;
+    parse_weekly_digest_schedule(&setting.value)
+}
 
-    let author = reply.author;
+/// Parse a stored weekly digest schedule, in the form `Mon 18:00`.
+fn parse_weekly_digest_schedule(value: &str) -> Option<(Weekday, NaiveTime)> {
+    let (day, time) = value.split_once(' ')?;
+    Some((day.parse().ok()?, parse_quiet_hours_time(time).ok()?))
+}
 
-    match db::get_users_tracking_thread(&database, guild_id, reply.channel_id).await {
-        Ok(users) => {
-            let subscribers: Vec<UserId> = match db::list_subscribers(&database).await {
-                Ok(s) => s.into_iter().map(|s| s.user_id()).collect(),
-                Err(_) => return,
-            };
+/// Get the unix timestamp of the most recent time the given day and local time came around, in the given timezone.
+fn last_weekly_occurrence(day: Weekday, time: NaiveTime, timezone: Tz) -> Option<i64> {
+    let now = Utc::now().with_timezone(&timezone);
+    let days_since = (7 + now.weekday().num_days_from_monday() - day.num_days_from_monday()) % 7;
+    let mut date = now.date_naive().checked_sub_days(Days::new(days_since.into()))?;
+
+    loop {
+        // Local times skipped by a daylight saving change fall back to the same time the week before.
+        if let Some(occurrence) = timezone.from_local_datetime(&date.and_time(time)).earliest() {
+            if occurrence <= now {
+                return Some(occurrence.timestamp());
+            }
+        }
 
-            let (preview_title, reply_preview) = {
-                let mut preview = truncate_string(&reply.content, MAX_EMBED_CHARS);
-                if preview.is_empty() && !reply.embeds.is_empty() {
-                    let embed = reply.embeds.iter().find(|&embed| match &embed.description {
-                        Some(description) => !description.is_empty(),
-                        None => false,
-                    });
+        date = date.checked_sub_days(Days::new(7))?;
+    }
+}
 
-                    if let Some(embed) = embed {
-                        preview.push_str(embed.description.as_ref().unwrap());
-                    }
-                }
+/// The full name of a day of the week, for display.
+fn weekday_name(day: Weekday) -> &'static str {
+    match day {
+        Weekday::Mon => "Monday",
+        Weekday::Tue => "Tuesday",
+        Weekday::Wed => "Wednesday",
+        Weekday::Thu => "Thursday",
+        Weekday::Fri => "Friday",
+        Weekday::Sat => "Saturday",
+        Weekday::Sun => "Sunday",
+    }
+}
 
-                if preview.is_empty() {
-                    (None, None)
-                }
-                else {
-                    (Some("Reply preview"), Some(preview))
-                }
-            };
+/// Get the user's preferred reply preview length for notifications, defaulting to the maximum embed length.
+async fn get_preview_length(database: &Database, user_id: UserId) -> usize {
+    get_user_setting(database, user_id, USER_PREVIEW_LENGTH)
+        .await
+        .ok()
+        .flatten()
+        .and_then(|s| s.value.parse::<usize>().ok())
+        .unwrap_or(MAX_EMBED_CHARS)
+}
 
-            let mut content = MessageBuilder::new();
-            let link = format!(
-                "https://discord.com/channels/{}/{}/{}",
-                guild_id, reply.channel_id, reply.id
-            );
-            content.push("New reply from ").mention(&author).push(" in thread ").push(link);
+/// Determine whether the user's current local time falls within their configured quiet hours.
+async fn in_quiet_hours(database: &Database, user_id: UserId) -> bool {
+    let Some((start, end)) = get_quiet_hours(database, user_id).await else {
+        return false;
+    };
 
-            let content = content.build();
+    let timezone = get_user_timezone(database, user_id).await.unwrap_or(Tz::UTC);
+    let now = Utc::now().with_timezone(&timezone).time();
 
-            for user in users {
-                if user == author.id {
-                    // Don't notify people of their own replies
-                    continue;
-                }
+    if start <= end {
+        start <= now && now < end
+    }
+    else {
+        // Quiet hours span midnight
+        now >= start || now < end
+    }
+}
 
-                let muses = match muses::get_list(&database, user, guild_id).await {
-                    Ok(m) => m,
-                    Err(e) => {
-                        error!("Unable to get muses for user {}: {}", user, e);
-                        Vec::new()
-                    },
-                };
+/// Get the user's quiet hours start and end times, if both are set.
+async fn get_quiet_hours(database: &Database, user_id: UserId) -> Option<(NaiveTime, NaiveTime)> {
+    let start = get_user_setting(database, user_id, USER_QUIET_HOURS_START).await.ok()??;
+    let end = get_user_setting(database, user_id, USER_QUIET_HOURS_END).await.ok()??;
 
-                if subscribers.contains(&user) && !muses.contains(&author.name) {
-                    info!("Sending reply notification to user ID {}", user);
+    Some((parse_quiet_hours_time(&start.value).ok()?, parse_quiet_hours_time(&end.value).ok()?))
+}
 
-                    if let Err(e) =
-                        dm(&context, user, &content, preview_title, reply_preview.as_deref()).await
-                    {
-                        error!("Unable to DM user {} for thread reply notification: {}", user, e);
-                    }
-                }
-            }
-        },
-        Err(e) => error!(
-            "Error getting users tracking thread {} in guild {}: {}",
-            reply.channel_id, guild_id, e
-        ),
-    };
+/// Parse a quiet hours time in `hh:mm` format.
+fn parse_quiet_hours_time(time: &str) -> CommandResult<NaiveTime> {
+    NaiveTime::parse_from_str(time.trim(), QUIET_HOURS_FORMAT)
+        .map_err(|e| CommandError::detailed(format!("Unable to parse time '{}'", time), e))
 }
 
 /// Get a random thread for the current user that is awaiting a reply.
@@ -785,12 +3161,19 @@ async fn get_pending_threads(
 ) -> CommandResult<Vec<(LastReplyInfo, TrackedThread)>> {
     let guild_user = GuildUser { user_id: user.id, guild_id };
     let muses = muses::get_list(&data.database, guild_user.user_id, guild_user.guild_id).await?;
+    let snoozed: HashSet<u64> =
+        db::list_snoozed_thread_ids(&data.database, guild_id.get(), user.id.get()).await?.into_iter().collect();
     let mut pending_threads = Vec::new();
 
-    for thread in enumerate(&data.database, &guild_user, category).await? {
-        let last_reply_info = get_last_responder(&thread, context, &data.message_cache).await;
-        if let Some(reply_info) = last_reply_info {
-            if reply_info.author.id != user.id && !muses.contains(&reply_info.author_nick) {
+    let threads: Vec<TrackedThread> = enumerate(&data.database, &guild_user, category)
+        .await?
+        .filter(|t| !snoozed.contains(&t.channel_id))
+        .collect();
+    for (last_reply_info, thread) in get_last_responders(threads, context, &data.message_cache, &data.unavailable_channels).await {
+        if let Ok(Some(reply_info)) = last_reply_info {
+            let own_reply = reply_info.author.id == user.id
+                || muses::muse_matches(&reply_info.author, &reply_info.author_nick, &muses);
+            if !own_reply {
                 pending_threads.push((reply_info, thread));
             }
         }
@@ -800,63 +3183,88 @@ async fn get_pending_threads(
 }
 
 /// Build a formatted thread and todo list message.
+#[allow(clippy::too_many_arguments)]
 pub(crate) async fn get_formatted_list(
     threads: Vec<TrackedThread>,
     todos: Vec<Todo>,
     sort: Option<SortResultsBy>,
+    database: &Database,
     context: &impl CacheHttp,
     message_cache: &MessageCache,
+    active_threads_cache: &ActiveThreadsCache,
+    unavailable_channels: &UnavailableChannelCache,
     user_data: &UserData,
 ) -> Result<String, SerenityError> {
-    let mut threads = categorise(threads);
+    // Todos linked to a thread in the list are shown under that thread rather than in their category.
+    let listed_threads: HashSet<ChannelId> = threads.iter().map(|t| t.channel_id()).collect();
+    let (thread_todos, todos): (Vec<Todo>, Vec<Todo>) = todos
+        .into_iter()
+        .partition(|t| t.channel_id().is_some_and(|id| listed_threads.contains(&id)));
+    let thread_todos = partition_into_map(thread_todos, |t| t.channel_id());
+
+    let threads = categorise(threads);
     let todos = todos::categorise(todos);
 
+    // If the active threads can't be fetched, thread names are looked up individually instead.
+    let guild_id = user_data.guild_id;
     let mut guild_threads: HashMap<ChannelId, String> = HashMap::new();
-    for channel in user_data.guild_id.get_active_threads(context.http()).await?.threads.into_iter() {
-        cache_last_channel_message(Some(&channel), context.http(), message_cache).await;
-        guild_threads.insert(channel.id, channel.name);
+    match active_threads_cache
+        .get_or_else(&guild_id, || async {
+            get_active_threads(guild_id, context).await
+        })
+        .await
+    {
+        Ok(active_threads) => {
+            for channel in active_threads.iter() {
+                cache_last_channel_message(Some(channel), context.http(), message_cache).await;
+                guild_threads.insert(channel.id, channel.name.clone());
+            }
+        },
+        Err(e) => error!("Error fetching active threads for guild {}: {}", guild_id, e),
     }
 
     let mut message = MessageBuilder::new();
 
-    let mut categories = BTreeSet::new();
-    for key in threads.keys() {
-        categories.insert(key.clone());
-    }
-
-    for key in todos.keys() {
-        categories.insert(key.clone());
-    }
+    let mut categories: Vec<Option<String>> =
+        threads.keys().chain(todos.keys()).cloned().collect::<BTreeSet<_>>().into_iter().collect();
+    let category_order = get_category_order(database, user_data.id, guild_id).await;
+    categories.sort_by_cached_key(|name| category_sort_key(name, &category_order));
 
     for name in categories {
         if let Some(n) = &name {
             message.push("### ").push_line(n).push_line("");
         }
 
-        if let Some(threads) = threads.get_mut(&name) {
-            let mut threads_reply_info = Vec::new();
-            for thread in threads {
-                let last_responder = get_last_responder(thread, context, message_cache).await;
-                threads_reply_info.push((last_responder, thread));
-            }
+        if let Some(threads) = threads.get(&name) {
+            let mut threads_reply_info =
+                get_last_responders(threads, context, message_cache, unavailable_channels).await;
 
             if let Some(sort) = sort {
                 match sort {
-                    SortResultsBy::NewestFirst => threads_reply_info.sort_by_key(|x| x.0.as_ref().map(|r| r.timestamp)),
-                    SortResultsBy::OldestFirst => threads_reply_info.sort_by_key(|x| x.0.as_ref().map(|r| Reverse(r.timestamp))),
+                    SortResultsBy::NewestFirst => threads_reply_info.sort_by_key(|x| last_reply(&x.0).map(|r| r.timestamp)),
+                    SortResultsBy::OldestFirst => threads_reply_info.sort_by_key(|x| last_reply(&x.0).map(|r| Reverse(r.timestamp))),
+                    SortResultsBy::PendingFirst => threads_reply_info.sort_by_key(|x| {
+                        let replied = last_reply(&x.0).is_some_and(|r| user_data.is_own_reply(r));
+                        (replied, last_reply(&x.0).map(|r| r.timestamp))
+                    }),
                 }
             }
 
-            for (_, thread) in threads_reply_info {
+            for (reply_info, thread) in threads_reply_info {
                 push_thread_line(
                     &mut message,
                     thread,
+                    reply_info,
                     &guild_threads,
+                    database,
                     context,
-                    message_cache,
                     user_data,
                 )
                 .await;
+
+                for todo in thread_todos.get(&Some(thread.channel_id())).into_iter().flatten() {
+                    message.push_line(format!("  - {}", todo.content));
+                }
             }
         }
 
@@ -894,55 +3302,150 @@ fn categorise(threads: Vec<TrackedThread>) -> BTreeMap<Option<String>, Vec<Track
     partition_into_map(threads, |t| t.category.clone())
 }
 
-/// Get the last user that responded to the thread, if any.
+/// Get the last user that responded to the thread, if any. Returns an error if the thread itself can't be looked up.
+/// Channels which recently couldn't be found or accessed are skipped until their `unavailable_channels` entry expires.
 async fn get_last_responder(
     thread: &TrackedThread,
     context: impl CacheHttp,
     message_cache: &MessageCache,
-) -> Option<LastReplyInfo> {
-    match context.http().get_channel(thread.channel_id.into()).await {
-        Ok(Channel::Guild(channel)) => {
-            let last_message = if let Some(last_message_id) = channel.last_message_id {
-                let channel_message = (last_message_id, channel.id).into();
-                message_cache
-                    .get_or_else(&channel_message, || channel_message.fetch(context.http()))
-                    .await
-                    .ok()
-            }
-            else {
-                None
-            };
+    unavailable_channels: &UnavailableChannelCache,
+) -> Result<Option<LastReplyInfo>, SerenityError> {
+    let start_time = Instant::now();
 
-            // This fallback is necessary as Discord may not report a correct or available message as the last_message_id.
-            // Messages can be deleted or otherwise unavailable, so this fallback should get the most recent
-            // *available* message in the channel.
-            let last_message = match last_message {
-                Some(m) => Some(m),
-                None => get_last_channel_message(channel, &context).await.map(Arc::new),
-            };
+    if unavailable_channels.get(&thread.channel_id()).await.is_some() {
+        return Err(SerenityError::Other("channel was recently unavailable"));
+    }
 
-            if let Some(message) = last_message {
-                let nick = get_nick_or_name(&message.author, thread.guild_id(), &context).await;
-                Some(LastReplyInfo::new(message.as_ref(), nick))
-            }
-            else {
-                None
+    // `to_channel` consults serenity's cache before falling back to an HTTP request.
+    let channel = match thread.channel_id().to_channel(&context).await {
+        Ok(Channel::Guild(channel)) => channel,
+        Ok(_) => return Ok(None),
+        Err(e) => {
+            // Only remember definite failures; anything else may succeed on the next render.
+            if is_not_found(&e) || is_forbidden(&e) {
+                unavailable_channels.store(thread.channel_id(), ()).await;
             }
+
+            return Err(e);
+        },
+    };
+
+    let last_message = match channel.last_message_id {
+        Some(last_message_id) => {
+            let channel_message = (last_message_id, channel.id).into();
+            message_cache
+                .get_or_else(&channel_message, || channel_message.fetch(context.http()))
+                .await
+                .ok()
+                .filter(|m| is_reply(m, &context))
+        },
+        None => None,
+    };
+
+    // This fallback is necessary as Discord may not report a correct or available message as the last_message_id.
+    // Messages can be deleted or otherwise unavailable, or the latest message may be Titi's own, so this fallback
+    // should get the most recent *available* reply in the channel.
+    let last_message = match last_message {
+        Some(m) => Some(m),
+        None => get_last_channel_message(channel.id, &context).await.map(Arc::new),
+    };
+
+    let result = match last_message {
+        Some(message) => {
+            let nick = get_nick_or_name(&message.author, thread.guild_id(), &context).await;
+            Some(LastReplyInfo::new(message.as_ref(), nick))
         },
-        _ => None,
+        None => None,
+    };
+
+    debug!(
+        "found last responder for thread {} in {:.2} ms",
+        thread.channel_id,
+        start_time.elapsed().as_secs_f64() * 1000.0
+    );
+
+    Ok(result)
+}
+
+/// Get the last reply from a thread lookup, if the thread could be looked up and has any replies.
+fn last_reply(lookup: &Result<Option<LastReplyInfo>, SerenityError>) -> Option<&LastReplyInfo> {
+    lookup.as_ref().ok().and_then(Option::as_ref)
+}
+
+/// Get the last responders for each of the given threads, looking them up concurrently while preserving their order.
+async fn get_last_responders<T>(
+    threads: impl IntoIterator<Item = T>,
+    context: &impl CacheHttp,
+    message_cache: &MessageCache,
+    unavailable_channels: &UnavailableChannelCache,
+) -> Vec<(Result<Option<LastReplyInfo>, SerenityError>, T)>
+where
+    T: Borrow<TrackedThread>,
+{
+    let mut lookups = Vec::new();
+    for thread in threads {
+        lookups.push(get_last_responder_for(thread, context, message_cache, unavailable_channels));
     }
+
+    stream::iter(lookups).buffered(MAX_CONCURRENT_THREAD_LOOKUPS).collect().await
+}
+
+/// Get the channels of the given threads which are awaiting a reply from the user or one of their muses.
+pub(crate) async fn get_pending_channels(
+    threads: &[TrackedThread],
+    context: &impl CacheHttp,
+    message_cache: &MessageCache,
+    unavailable_channels: &UnavailableChannelCache,
+    user_data: &UserData,
+) -> Vec<ChannelId> {
+    get_last_responders(threads, context, message_cache, unavailable_channels)
+        .await
+        .into_iter()
+        .filter(|(reply_info, _)| last_reply(reply_info).is_some_and(|r| !user_data.is_own_reply(r)))
+        .map(|(_, thread)| thread.channel_id())
+        .collect()
+}
+
+async fn get_last_responder_for<T: Borrow<TrackedThread>>(
+    thread: T,
+    context: &impl CacheHttp,
+    message_cache: &MessageCache,
+    unavailable_channels: &UnavailableChannelCache,
+) -> (Result<Option<LastReplyInfo>, SerenityError>, T) {
+    let reply_info = get_last_responder(thread.borrow(), context, message_cache, unavailable_channels).await;
+    (reply_info, thread)
+}
+
+/// How many of a thread's most recent messages are checked for the last reply. Set once at startup from the
+/// configuration, or left at the default.
+static CONFIGURED_LAST_REPLY_LOOKBACK: AtomicU8 = AtomicU8::new(LAST_REPLY_LOOKBACK);
+
+/// Set how many of a thread's most recent messages are checked for the last reply. Discord returns at most
+/// `MAX_LAST_REPLY_LOOKBACK` messages at once, so larger values are capped.
+pub(crate) fn set_last_reply_lookback(lookback: usize) {
+    let lookback = lookback.clamp(1, MAX_LAST_REPLY_LOOKBACK as usize) as u8;
+    CONFIGURED_LAST_REPLY_LOOKBACK.store(lookback, Ordering::Relaxed);
 }
 
-/// Get the last message from a channel, if any.
+/// Get the last reply in a channel, if any, looking back over a few of the most recent messages.
 async fn get_last_channel_message(
-    channel: GuildChannel,
+    channel_id: ChannelId,
     context: impl CacheHttp,
 ) -> Option<Message> {
-    channel
-        .messages(context.http(), GetMessages::new().limit(1))
+    let lookback = CONFIGURED_LAST_REPLY_LOOKBACK.load(Ordering::Relaxed);
+    channel_id
+        .messages(context.http(), GetMessages::new().limit(lookback))
         .await
-        .ok()
-        .and_then(|mut m| m.pop())
+        .ok()?
+        .into_iter()
+        .find(|m| is_reply(m, &context))
+}
+
+/// Determine whether a message counts as a reply to a thread. Titi's own messages, such as watchers, and system
+/// messages like pins or members joining don't count.
+fn is_reply(message: &Message, context: &impl CacheHttp) -> bool {
+    let own_message = context.cache().is_some_and(|cache| cache.current_user().id == message.author.id);
+    !own_message && matches!(message.kind, MessageType::Regular | MessageType::InlineReply)
 }
 
 /// Get the user's nickname in the given guild, or their username.
@@ -959,60 +3462,95 @@ async fn get_nick_or_name(user: &User, guild_id: GuildId, cache_http: impl Cache
 async fn push_thread_line<'a>(
     message: &'a mut MessageBuilder,
     thread: &TrackedThread,
+    last_message_author: Result<Option<LastReplyInfo>, SerenityError>,
     guild_threads: &HashMap<ChannelId, String>,
+    database: &Database,
     context: &impl CacheHttp,
-    message_cache: &MessageCache,
     user_data: &UserData,
 ) -> &'a mut MessageBuilder {
-    let last_message_author = get_last_responder(thread, context, message_cache).await;
+    let pending = matches!(&last_message_author, Ok(Some(r)) if !user_data.is_own_reply(r));
+    let bold = pending || !user_data.bold_pending_only;
 
+    // There's no point looking up the name of a thread that couldn't be looked up just now, so use its last-known name.
+    let name = match &last_message_author {
+        Err(_) => guild_threads.get(&thread.channel_id()).cloned().or_else(|| thread.thread_name.clone()),
+        Ok(_) => guild_threads.get(&thread.channel_id()).cloned(),
+    };
     let mut link: MessageBuilder =
-        get_thread_link(thread, guild_threads.get(&thread.channel_id()).cloned(), context).await;
+        get_thread_link(thread, name, user_data.thread_name_length, bold, database, context).await;
     // Thread entries in blockquotes
     message.push("- ").push(link.build()).push(" — ");
 
     match last_message_author {
-        Some(reply_info) => {
-            let last_author_name = get_nick_or_name(&reply_info.author, thread.guild_id(), context).await;
-            if reply_info.author.id == user_data.id || user_data.muses.contains(&last_author_name) {
-                message.push(last_author_name);
+        Ok(Some(reply_info)) => {
+            let unread = user_data.is_unread(thread.channel_id(), &reply_info);
+            if user_data.is_own_reply(&reply_info) {
+                message.push(reply_info.author_nick);
             }
             else {
-                message.push(Bold + last_author_name);
+                message.push(Bold + reply_info.author_nick);
+            }
+
+            if unread {
+                message.push(" ").push(Italic + "(new)");
             }
 
             if user_data.show_timestamps {
                 message.push(" (")
                     .push_timestamp(reply_info.timestamp)
-                    .push_line(")")
+                    .push_line(")");
+            }
+            else {
+                message.push_line("");
+            }
+        },
+        Ok(None) => {
+            if bold {
+                message.push_line(Bold + "No replies yet");
             }
             else {
-                message.push_line("")
+                message.push_line("No replies yet");
             }
         },
-        None => message.push_line(Bold + "No replies yet"),
+        Err(e) => {
+            debug!("Unable to look up thread {}: {}", thread.channel_id, e);
+            message.push_line(Italic + "(unavailable)");
+        },
+    }
+
+    if let Some(note) = &thread.note {
+        message.push("  -# ").push_line_safe(note);
     }
+
+    message
 }
 
 /// Build a thread link, either as a named link or a simple thread mention if the name isn't provided and can't be looked up.
+/// The user's label for the thread is used instead of its name if they've set one. Named links are shown in bold if
+/// `bold` is set.
 async fn get_thread_link(
     thread: &TrackedThread,
     name: Option<String>,
+    name_length: usize,
+    bold: bool,
+    database: &Database,
     cache_http: impl CacheHttp,
 ) -> MessageBuilder {
     let mut link = MessageBuilder::new();
-    let channel_name = match name {
-        Some(n) => Some(n),
-        None => get_channel_name(thread.channel_id(), cache_http).await,
+    let channel_name = match &thread.label {
+        Some(label) => Some(label.clone()),
+        None => get_thread_name(thread, name, database, cache_http).await,
     };
 
     match channel_name {
         Some(n) => {
-            let name = trim_string(&n, THREAD_NAME_LENGTH);
-            link.push_named_link(
-                Bold + format!("#{}", name),
-                format!("https://discord.com/channels/{}/{}", thread.guild_id, thread.channel_id),
-            )
+            let name = format!("#{}", escape_link_text(&trim_string(&n, name_length)));
+            if bold {
+                link.push_named_link(Bold + name, thread_url(thread))
+            }
+            else {
+                link.push_named_link(name, thread_url(thread))
+            }
         },
         None => link.push(thread.channel_id().mention().to_string()),
     };
@@ -1020,6 +3558,38 @@ async fn get_thread_link(
     link
 }
 
+/// Get the name of a thread, looking it up if it isn't provided, or the last-known name if it can't be looked up.
+/// Successfully resolved names are stored so they can be used as a fallback later.
+async fn get_thread_name(
+    thread: &TrackedThread,
+    name: Option<String>,
+    database: &Database,
+    cache_http: impl CacheHttp,
+) -> Option<String> {
+    let channel_name = match name {
+        Some(n) => Some(n),
+        None => get_channel_name(thread.channel_id(), cache_http).await,
+    };
+
+    match channel_name {
+        Some(n) => {
+            if thread.thread_name.as_ref() != Some(&n) {
+                if let Err(e) = db::update_thread_name(database, thread.guild_id, thread.channel_id, &n).await {
+                    error!("Error storing thread name for {}: {}", thread.channel_id, e);
+                }
+            }
+
+            Some(n)
+        },
+        None => thread.thread_name.clone(),
+    }
+}
+
+/// Get the URL of a thread.
+fn thread_url(thread: &TrackedThread) -> String {
+    format!("https://discord.com/channels/{}/{}", thread.guild_id, thread.channel_id)
+}
+
 /// Trim the given string to the maximum length, and append ellipsis if the string was trimmed.
 fn trim_string(name: &str, max_length: usize) -> String {
     if name.chars().count() > max_length {
@@ -1050,6 +3620,35 @@ async fn cache_last_channel_message(
     }
 }
 
+/// Get the user's default sort order for thread lists, if they have set one.
+async fn default_sort(database: &Database, user_id: UserId) -> Option<SortResultsBy> {
+    get_user_setting(database, user_id, USER_DEFAULT_SORT)
+        .await
+        .ok()
+        .flatten()
+        .and_then(|s| SortResultsBy::from_name(&s.value))
+}
+
+/// Get the number of characters of thread names the user wants to see in thread lists.
+pub(crate) async fn thread_name_length(database: &Database, user_id: UserId) -> usize {
+    get_user_setting(database, user_id, USER_THREAD_NAME_LENGTH)
+        .await
+        .ok()
+        .flatten()
+        .and_then(|s| s.value.parse::<usize>().ok())
+        .map(|length| length.clamp(MIN_THREAD_NAME_LENGTH, MAX_THREAD_NAME_LENGTH))
+        .unwrap_or(THREAD_NAME_LENGTH)
+}
+
+/// Determine whether the current user only wants threads awaiting their reply shown in bold.
+pub(crate) async fn bold_pending_only(database: &Database, user_id: UserId) -> bool {
+    get_user_setting(database, user_id, USER_BOLD_PENDING_ONLY).await
+        .map(|s| s.map(|s| s.value.parse::<bool>()))
+        .unwrap_or(None)
+        .map(|r| r.unwrap_or_default())
+        .unwrap_or_default()
+}
+
 /// Determine whether the current user has timestamps enabled
 pub(crate) async fn show_timestamps(database: &Database, user_id: UserId) -> bool {
     get_user_setting(database, user_id, USER_SHOW_TIMESTAMPS).await
@@ -1058,3 +3657,42 @@ pub(crate) async fn show_timestamps(database: &Database, user_id: UserId) -> boo
         .map(|r| r.unwrap_or_default())
         .unwrap_or_default()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cap_notification_caps_over_length_previews() {
+        let preview = "a".repeat(MAX_EMBED_CHARS * 2);
+        let (_, capped) = cap_notification("New reply", &preview, MAX_EMBED_CHARS * 3);
+
+        let capped = capped.expect("preview should be kept");
+        assert!(capped.chars().count() <= MAX_EMBED_CHARS);
+        assert!(capped.ends_with('\u{2026}'));
+    }
+
+    #[test]
+    fn cap_notification_uses_preferred_preview_length() {
+        let (_, capped) = cap_notification("New reply", "The quick brown fox jumps over the lazy dog", 10);
+
+        let capped = capped.expect("preview should be kept");
+        assert!(capped.chars().count() <= 10);
+        assert!(capped.starts_with("The qu"));
+        assert!(capped.ends_with('\u{2026}'));
+    }
+
+    #[test]
+    fn cap_notification_caps_over_length_content() {
+        let content = "b".repeat(MAX_MESSAGE_CHARS + 100);
+        let (capped, _) = cap_notification(&content, "", MAX_EMBED_CHARS);
+        assert!(capped.chars().count() <= MAX_MESSAGE_CHARS);
+        assert!(capped.ends_with('\u{2026}'));
+    }
+
+    #[test]
+    fn cap_notification_leaves_out_disabled_or_empty_previews() {
+        assert_eq!(cap_notification("New reply", "Some text", 0).1, None);
+        assert_eq!(cap_notification("New reply", "", MAX_EMBED_CHARS).1, None);
+    }
+}
@@ -1,14 +1,19 @@
 use std::collections::BTreeMap;
 
 use anyhow::anyhow;
-use serenity::utils::{ContentModifier::*, MessageBuilder};
+use serenity::{
+    model::prelude::*,
+    utils::{ContentModifier::*, MessageBuilder},
+};
 use tracing::{error, info};
 
 use super::CommandResult;
 use crate::{
+    category::Category,
     commands::CommandContext,
+    consts::{MAX_TODO_LENGTH, TODO_DISPLAY_LENGTH},
     db::{self, Todo},
-    messaging::reply,
+    messaging::{reply, send_confirmation_prompt, whisper, ConfirmationResponse},
     utils::*,
     Database,
 };
@@ -19,12 +24,21 @@ pub(crate) async fn add(
     ctx: CommandContext<'_>,
     #[description = "The content of the todo list item"] entry: String,
     #[description = "The category to track the todo list item under"] category: Option<String>,
+    #[description = "The thread to list the todo list item under"]
+    #[channel_types("NewsThread", "PrivateThread", "PublicThread", "Text")]
+    thread: Option<GuildChannel>,
 ) -> CommandResult<()> {
     let guild_id = match ctx.guild_id() {
         Some(id) => id,
         None => return Err(anyhow!("Unable to manage todo list items outside of a server").into()),
     };
 
+    let length = entry.chars().count();
+    if length > MAX_TODO_LENGTH {
+        return Err(anyhow!("To do list entries can be at most {} characters long.", MAX_TODO_LENGTH).into());
+    }
+
+    let category = Category::from_argument(category.as_deref())?;
     let data = ctx.data();
     let database = &data.database;
     let user = ctx.author();
@@ -34,10 +48,20 @@ pub(crate) async fn add(
     let mut result = MessageBuilder::new();
     let mut errors = MessageBuilder::new();
     result.push("Todo list entry ").push(Italic + &entry);
-    match db::add_todo(database, guild_id.get(), user.id.get(), &entry, category.as_deref()).await {
+    let channel_id = thread.map(|t| t.id.get());
+    match db::add_todo(database, guild_id.get(), user.id.get(), &entry, category.as_ref(), channel_id).await {
         Ok(true) => {
             result.push_line(" added successfully.");
             reply(&ctx, "To do list entry added", &result.build()).await?;
+
+            if length > TODO_DISPLAY_LENGTH {
+                let warning = format!(
+                    "This entry is {} characters long, so only the first {} will be shown in your lists. The full text is still saved.",
+                    length, TODO_DISPLAY_LENGTH
+                );
+                whisper(&ctx, "Long to do list entry", &warning).await?;
+            }
+
             Ok(())
         },
         Ok(false) => {
@@ -107,6 +131,50 @@ pub(crate) async fn remove(
     }
 }
 
+/// Move a to do list entry to a different position within its category.
+#[poise::command(slash_command, guild_only, rename = "tt_reordertodo", category = "Todo list")]
+pub(crate) async fn reorder(
+    ctx: CommandContext<'_>,
+    #[description = "The content of the todo list item to move"] entry: String,
+    #[description = "The new position of the item in its category, starting from 1"]
+    #[min = 1]
+    position: u32,
+) -> CommandResult<()> {
+    let guild_id = match ctx.guild_id() {
+        Some(id) => id,
+        None => return Err(anyhow!("Unable to manage todo list items outside of a server").into()),
+    };
+
+    let user = ctx.author();
+    let database = &ctx.data().database;
+
+    info!("moving todo `{}` to position {} for {} ({})", entry, position, user.name, user.id);
+
+    let todos = db::list_todos(database, guild_id.get(), user.id.get(), None).await?;
+    let Some(todo) = todos.iter().find(|t| t.content.to_lowercase() == entry.to_lowercase()) else {
+        return Err(anyhow!("Could not find a to do list entry matching `{}`.", entry).into());
+    };
+
+    let mut ids: Vec<i32> = todos
+        .iter()
+        .filter(|t| t.category == todo.category && t.id != todo.id)
+        .map(|t| t.id)
+        .collect();
+    let index = (position as usize - 1).min(ids.len());
+    ids.insert(index, todo.id);
+
+    db::reorder_todos(database, guild_id.get(), user.id.get(), &ids).await?;
+
+    let mut message = MessageBuilder::new();
+    message
+        .push("To do list entry ")
+        .push(Italic + &todo.content)
+        .push_line(format!(" moved to position {}.", index + 1));
+    reply(&ctx, "To do list updated", &message.build()).await?;
+
+    Ok(())
+}
+
 /// Send the full to do list.
 #[poise::command(slash_command, guild_only, rename = "tt_todolist", category = "Todo list")]
 pub(crate) async fn list(
@@ -170,6 +238,89 @@ pub(crate) async fn list(
     }
 }
 
+/// List to do list entries in categories without any tracked threads, and optionally tidy them up.
+#[poise::command(slash_command, guild_only, rename = "tt_orphans", category = "Todo list")]
+pub(crate) async fn orphans(
+    ctx: CommandContext<'_>,
+    #[description = "Remove the orphaned to do list entries"] remove: Option<bool>,
+    #[description = "Move the orphaned entries to this category; use `none` to remove their category"]
+    move_to: Option<String>,
+) -> CommandResult<()> {
+    const REPLY_TITLE: &str = "Orphaned to do list entries";
+    let guild_id = match ctx.guild_id() {
+        Some(id) => id,
+        None => return Err(anyhow!("Unable to manage todo list items outside of a server").into()),
+    };
+
+    let remove = remove.unwrap_or(false);
+    if remove && move_to.is_some() {
+        return Err(anyhow!("Please choose either to remove the orphaned entries or to move them, not both.").into());
+    }
+
+    let user = ctx.author();
+    let database = &ctx.data().database;
+
+    info!("listing orphaned todos for {} ({})", user.name, user.id);
+
+    let todos = db::list_orphaned_todos(database, guild_id.get(), user.id.get()).await?;
+    if todos.is_empty() {
+        whisper(&ctx, REPLY_TITLE, "All of your to do list categories still have tracked threads.").await?;
+        return Ok(());
+    }
+
+    let ids: Vec<i32> = todos.iter().map(|t| t.id).collect();
+
+    let mut message = MessageBuilder::new();
+    for (name, todos) in categorise(todos) {
+        message.push("## ").push_line(name.unwrap_or_default());
+
+        for item in todos {
+            push_todo_line(&mut message, &item);
+        }
+
+        message.push_line("");
+    }
+
+    let destination = Category::from_argument(move_to.as_deref())?;
+    let question = match (remove, &move_to) {
+        (true, _) => format!("Would you like to remove these {} entries?", ids.len()),
+        (false, Some(_)) => match &destination {
+            Some(category) => format!("Would you like to move these {} entries to `{}`?", ids.len(), category),
+            None => format!("Would you like to remove the category from these {} entries?", ids.len()),
+        },
+        (false, None) => {
+            message.push("Use the `remove` or `move_to` options to tidy these up.");
+            reply(&ctx, REPLY_TITLE, &message.build()).await?;
+            return Ok(());
+        },
+    };
+
+    message.push(question);
+
+    match send_confirmation_prompt(&ctx, REPLY_TITLE, &message.build(), user.id).await? {
+        ConfirmationResponse::Confirmed => {
+            let changed = if remove {
+                info!("removing {} orphaned todos for {} ({})", ids.len(), user.name, user.id);
+                db::remove_todos_by_id(database, guild_id.get(), user.id.get(), &ids).await?
+            }
+            else {
+                info!("moving {} orphaned todos for {} ({})", ids.len(), user.name, user.id);
+                db::update_todos_category(database, guild_id.get(), user.id.get(), &ids, destination.as_ref()).await?
+            };
+
+            reply(&ctx, "To do list updated", &format!("Updated {} to do list entries.", changed)).await?;
+        },
+        ConfirmationResponse::Denied => {
+            whisper(&ctx, REPLY_TITLE, "No to do list entries were changed.").await?;
+        },
+        ConfirmationResponse::TimedOut => {
+            whisper(&ctx, REPLY_TITLE, "The prompt timed out. No to do list entries were changed.").await?;
+        },
+    }
+
+    Ok(())
+}
+
 /// Partition the to do entries into categories.
 pub(crate) fn categorise(todos: Vec<Todo>) -> BTreeMap<Option<String>, Vec<Todo>> {
     partition_into_map(todos, |t| t.category.clone())
@@ -213,5 +364,10 @@ pub(crate) fn push_todo_line<'a>(
     message: &'a mut MessageBuilder,
     todo: &Todo,
 ) -> &'a mut MessageBuilder {
-    message.push_line(format!("- {}", &todo.content))
+    message.push(format!("- {}", truncate_string(&todo.content, TODO_DISPLAY_LENGTH)));
+
+    match todo.channel_id() {
+        Some(channel_id) => message.push(" (").mention(&channel_id).push_line(")"),
+        None => message.push_line(""),
+    }
 }
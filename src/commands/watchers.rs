@@ -1,16 +1,19 @@
 use anyhow::anyhow;
-use chrono::Utc;
 use serenity::{
     http::CacheHttp,
     model::{prelude::*, Colour},
-    utils::{EmbedMessageBuilding, MessageBuilder}, builder::{EditThread, EditMessage, CreateEmbed, CreateEmbedFooter},
+    prelude::SerenityError,
+    utils::{EmbedMessageBuilding, MessageBuilder}, builder::{EditThread, EditMessage, CreateEmbed, CreateMessage},
 };
-use tokio::time::Instant;
-use tracing::{error, info, warn};
+use tokio::time::{sleep, Instant};
+use tracing::{debug, error, info, warn};
 
 use super::CommandResult;
 use crate::{
-    cache::MessageCache, commands::{muses, threads::{self, show_timestamps, UserData}, todos, CommandContext}, db::{self, ThreadWatcher, Todo, TrackedThread}, messaging::{reply, whisper}, utils::get_channel_name, CommandError, Database
+    cache::{ActiveThreadsCache, MessageCache, UnavailableChannelCache},
+    category::matches_filter,
+    consts::{WATCHER_FETCH_ATTEMPTS, WATCHER_FETCH_RETRY_DELAY, WATCHER_MESSAGE_MISSING},
+    commands::{muses, threads::{self, bold_pending_only, show_timestamps, thread_name_length, UserData}, todos, CommandContext}, db::{self, ThreadWatcher, Todo, TrackedThread}, messaging::{reply, whisper}, utils::{get_channel_name, is_not_found, parse_discord_link, GuildUser, parse_snowflake, retry_transient, DiscordLink, MessageBuilderExtensions}, CommandError, Database
 };
 
 /// List currently tracked watchers.
@@ -43,10 +46,27 @@ pub(crate) async fn list(ctx: CommandContext<'_>) -> CommandResult<()> {
         );
         message
             .push_quote("- Categories: ")
-            .push(watcher.categories.as_deref().unwrap_or("All"))
-            .push(" - ")
-            .push_named_link("Link", url)
-            .push_line("");
+            .push(watcher.categories.as_deref().unwrap_or("All"));
+
+        if watcher.paused {
+            message.push(" (paused)");
+        }
+
+        message.push(" - ").push_named_link("Link", url).push(" - ");
+        match (watcher.last_error.as_deref(), watcher.last_updated) {
+            (Some(WATCHER_MESSAGE_MISSING), _) => message.push_bold("message missing"),
+            (Some(error), _) => message.push_bold("last error: ").push_safe(error),
+            (None, Some(updated)) => {
+                message.push("OK, updated ");
+                match Timestamp::from_unix_timestamp(updated) {
+                    Ok(time) => message.push_timestamp(time),
+                    Err(_) => message.push("at an unknown time"),
+                }
+            },
+            (None, None) => message.push_italic("not updated yet"),
+        };
+
+        message.push_line("");
     }
 
     reply(&ctx, "Currently active watchers", &message.build()).await?;
@@ -54,11 +74,46 @@ pub(crate) async fn list(ctx: CommandContext<'_>) -> CommandResult<()> {
     Ok(())
 }
 
+/// Stop updating all of your watchers until they're resumed.
+#[poise::command(slash_command, rename = "tt_pausewatchers", category = "Watchers")]
+pub(crate) async fn pause(ctx: CommandContext<'_>) -> CommandResult<()> {
+    set_paused(ctx, true).await
+}
+
+/// Resume updating all of your paused watchers.
+#[poise::command(slash_command, rename = "tt_resumewatchers", category = "Watchers")]
+pub(crate) async fn resume(ctx: CommandContext<'_>) -> CommandResult<()> {
+    set_paused(ctx, false).await
+}
+
+/// Pause or resume all of the command author's watchers and report how many were changed.
+async fn set_paused(ctx: CommandContext<'_>, paused: bool) -> CommandResult<()> {
+    let user = ctx.author();
+    info!("setting watchers paused to {} for {} ({})", paused, user.name, user.id);
+
+    let changed = db::set_watchers_paused(&ctx.data().database, user.id.get(), paused).await?;
+    let message = match (changed, paused) {
+        (0, true) => "You have no active watchers to pause.".to_owned(),
+        (0, false) => "You have no paused watchers to resume.".to_owned(),
+        (count, true) => format!(
+            "Paused {} watcher(s). Their messages will stay as they are until you use `/tt_resumewatchers`.",
+            count
+        ),
+        (count, false) => format!("Resumed {} watcher(s). They will be updated again shortly.", count),
+    };
+
+    whisper(&ctx, "Watchers", &message).await?;
+
+    Ok(())
+}
+
 /// Add a new thread watcher and send the initial watcher message.
-#[poise::command(slash_command, guild_only, rename = "tt_watch", category = "Watchers")]
+#[poise::command(slash_command, guild_only, user_cooldown = 5, rename = "tt_watch", category = "Watchers")]
 pub(crate) async fn add(
     ctx: CommandContext<'_>,
-    #[description = "The category to filter the watched threads by"] category: Option<String>,
+    #[description = "Categories to watch, separated by spaces; prefix a category with ! to leave it out"]
+    category: Option<String>,
+    #[description = "Mention you when more threads are awaiting your reply (default: off)"] notify: Option<bool>,
 ) -> CommandResult<()> {
     let user = ctx.author();
 
@@ -67,11 +122,34 @@ pub(crate) async fn add(
         None => return Err(CommandError::new("Unable to manage watchers outside of a server")),
     };
 
+    let category = category.map(|c| c.split_whitespace().collect::<Vec<_>>().join(" ")).filter(|c| !c.is_empty());
+    if category.as_deref().is_some_and(|c| c.split(' ').any(|name| name == "!")) {
+        return Err(CommandError::new(
+            "Put the name of the category to leave out straight after the `!`, like `!Archive`.",
+        ));
+    }
+
     let data = ctx.data();
 
-    info!("adding watcher for {} ({}), categories {:?}", user.name, user.id, category);
-    let list = threads::get_threads_and_todos(user, guild_id, category.as_deref(), None, data, ctx.serenity_context())
-        .await?;
+    let notify = notify.unwrap_or(false);
+    let _render_guard = data.begin_list_render(user.id)?;
+
+    info!("adding watcher for {} ({}), categories {:?}, notify {}", user.name, user.id, category, notify);
+    let guild_user = GuildUser { user_id: user.id, guild_id };
+    let (threads, todos) = get_watched_items(&data.database, &guild_user, category.as_deref()).await?;
+    let user_data = get_user_data(&data.database, &guild_user, &threads).await?;
+    let list = threads::get_formatted_list(
+        threads,
+        todos,
+        None,
+        &data.database,
+        ctx.serenity_context(),
+        &data.message_cache,
+        &data.active_threads_cache,
+        &data.unavailable_channels,
+        &user_data,
+    )
+    .await?;
 
     if list.chars().count() > crate::consts::MAX_EMBED_CHARS {
         return Err(CommandError::new(
@@ -97,6 +175,7 @@ pub(crate) async fn add(
         channel_id.get(),
         guild_id.get(),
         category.as_deref(),
+        notify,
     )
     .await;
 
@@ -118,16 +197,38 @@ pub(crate) async fn add(
 #[poise::command(slash_command, guild_only, rename = "tt_unwatch", category = "Watchers")]
 pub(crate) async fn remove(
     ctx: CommandContext<'_>,
-    #[description = "The watched message (enter a link or message ID)"] watched_message: Message,
+    #[description = "The watched message (enter a link or message ID)"] watched_message: String,
 ) -> CommandResult<()> {
     let data = ctx.data();
     let (database, message_cache) = (&data.database, &data.message_cache);
 
+    let guild_id = match ctx.guild_id() {
+        Some(id) => id,
+        None => return Err(CommandError::new("Unable to manage watchers outside of a server")),
+    };
+
+    // A plain message ID can only refer to a message in the current channel.
+    let (channel_id, message_id) = match parse_discord_link(&watched_message) {
+        Some(DiscordLink { guild_id: Some(link_guild_id), .. }) if link_guild_id != guild_id => {
+            return Err(CommandError::new("That message is in another server. Remove the watcher from that server instead."))
+        },
+        Some(DiscordLink { channel_id, message_id: Some(message_id), .. }) => (channel_id, message_id),
+        Some(DiscordLink { channel_id, message_id: None, .. }) => {
+            return Err(CommandError::new(format!("{} is a channel, not a message. Link the watched message instead.", channel_id.mention())))
+        },
+        None => match parse_snowflake(&watched_message) {
+            Some(id) => (ctx.channel_id(), MessageId::new(id)),
+            None => {
+                return Err(CommandError::new(format!("`{}` is not a message link or message ID", watched_message)))
+            },
+        },
+    };
+
     let user = ctx.author();
-    let message_url = watched_message.link();
+    let message_url = format!("https://discord.com/channels/{}/{}/{}", guild_id, channel_id, message_id);
 
     let watcher: ThreadWatcher =
-        match db::get_watcher(database, watched_message.channel_id.get(), watched_message.id.get()).await {
+        match db::get_watcher(database, channel_id.get(), message_id.get()).await {
             Ok(Some(w)) => w,
             Ok(None) => {
                 return Err(CommandError::new(format!(
@@ -139,7 +240,7 @@ pub(crate) async fn remove(
                 return Err(CommandError::detailed(
                     format!(
                         "Error looking up watcher for (channel: {}, message: {})",
-                        watched_message.channel_id, watched_message.id,
+                        channel_id, message_id,
                     ),
                     e,
                 ))
@@ -152,7 +253,7 @@ pub(crate) async fn remove(
 
     info!(
         "removing watcher for {} ({}), (channel: {}, message: {})",
-        user.name, user.id, watched_message.channel_id, watched_message.id
+        user.name, user.id, channel_id, message_id
     );
 
     match db::remove_watcher(database, watcher.id).await? {
@@ -168,6 +269,8 @@ pub(crate) async fn remove(
                 Ok(message) => if let Err(e) = message.delete(ctx).await {
                     return Err(anyhow!("Unable to delete watched message ({}): {}", message_url, e).into());
                 }
+                // The watched message was already deleted, so there's nothing left to clean up.
+                Err(e) if is_not_found(&e) => {},
                 Err(e) => return Err(anyhow!("Unable to locate message {}. Perhaps it was already deleted?", e).into()),
             }
 
@@ -178,46 +281,67 @@ pub(crate) async fn remove(
     Ok(())
 }
 
+/// Record the outcome of a watcher update so `/tt_watchers` can report it. Failures here are only logged, since the
+/// status is purely informational.
+pub(crate) async fn record_status(database: &Database, watcher_id: i32, error: Option<&str>) {
+    if let Err(e) = db::set_watcher_status(database, watcher_id, error).await {
+        error!("Failed to record status for watcher {}: {}", watcher_id, e);
+    }
+}
+
 pub(crate) async fn update_watched_message(
     watcher: ThreadWatcher,
     cache_http: impl CacheHttp,
     database: &Database,
     message_cache: &MessageCache,
+    active_threads_cache: &ActiveThreadsCache,
+    unavailable_channels: &UnavailableChannelCache,
 ) -> anyhow::Result<()> {
     info!("updating watched message for {:?}", &watcher);
     let start_time = Instant::now();
 
-    let mut message =
-        match cache_http.http().get_message(watcher.channel_id.into(), watcher.message_id.into()).await {
-            Ok(m) => m,
-            Err(e) => {
-                let channel_name = get_channel_name(watcher.channel_id(), cache_http)
-                    .await
-                    .unwrap_or_else(|| "<unavailable channel>".to_owned());
-
-                if cfg!(debug_assertions) {
-                    warn!(
-                        "could not find message {} in channel {} for watcher {}: {}.",
-                        watcher.message_id,
-                        channel_name,
-                        watcher.id,
-                        e
-                    );
-                }
-                else {
-                    warn!(
+    let message = match fetch_watched_message(&watcher, &cache_http).await {
+        Ok(m) => m,
+        Err(e) if is_not_found(&e) => {
+            let channel_name = get_channel_name(watcher.channel_id(), &cache_http)
+                .await
+                .unwrap_or_else(|| "<unavailable channel>".to_owned());
+
+            if cfg!(debug_assertions) {
+                warn!(
+                    "could not find message {} in channel {} for watcher {}: {}.",
+                    watcher.message_id,
+                    channel_name,
+                    watcher.id,
+                    e
+                );
+                record_status(database, watcher.id, Some(WATCHER_MESSAGE_MISSING)).await;
+            }
+            else {
+                warn!(
                     "could not find message {} in channel {} for watcher {}: {}. Removing watcher.",
                     watcher.message_id, channel_name, watcher.id, e
                 );
-                    db::remove_watcher(database, watcher.id)
-                        .await
-                        .map_err(|e| error!("Failed to remove watcher: {}", e))
-                        .ok();
-                }
+                db::remove_watcher(database, watcher.id)
+                    .await
+                    .map_err(|e| error!("Failed to remove watcher: {}", e))
+                    .ok();
+            }
 
-                return Ok(());
-            },
-        };
+            return Ok(());
+        },
+        Err(e) => {
+            // Anything other than a definitive not-found is likely a temporary Discord issue; keep the watcher and
+            // try again on the next update.
+            warn!(
+                "unable to fetch message {} for watcher {}, will retry next update: {}",
+                watcher.message_id, watcher.id, e
+            );
+            record_status(database, watcher.id, Some(&e.to_string())).await;
+
+            return Ok(());
+        },
+    };
 
     if let Some(mut channel) = message.channel(&cache_http).await?.guild() {
         // If this is a thread, there will be thread metadata
@@ -229,60 +353,272 @@ pub(crate) async fn update_watched_message(
     }
 
     let user = watcher.user();
+    let (threads, todos) = get_watched_items(database, &user, watcher.categories.as_deref()).await?;
+    let user_data = get_user_data(database, &user, &threads).await?;
 
-    let mut threads: Vec<TrackedThread> = Vec::new();
-    let mut todos: Vec<Todo> = Vec::new();
-
-    match watcher.categories.as_deref() {
-        Some("") | None => {
-            threads.extend(threads::enumerate(database, &user, None).await?);
-            todos.extend(todos::enumerate(database, &user, None).await?);
-        },
-        Some(cats) => {
-            for category in cats.split(' ') {
-                threads.extend(threads::enumerate(database, &user, Some(category)).await?);
-                todos.extend(todos::enumerate(database, &user, Some(category)).await?);
-            }
-        },
-    }
-
-    let user_data = UserData {
-        id: user.user_id,
-        guild_id: user.guild_id,
-        muses: muses::get_list(database, user.user_id, user.guild_id).await?,
-        show_timestamps: show_timestamps(database, user.user_id).await,
-    };
+    let pending_channels = threads::get_pending_channels(
+        &threads,
+        &cache_http,
+        message_cache,
+        unavailable_channels,
+        &user_data,
+    )
+    .await;
 
     let threads_content = threads::get_formatted_list(
         threads,
         todos,
         None,
+        database,
         &cache_http,
         message_cache,
+        active_threads_cache,
+        unavailable_channels,
         &user_data,
     )
     .await?;
 
-    let edit_result = message
-        .edit(
-            &cache_http,
-            EditMessage::new()
-                .add_embed(
-                    CreateEmbed::new()
-                        .colour(Colour::PURPLE)
-                        .title("Watching threads")
-                        .description(threads_content)
-                        .footer(CreateEmbedFooter::new(format!("Last updated: {} UTC", Utc::now())))))
-        .await;
+    // Footers don't render timestamp markdown, so the update time goes at the end of the description instead.
+    let description = MessageBuilder::new()
+        .push_line(threads_content)
+        .push("-# Last updated ")
+        .push_timestamp(Timestamp::now())
+        .build();
+
+    let edit = EditMessage::new()
+        .add_embed(
+            CreateEmbed::new()
+                .colour(Colour::PURPLE)
+                .title("Watching threads")
+                .description(description));
+    let edit_result =
+        retry_transient(|| message.channel_id.edit_message(&cache_http, message.id, edit.clone())).await;
     if let Err(e) = edit_result {
         // If we return here, an error updating one watcher message would prevent the rest from being updated.
         // Simply log these instead.
         error!("Could not edit message: {}", e);
+        record_status(database, watcher.id, Some(&e.to_string())).await;
     }
     else {
         let elapsed = Instant::now() - start_time;
-        info!("updated watcher {} in {:.2} ms", watcher.id, elapsed.as_millis());
+        info!("updated watcher {} in {:.2} ms", watcher.id, elapsed.as_secs_f64() * 1000.0);
+        record_status(database, watcher.id, None).await;
+
+        update_watcher_state(&watcher, pending_channels, &cache_http, database).await;
     }
 
     Ok(())
 }
+
+/// The categories a watcher shows, parsed from its space-separated `categories` field. Categories prefixed with `!`
+/// are left out, and leaving one out takes precedence over including it. If no categories are included, every
+/// category is shown apart from those left out.
+struct WatcherCategories<'a> {
+    include: Vec<&'a str>,
+    exclude: Vec<&'a str>,
+}
+
+impl<'a> WatcherCategories<'a> {
+    fn parse(categories: Option<&'a str>) -> Self {
+        let mut include = Vec::new();
+        let mut exclude = Vec::new();
+        for name in categories.unwrap_or_default().split_whitespace() {
+            match name.strip_prefix('!') {
+                Some("") => {},
+                Some(excluded) => exclude.push(excluded),
+                None => include.push(name),
+            }
+        }
+
+        Self { include, exclude }
+    }
+
+    /// Returns true if entries in the given category should be left out of the watcher.
+    fn is_excluded(&self, category: Option<&str>) -> bool {
+        self.exclude.iter().any(|filter| matches_filter(filter, category))
+    }
+
+    /// Returns true if entries in the given category are shown by the watcher.
+    fn shows(&self, category: Option<&str>) -> bool {
+        let included = self.include.is_empty() || self.include.iter().any(|filter| matches_filter(filter, category));
+        included && !self.is_excluded(category)
+    }
+}
+
+/// Get the threads and to do list entries shown by a watcher with the given categories.
+async fn get_watched_items(
+    database: &Database,
+    user: &GuildUser,
+    categories: Option<&str>,
+) -> anyhow::Result<(Vec<TrackedThread>, Vec<Todo>)> {
+    let categories = WatcherCategories::parse(categories);
+
+    let mut threads: Vec<TrackedThread> = Vec::new();
+    let mut todos: Vec<Todo> = Vec::new();
+
+    if categories.include.is_empty() {
+        threads.extend(threads::enumerate(database, user, None).await?);
+        todos.extend(todos::enumerate(database, user, None).await?);
+    }
+    else {
+        for &category in &categories.include {
+            threads.extend(threads::enumerate(database, user, Some(category)).await?);
+            todos.extend(todos::enumerate(database, user, Some(category)).await?);
+        }
+    }
+
+    threads.retain(|t| categories.shows(t.category.as_deref()));
+    todos.retain(|t| categories.shows(t.category.as_deref()));
+
+    Ok((threads, todos))
+}
+
+/// Get the user's list settings for rendering a watcher. Rendering a watcher doesn't count as the user seeing its
+/// threads, so they stay marked as new until the user lists them.
+async fn get_user_data(
+    database: &Database,
+    user: &GuildUser,
+    threads: &[TrackedThread],
+) -> anyhow::Result<UserData> {
+    let channel_ids: Vec<i64> = threads.iter().map(|t| t.channel_id as i64).collect();
+
+    Ok(UserData {
+        id: user.user_id,
+        guild_id: user.guild_id,
+        muses: muses::get_list(database, user.user_id, user.guild_id).await?,
+        show_timestamps: show_timestamps(database, user.user_id).await,
+        bold_pending_only: bold_pending_only(database, user.user_id).await,
+        thread_name_length: thread_name_length(database, user.user_id).await,
+        last_seen: threads::get_last_seen(database, user.user_id, &channel_ids).await,
+    })
+}
+
+/// Fetch a watcher's message, retrying a few times if Discord reports it as missing before giving up.
+async fn fetch_watched_message(
+    watcher: &ThreadWatcher,
+    cache_http: impl CacheHttp,
+) -> Result<Message, SerenityError> {
+    let mut attempt = 1;
+    loop {
+        match cache_http.http().get_message(watcher.channel_id(), watcher.message_id()).await {
+            Err(e) if is_not_found(&e) && attempt < WATCHER_FETCH_ATTEMPTS => {
+                debug!("message for watcher {} not found on attempt {}, retrying", watcher.id, attempt);
+                attempt += 1;
+                sleep(WATCHER_FETCH_RETRY_DELAY).await;
+            },
+            result => return result,
+        }
+    }
+}
+
+/// Record which threads a watcher is showing as awaiting a reply, and mention the user if the pending threads
+/// changed and there are more of them than before. Only the set of pending threads is compared, so refreshes that
+/// only change timestamps don't trigger a notification.
+async fn update_watcher_state(
+    watcher: &ThreadWatcher,
+    mut pending_channels: Vec<ChannelId>,
+    cache_http: impl CacheHttp,
+    database: &Database,
+) {
+    pending_channels.sort();
+
+    let content_hash = hash_pending_channels(&pending_channels);
+    let pending_count = pending_channels.len() as i32;
+
+    if watcher.content_hash == Some(content_hash) {
+        return;
+    }
+
+    // Watchers without a previous state have only just been created, so there's nothing to compare against yet.
+    let increased = watcher.pending_count.is_some_and(|previous| pending_count > previous);
+    if watcher.notify && watcher.content_hash.is_some() && increased {
+        let content = MessageBuilder::new()
+            .mention(&watcher.user_id())
+            .push(format!(", you now have {} threads awaiting your reply.", pending_count))
+            .build();
+
+        if let Err(e) = watcher.channel_id().send_message(&cache_http, CreateMessage::new().content(content)).await {
+            error!("Could not send watcher notification for watcher {}: {}", watcher.id, e);
+        }
+    }
+
+    if let Err(e) = db::update_watcher_state(database, watcher.id, content_hash, pending_count).await {
+        error!("Failed to record state for watcher {}: {}", watcher.id, e);
+    }
+}
+
+/// Hash a sorted list of pending channels, to store and compare against on the next update. This uses 64-bit FNV-1a
+/// rather than the standard library's hasher, whose output may change between Rust releases and so can't be stored.
+fn hash_pending_channels(pending_channels: &[ChannelId]) -> i64 {
+    const FNV_OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+    const FNV_PRIME: u64 = 0x0100_0000_01b3;
+
+    let hash = pending_channels
+        .iter()
+        .flat_map(|channel| channel.get().to_le_bytes())
+        .fold(FNV_OFFSET_BASIS, |hash, byte| (hash ^ byte as u64).wrapping_mul(FNV_PRIME));
+
+    hash as i64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pending_channel_hashes_are_stable() {
+        // These values are stored in the database, so they must never change
+        assert_eq!(hash_pending_channels(&[]), 0xcbf2_9ce4_8422_2325_u64 as i64);
+        assert_eq!(hash_pending_channels(&[ChannelId::new(1), ChannelId::new(2)]), 0x7717_9803_63c8_e066);
+        assert_ne!(
+            hash_pending_channels(&[ChannelId::new(1), ChannelId::new(2)]),
+            hash_pending_channels(&[ChannelId::new(1), ChannelId::new(3)])
+        );
+    }
+
+    #[test]
+    fn exclusion_wins_over_inclusion() {
+        let categories = WatcherCategories::parse(Some("main !main side"));
+
+        assert!(categories.is_excluded(Some("Main")));
+        assert!(!categories.shows(Some("main")));
+        assert!(categories.shows(Some("side")));
+        assert!(!categories.shows(Some("other")));
+    }
+
+    #[test]
+    fn exclusions_alone_show_everything_else() {
+        let categories = WatcherCategories::parse(Some("!archive"));
+
+        assert!(categories.include.is_empty());
+        assert!(!categories.shows(Some("archive")));
+        assert!(categories.shows(Some("main")));
+        assert!(categories.shows(None));
+    }
+
+    #[test]
+    fn excluding_none_hides_uncategorised_entries() {
+        let categories = WatcherCategories::parse(Some("!none"));
+
+        assert!(categories.is_excluded(None));
+        assert!(!categories.shows(None));
+        assert!(categories.shows(Some("main")));
+    }
+
+    #[test]
+    fn bare_exclamation_mark_is_ignored() {
+        let categories = WatcherCategories::parse(Some("! main"));
+
+        assert!(categories.exclude.is_empty());
+        assert_eq!(categories.include, vec!["main"]);
+        assert!(categories.shows(Some("main")));
+    }
+
+    #[test]
+    fn no_categories_shows_everything() {
+        let categories = WatcherCategories::parse(None);
+
+        assert!(categories.shows(Some("main")));
+        assert!(categories.shows(None));
+    }
+}
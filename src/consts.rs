@@ -6,9 +6,30 @@ pub(crate) mod setting_names;
 
 pub(crate) const DELETE_EMOJI: [&str; 2] = ["🚫", "🗑️"];
 
-//pub(crate) const DEBUG_USER: u64 = 283711673934807042;
+pub(crate) const MAX_DELETE_EMOJI: usize = 5;
+
+/// The guild ID used to store muses which apply in every server.
+pub(crate) const GLOBAL_MUSE_GUILD_ID: u64 = 0;
+
+/// Category filters which select only entries without a category.
+pub(crate) const UNCATEGORISED_KEYWORDS: [&str; 3] = ["none", "uncategorised", "uncategorized"];
+
+/// The prefix for text commands, used unless a server has chosen its own.
+pub(crate) const DEFAULT_PREFIX: &str = "tt!";
+
+pub(crate) const MAX_PREFIX_LENGTH: usize = 5;
+
+/// The user that bug reports are sent to, unless `BUG_REPORT_USER_ID` is set in Secrets.toml.
+pub(crate) const DEBUG_USER: u64 = 283711673934807042;
 
 pub(crate) const THREAD_NAME_LENGTH: usize = 32;
+pub(crate) const MIN_THREAD_NAME_LENGTH: usize = 8;
+pub(crate) const MAX_THREAD_NAME_LENGTH: usize = 100;
+pub(crate) const THREAD_NOTE_LENGTH: usize = 200;
+pub(crate) const THREAD_LABEL_LENGTH: usize = 100;
+/// To do list entries longer than this are shortened in lists, and the user is warned when adding one.
+pub(crate) const TODO_DISPLAY_LENGTH: usize = 200;
+pub(crate) const MAX_TODO_LENGTH: usize = 1000;
 
 #[cfg(not(debug_assertions))]
 pub(crate) const SHARD_CHECKUP_INTERVAL: Duration = Duration::from_secs(300);
@@ -25,14 +46,92 @@ pub(crate) const WATCHER_UPDATE_INTERVAL: Duration = Duration::from_secs(60);
 
 pub(crate) const SCHEDULED_MESSAGE_INTERVAL: Duration = Duration::from_secs(60);
 
+pub(crate) const SCHEDULED_MESSAGE_STAGGER: Duration = Duration::from_millis(250);
+
+pub(crate) const DEFAULT_UPCOMING_OCCURRENCES: usize = 5;
+
+pub(crate) const MAX_UPCOMING_OCCURRENCES: usize = 25;
+
+pub(crate) const QUEUED_NOTIFICATION_INTERVAL: Duration = Duration::from_secs(300);
+
+pub(crate) const DELETED_THREAD_PURGE_INTERVAL: Duration = Duration::from_secs(300);
+
+pub(crate) const WEEKLY_DIGEST_INTERVAL: Duration = Duration::from_secs(900);
+
+/// The local time weekly digests are sent at, if only a day is given.
+pub(crate) const DEFAULT_WEEKLY_DIGEST_TIME: &str = "18:00";
+
+pub(crate) const THREAD_UNDO_WINDOW: Duration = Duration::from_secs(600);
+
+/// The default cache trim interval, unless `CACHE_TRIM_INTERVAL_SECS` is set in Secrets.toml.
 pub(crate) const CACHE_TRIM_INTERVAL: Duration = Duration::from_secs(2995);
 
+/// The default cache entry lifetime, unless `CACHE_LIFETIME_SECS` is set in Secrets.toml.
 pub(crate) const CACHE_LIFETIME: Duration = Duration::from_secs(6000);
 
+pub(crate) const MESSAGE_CACHE_MAX_ENTRIES: usize = 10_000;
+
+pub(crate) const ACTIVE_THREADS_CACHE_LIFETIME: Duration = Duration::from_secs(15);
+
+/// The maximum number of connections in the database pool.
+pub(crate) const MAX_DATABASE_CONNECTIONS: u32 = 20;
+
+/// How long a channel that couldn't be found or accessed is skipped before it's looked up again.
+pub(crate) const UNAVAILABLE_CHANNEL_CACHE_LIFETIME: Duration = Duration::from_secs(600);
+
+/// The default number of watched messages updated at once, unless `MAX_WATCHER_UPDATE_TASKS` is set in Secrets.toml.
 pub(crate) const MAX_WATCHER_UPDATE_TASKS: usize = 5;
 
+/// The fewest watchers each watcher update task handles, so that small lists aren't split up more than needed.
 pub(crate) const MIN_WATCHER_BATCH_SIZE: usize = 10;
 
+pub(crate) const WATCHER_FETCH_ATTEMPTS: u32 = 3;
+
+pub(crate) const WATCHER_FETCH_RETRY_DELAY: Duration = Duration::from_secs(5);
+
+/// The error recorded for a watcher whose message could no longer be found.
+pub(crate) const WATCHER_MESSAGE_MISSING: &str = "message missing";
+
+/// How many times to attempt a Discord request that fails with a transient error.
+pub(crate) const TRANSIENT_RETRY_ATTEMPTS: u32 = 4;
+
+/// The delay before the first retry of a Discord request, doubled after each further attempt.
+pub(crate) const TRANSIENT_RETRY_DELAY: Duration = Duration::from_millis(500);
+
+pub(crate) const MAX_CONCURRENT_THREAD_LOOKUPS: usize = 8;
+
+/// How many of a thread's most recent messages are checked for the last reply, if the latest message isn't a reply.
+/// Can be overridden with `LAST_REPLY_LOOKBACK` in Secrets.toml.
+pub(crate) const LAST_REPLY_LOOKBACK: u8 = 5;
+
+/// The most messages Discord returns in one request, and so the largest allowed last reply lookback.
+pub(crate) const MAX_LAST_REPLY_LOOKBACK: u8 = 100;
+
+pub(crate) const MAX_TRACKED_THREADS_PER_USER: i64 = 500;
+
+/// The most archived threads Discord returns in a single request.
+pub(crate) const MAX_ARCHIVED_THREADS_FETCHED: u64 = 100;
+
+pub(crate) const MAX_TRACKERS_LISTED: usize = 25;
+
+pub(crate) const MAX_SEARCH_RESULTS: usize = 25;
+
+pub(crate) const MAX_CLEANUP_THREADS_LISTED: usize = 25;
+
+/// The prefix of the custom ID for the button on reply notifications which tracks the thread.
+pub(crate) const TRACK_THREAD_BUTTON_ID: &str = "tt_track_thread";
+
+pub(crate) const CONFIRMATION_TIMEOUT: Duration = Duration::from_secs(120);
+
 pub(crate) const MPSC_BUFFER_SIZE: usize = 32;
 
+/// How long to wait for queued background tasks to finish when shutting down.
+pub(crate) const SHUTDOWN_TIMEOUT: Duration = Duration::from_secs(30);
+
 pub(crate) const MAX_EMBED_CHARS: usize = 2048;
+
+pub(crate) const MAX_EMBED_TITLE_CHARS: usize = 256;
+
+pub(crate) const MAX_MESSAGE_CHARS: usize = 2000;
+
+pub(crate) const MIN_PREVIEW_LENGTH: usize = 10;
@@ -10,6 +10,9 @@ pub(crate) const MUSES: &str = include_str!("../../help/muses.md");
 pub(crate) const SCHEDULING_TITLE: &str = "Manage scheduled messages and associated settings";
 pub(crate) const SCHEDULING: &str = include_str!("../../help/scheduling.md");
 
+pub(crate) const SERVER_TITLE: &str = "Manage server-wide settings";
+pub(crate) const SERVER: &str = include_str!("../../help/server.md");
+
 pub(crate) const THREADS_TITLE: &str = "View or change tracked threads";
 pub(crate) const THREADS: &str = include_str!("../../help/threads.md");
 
@@ -1,3 +1,31 @@
 pub(crate) const USER_TIMEZONE: &str = "USER_TIMEZONE";
 
+pub(crate) const USER_DATE_FORMAT: &str = "USER_DATE_FORMAT";
+
 pub(crate) const USER_SHOW_TIMESTAMPS: &str = "USER_SHOW_TIMESTAMPS";
+
+pub(crate) const USER_BOLD_PENDING_ONLY: &str = "USER_BOLD_PENDING_ONLY";
+
+pub(crate) const USER_QUIET_HOURS_START: &str = "USER_QUIET_HOURS_START";
+
+pub(crate) const USER_QUIET_HOURS_END: &str = "USER_QUIET_HOURS_END";
+
+pub(crate) const USER_PREVIEW_LENGTH: &str = "USER_PREVIEW_LENGTH";
+
+pub(crate) const USER_THREAD_NAME_LENGTH: &str = "USER_THREAD_NAME_LENGTH";
+
+pub(crate) const USER_DEFAULT_SORT: &str = "USER_DEFAULT_SORT";
+
+pub(crate) const USER_WEEKLY_DIGEST: &str = "USER_WEEKLY_DIGEST";
+
+pub(crate) const USER_WEEKLY_DIGEST_LAST_SENT: &str = "USER_WEEKLY_DIGEST_LAST_SENT";
+
+pub(crate) const GUILD_NOTIFICATION_CHANNEL: &str = "GUILD_NOTIFICATION_CHANNEL";
+
+pub(crate) const GUILD_NOTIFICATION_ROLE: &str = "GUILD_NOTIFICATION_ROLE";
+
+pub(crate) const GUILD_PREFIX: &str = "GUILD_PREFIX";
+
+pub(crate) const GUILD_DELETE_EMOJI: &str = "GUILD_DELETE_EMOJI";
+
+pub(crate) const GUILD_DELETE_BY_REACTION: &str = "GUILD_DELETE_BY_REACTION";
@@ -1,12 +1,36 @@
+mod migrations;
 mod models;
 
+use std::time::Duration;
+
 use chrono::{DateTime, Utc};
+pub(crate) use migrations::run_migrations;
 pub(crate) use models::*;
 use poise::serenity_prelude::UserId;
 
+use crate::{
+    category::{is_uncategorised, Category},
+    consts::{GLOBAL_MUSE_GUILD_ID, UNCATEGORISED_KEYWORDS},
+};
+
 pub(crate) use sqlx::PgPool as Database;
 pub(crate) type Result<T> = std::result::Result<T, sqlx::Error>;
 
+/// Describe how many of the database pool's connections are open and how many of those are idle.
+pub(crate) fn pool_summary(database: &Database) -> String {
+    format!(
+        "{} of {} connections open, {} idle",
+        database.size(),
+        database.options().get_max_connections(),
+        database.num_idle()
+    )
+}
+
+/// Returns true if every connection the database pool may open is in use.
+pub(crate) fn pool_exhausted(database: &Database) -> bool {
+    database.num_idle() == 0 && database.size() >= database.options().get_max_connections()
+}
+
 /// Delete a scheduled message completely.
 pub(crate) async fn delete_scheduled_message(database: &Database, id: i32) -> Result<bool> {
     match get_scheduled_message(database, id).await? {
@@ -45,26 +69,7 @@ pub(crate) async fn update_scheduled_message(
     let channel_id = channel_id.map(|cid| cid.into());
     match get_scheduled_message(database, id).await? {
         Some(mut record) => {
-            if let Some(datetime) = datetime {
-                record.datetime = datetime.to_rfc3339();
-                record.archived = false;
-            }
-
-            if let Some(repeat) = repeat {
-                record.repeat = repeat;
-            }
-
-            if let Some(title) = title {
-                record.title = title;
-            }
-
-            if let Some(message) = message {
-                record.message = message;
-            }
-
-            if let Some(channel_id) = channel_id {
-                record.channel_id = channel_id;
-            }
+            record.apply_update(datetime, repeat, title, message, channel_id);
 
             let result = sqlx::query(
                 "UPDATE scheduled_messages SET channel_id = $2, datetime = $3, repeat = $4, title = $5, message = $6, archived = $7 WHERE id = $1")
@@ -123,7 +128,7 @@ pub(crate) async fn list_scheduled_messages_for_user(
     user_id: impl Into<u64>,
 ) -> Result<Vec<ScheduledMessageSummary>> {
     sqlx::query_as(
-        "SELECT id, channel_id, datetime, repeat, title FROM scheduled_messages WHERE user_id = $1",
+        "SELECT id, channel_id, datetime, repeat, title, archived FROM scheduled_messages WHERE user_id = $1",
     )
     .bind(user_id.into() as i64)
     .fetch_all(database)
@@ -192,6 +197,164 @@ where
     .await
 }
 
+/// List every user's value for the given setting from the user_settings table
+pub(crate) async fn list_user_settings(database: &Database, name: &str) -> Result<Vec<UserSetting>> {
+    sqlx::query_as("SELECT user_id, name, value FROM user_settings WHERE name = $1")
+        .bind(name)
+        .fetch_all(database)
+        .await
+}
+
+/// Remove a stored user setting from the user_settings table
+pub(crate) async fn remove_user_setting(
+    database: &Database,
+    user_id: impl Into<u64>,
+    name: &str,
+) -> Result<bool> {
+    let result = sqlx::query("DELETE FROM user_settings WHERE user_id = $1 AND name = $2")
+        .bind(user_id.into() as i64)
+        .bind(name)
+        .execute(database)
+        .await?;
+
+    Ok(result.rows_affected() > 0)
+}
+
+/// Add or update a server setting in the guild_settings table
+pub(crate) async fn update_guild_setting<Id>(
+    database: &Database,
+    guild_id: Id,
+    name: &str,
+    value: &str,
+) -> Result<bool>
+where
+    Id: Into<u64> + Copy,
+{
+    let query_string = match get_guild_setting(database, guild_id, name).await? {
+        Some(entry) => {
+            if entry.value == value {
+                return Ok(false);
+            }
+
+            "UPDATE guild_settings SET value = $3 WHERE guild_id = $1 AND name = $2"
+        },
+        None => {
+            "INSERT INTO guild_settings (guild_id, name, value) VALUES ($1, $2, $3)"
+        },
+    };
+
+    let result = sqlx::query(query_string)
+        .bind(guild_id.into() as i64)
+        .bind(name)
+        .bind(value)
+        .execute(database)
+        .await?;
+
+    Ok(result.rows_affected() > 0)
+}
+
+/// Retrieve a stored server setting from the guild_settings table
+pub(crate) async fn get_guild_setting<Id>(
+    database: &Database,
+    guild_id: Id,
+    name: &str,
+) -> Result<Option<GuildSetting>>
+where
+    Id: Into<u64> + Copy,
+{
+    sqlx::query_as(
+        "SELECT guild_id, name, value FROM guild_settings WHERE guild_id = $1 AND name = $2",
+    )
+    .bind(guild_id.into() as i64)
+    .bind(name)
+    .fetch_optional(database)
+    .await
+}
+
+/// Remove a stored server setting from the guild_settings table
+pub(crate) async fn remove_guild_setting(
+    database: &Database,
+    guild_id: impl Into<u64>,
+    name: &str,
+) -> Result<bool> {
+    let result = sqlx::query("DELETE FROM guild_settings WHERE guild_id = $1 AND name = $2")
+        .bind(guild_id.into() as i64)
+        .bind(name)
+        .execute(database)
+        .await?;
+
+    Ok(result.rows_affected() > 0)
+}
+
+/// Add or update a user's nickname for a server
+pub(crate) async fn set_server_nickname(
+    database: &Database,
+    user_id: u64,
+    guild_id: u64,
+    nickname: &str,
+) -> Result<bool> {
+    let query_string = match get_server_nickname(database, user_id, guild_id).await? {
+        Some(entry) => {
+            if entry.nickname == nickname {
+                return Ok(false);
+            }
+
+            "UPDATE server_nicknames SET nickname = $3 WHERE user_id = $1 AND guild_id = $2"
+        },
+        None => "INSERT INTO server_nicknames (user_id, guild_id, nickname) VALUES ($1, $2, $3)",
+    };
+
+    let result = sqlx::query(query_string)
+        .bind(user_id as i64)
+        .bind(guild_id as i64)
+        .bind(nickname)
+        .execute(database)
+        .await?;
+
+    Ok(result.rows_affected() > 0)
+}
+
+/// Get a user's nickname for a server, if they have set one
+pub(crate) async fn get_server_nickname(
+    database: &Database,
+    user_id: u64,
+    guild_id: u64,
+) -> Result<Option<ServerNickname>> {
+    sqlx::query_as("SELECT guild_id, nickname FROM server_nicknames WHERE user_id = $1 AND guild_id = $2")
+        .bind(user_id as i64)
+        .bind(guild_id as i64)
+        .fetch_optional(database)
+        .await
+}
+
+/// Find the server a user has given the specified nickname, if any
+pub(crate) async fn get_server_id_from_nickname(
+    database: &Database,
+    user_id: u64,
+    nickname: &str,
+) -> Result<Option<ServerNickname>> {
+    sqlx::query_as("SELECT guild_id, nickname FROM server_nicknames WHERE user_id = $1 AND lower(nickname) = lower($2)")
+        .bind(user_id as i64)
+        .bind(nickname)
+        .fetch_optional(database)
+        .await
+}
+
+/// Remove a user's nickname for a server
+pub(crate) async fn remove_server_nickname(
+    database: &Database,
+    user_id: u64,
+    guild_id: u64,
+) -> Result<bool> {
+    let result = sqlx::query("DELETE FROM server_nicknames WHERE user_id = $1 AND guild_id = $2")
+        .bind(user_id as i64)
+        .bind(guild_id as i64)
+        .execute(database)
+        .await?;
+
+    Ok(result.rows_affected() > 0)
+}
+
 /// Store an entry in the Subscriptions table
 pub(crate) async fn add_subscriber<Id>(database: &Database, user_id: Id) -> Result<bool>
 where
@@ -242,9 +405,120 @@ pub(crate) async fn remove_subscriber(
     Ok(result.rows_affected() > 0)
 }
 
+/// Add a category to the set of categories a user receives reply notifications for.
+pub(crate) async fn add_subscription_category(
+    database: &Database,
+    user_id: impl Into<u64>,
+    category: &str,
+) -> Result<bool> {
+    let result = sqlx::query(
+        "INSERT INTO subscription_categories (user_id, category) VALUES ($1, $2)
+        ON CONFLICT (user_id, lower(category)) DO NOTHING",
+    )
+    .bind(user_id.into() as i64)
+    .bind(category)
+    .execute(database)
+    .await?;
+
+    Ok(result.rows_affected() > 0)
+}
+
+/// List the categories a user receives reply notifications for. An empty list means notifications are sent for
+/// every category.
+pub(crate) async fn list_subscription_categories(
+    database: &Database,
+    user_id: impl Into<u64>,
+) -> Result<Vec<String>> {
+    sqlx::query_scalar("SELECT category FROM subscription_categories WHERE user_id = $1 ORDER BY lower(category)")
+        .bind(user_id.into() as i64)
+        .fetch_all(database)
+        .await
+}
+
+/// Remove a category from the set of categories a user receives reply notifications for.
+pub(crate) async fn remove_subscription_category(
+    database: &Database,
+    user_id: impl Into<u64>,
+    category: &str,
+) -> Result<bool> {
+    let result = sqlx::query("DELETE FROM subscription_categories WHERE user_id = $1 AND lower(category) = lower($2)")
+        .bind(user_id.into() as i64)
+        .bind(category)
+        .execute(database)
+        .await?;
+
+    Ok(result.rows_affected() > 0)
+}
+
+/// Returns true if the user should be notified of replies to the given thread: either they have no category
+/// subscriptions at all, or the category they track the thread under is one of them.
+pub(crate) async fn is_subscribed_to_thread_category(
+    database: &Database,
+    user_id: impl Into<u64>,
+    guild_id: impl Into<u64>,
+    channel_id: impl Into<u64>,
+) -> Result<bool> {
+    sqlx::query_scalar(
+        "SELECT NOT EXISTS (SELECT 1 FROM subscription_categories WHERE user_id = $1)
+        OR EXISTS (
+            SELECT 1 FROM threads t JOIN subscription_categories s ON s.user_id = t.user_id
+            WHERE t.user_id = $1 AND t.guild_id = $2 AND t.channel_id = $3 AND t.deleted_at IS NULL
+            AND (lower(t.category) = lower(s.category) OR (t.category IS NULL AND lower(s.category) = ANY($4)))
+        )",
+    )
+    .bind(user_id.into() as i64)
+    .bind(guild_id.into() as i64)
+    .bind(channel_id.into() as i64)
+    .bind(&UNCATEGORISED_KEYWORDS[..])
+    .fetch_one(database)
+    .await
+}
+
+/// Store a reply notification to be sent at a later time.
+pub(crate) async fn add_queued_notification(
+    database: &Database,
+    user_id: impl Into<u64>,
+    guild_id: impl Into<u64>,
+    channel_id: impl Into<u64>,
+    content: &str,
+    preview_title: Option<&str>,
+    preview: Option<&str>,
+) -> Result<bool> {
+    let result = sqlx::query("INSERT INTO queued_notifications (user_id, guild_id, channel_id, content, preview_title, preview) VALUES ($1, $2, $3, $4, $5, $6)")
+        .bind(user_id.into() as i64)
+        .bind(guild_id.into() as i64)
+        .bind(channel_id.into() as i64)
+        .bind(content)
+        .bind(preview_title)
+        .bind(preview)
+        .execute(database)
+        .await?;
+
+    Ok(result.rows_affected() > 0)
+}
+
+/// Get all queued reply notifications, oldest first.
+pub(crate) async fn list_queued_notifications(
+    database: &Database,
+) -> Result<Vec<QueuedNotification>> {
+    sqlx::query_as("SELECT id, user_id, guild_id, channel_id, content, preview_title, preview FROM queued_notifications ORDER BY id")
+        .fetch_all(database)
+        .await
+}
+
+/// Remove a queued reply notification, typically once it has been sent.
+pub(crate) async fn remove_queued_notification(database: &Database, id: i32) -> Result<bool> {
+    let result = sqlx::query("DELETE FROM queued_notifications WHERE id = $1")
+        .bind(id)
+        .execute(database)
+        .await?;
+
+    Ok(result.rows_affected() > 0)
+}
+
 /// Get all entries from the watchers table.
 pub(crate) async fn list_watchers(database: &Database) -> Result<Vec<ThreadWatcher>> {
-    sqlx::query_as("SELECT id, user_id, message_id, channel_id, guild_id, categories FROM watchers")
+    sqlx::query_as("SELECT id, user_id, message_id, channel_id, guild_id, categories, notify, content_hash, pending_count, paused, EXTRACT(EPOCH FROM last_updated)::BIGINT AS last_updated, last_error FROM watchers")
         .fetch_all(database)
         .await
 }
@@ -255,7 +529,7 @@ pub(crate) async fn list_current_watchers(
     user_id: u64,
     guild_id: u64,
 ) -> Result<Vec<ThreadWatcher>> {
-    sqlx::query_as("SELECT id, user_id, message_id, channel_id, guild_id, categories FROM watchers WHERE user_id = $1 AND guild_id = $2")
+    sqlx::query_as("SELECT id, user_id, message_id, channel_id, guild_id, categories, notify, content_hash, pending_count, paused, EXTRACT(EPOCH FROM last_updated)::BIGINT AS last_updated, last_error FROM watchers WHERE user_id = $1 AND guild_id = $2")
         .bind(user_id as i64)
         .bind(guild_id as i64)
         .fetch_all(database)
@@ -268,7 +542,7 @@ pub(crate) async fn get_watcher(
     channel_id: u64,
     message_id: u64,
 ) -> Result<Option<ThreadWatcher>> {
-    sqlx::query_as("SELECT id, user_id, message_id, channel_id, guild_id, categories FROM watchers WHERE channel_id = $1 AND message_id = $2")
+    sqlx::query_as("SELECT id, user_id, message_id, channel_id, guild_id, categories, notify, content_hash, pending_count, paused, EXTRACT(EPOCH FROM last_updated)::BIGINT AS last_updated, last_error FROM watchers WHERE channel_id = $1 AND message_id = $2")
         .bind(channel_id as i64)
         .bind(message_id as i64)
         .fetch_optional(database).await
@@ -282,18 +556,64 @@ pub(crate) async fn add_watcher(
     channel_id: u64,
     guild_id: u64,
     categories: Option<&str>,
+    notify: bool,
 ) -> Result<bool> {
-    let result = sqlx::query("INSERT INTO watchers (user_id, message_id, channel_id, guild_id, categories) VALUES ($1, $2, $3, $4, $5)")
+    let result = sqlx::query("INSERT INTO watchers (user_id, message_id, channel_id, guild_id, categories, notify) VALUES ($1, $2, $3, $4, $5, $6)")
         .bind(user_id as i64)
         .bind(message_id as i64)
         .bind(channel_id as i64)
         .bind(guild_id as i64)
         .bind(categories)
+        .bind(notify)
         .execute(database).await?;
 
     Ok(result.rows_affected() > 0)
 }
 
+/// Record the state of the pending threads shown by a watcher when it was last updated.
+pub(crate) async fn update_watcher_state(
+    database: &Database,
+    watcher_id: i32,
+    content_hash: i64,
+    pending_count: i32,
+) -> Result<bool> {
+    let result = sqlx::query("UPDATE watchers SET content_hash = $1, pending_count = $2 WHERE id = $3")
+        .bind(content_hash)
+        .bind(pending_count)
+        .bind(watcher_id)
+        .execute(database)
+        .await?;
+
+    Ok(result.rows_affected() > 0)
+}
+
+/// Record the outcome of the latest update to a watcher's message. A successful update clears any previous error.
+pub(crate) async fn set_watcher_status(database: &Database, watcher_id: i32, error: Option<&str>) -> Result<bool> {
+    let query = match error {
+        Some(_) => "UPDATE watchers SET last_error = $1 WHERE id = $2",
+        None => "UPDATE watchers SET last_updated = NOW(), last_error = $1 WHERE id = $2",
+    };
+
+    let result = sqlx::query(query)
+        .bind(error)
+        .bind(watcher_id)
+        .execute(database)
+        .await?;
+
+    Ok(result.rows_affected() > 0)
+}
+
+/// Pause or resume updates for all of a user's watchers. Returns the number of watchers changed.
+pub(crate) async fn set_watchers_paused(database: &Database, user_id: u64, paused: bool) -> Result<u64> {
+    let result = sqlx::query("UPDATE watchers SET paused = $1 WHERE user_id = $2 AND paused <> $1")
+        .bind(paused)
+        .bind(user_id as i64)
+        .execute(database)
+        .await?;
+
+    Ok(result.rows_affected())
+}
+
 /// Remove an entry from the watchers table.
 pub(crate) async fn remove_watcher(database: &Database, watcher_id: i32) -> Result<u64> {
     let result = sqlx::query("DELETE FROM watchers WHERE id = $1")
@@ -304,59 +624,367 @@ pub(crate) async fn remove_watcher(database: &Database, watcher_id: i32) -> Resu
     Ok(result.rows_affected())
 }
 
-/// Add a new entry to the threads table.
-pub(crate) async fn add_thread(
-    database: &Database,
-    guild_id: u64,
-    channel_id: u64,
-    user_id: u64,
-    category: Option<&str>,
-) -> Result<bool> {
-    match get_thread(database, guild_id, user_id, channel_id).await? {
-        Some(_) => Ok(false),
-        None => {
-            sqlx::query("INSERT INTO threads (channel_id, user_id, guild_id, category) VALUES ($1, $2, $3, $4)")
-                .bind(channel_id as i64)
-                .bind(user_id as i64)
-                .bind(guild_id as i64)
-                .bind(category)
-                .execute(database).await?;
-
-            Ok(true)
-        },
-    }
+/// Count the entries in the threads table for a given user across all guilds.
+pub(crate) async fn count_threads(database: &Database, user_id: u64) -> Result<i64> {
+    sqlx::query_scalar("SELECT COUNT(*) FROM threads WHERE user_id = $1 AND deleted_at IS NULL")
+        .bind(user_id as i64)
+        .fetch_one(database)
+        .await
 }
 
-/// Update the category of an entry in the threads table.
-pub(crate) async fn update_thread_category(
+/// Get the name a user already files a category under, so that a category typed with different casing joins the
+/// existing one instead of starting a near-duplicate. New categories keep the casing they were given.
+async fn canonical_category(
     database: &Database,
     guild_id: u64,
-    channel_id: u64,
     user_id: u64,
-    category: Option<&str>,
-) -> Result<bool> {
-    let result = sqlx::query(
-        "UPDATE threads SET category = $1 WHERE guild_id = $2 AND channel_id = $3 AND user_id = $4",
+    category: Option<&Category>,
+) -> Result<Option<String>> {
+    let Some(category) = category else {
+        return Ok(None);
+    };
+
+    let existing: Option<String> = sqlx::query_scalar(
+        "SELECT category FROM threads WHERE guild_id = $1 AND user_id = $2 AND lower(category) = lower($3) AND deleted_at IS NULL
+        UNION ALL
+        SELECT category FROM todos WHERE guild_id = $1 AND user_id = $2 AND lower(category) = lower($3)
+        LIMIT 1",
     )
-    .bind(category)
     .bind(guild_id as i64)
-    .bind(channel_id as i64)
     .bind(user_id as i64)
-    .execute(database)
+    .bind(category.as_str())
+    .fetch_optional(database)
     .await?;
 
-    Ok(result.rows_affected() > 0)
+    Ok(Some(existing.unwrap_or_else(|| category.to_string())))
 }
 
-/// Remove an entry from the threads table.
-pub(crate) async fn remove_thread(
+/// Add a new entry to the threads table. Returns false if the user is already tracking the thread.
+pub(crate) async fn add_thread(
     database: &Database,
     guild_id: u64,
     channel_id: u64,
     user_id: u64,
-) -> Result<u64> {
+    category: Option<&Category>,
+) -> Result<bool> {
+    let added = add_threads_bulk(database, guild_id, user_id, &[channel_id], category).await?;
+    Ok(added.contains(&channel_id))
+}
+
+/// Add several new entries to the threads table at once, skipping any that are already tracked.
+/// Threads that were recently untracked are tracked afresh with the new category, clearing the rest of the user's
+/// state for them; only `restore_threads` brings that state back.
+/// Returns the channel IDs of the newly added threads.
+pub(crate) async fn add_threads_bulk(
+    database: &Database,
+    guild_id: u64,
+    user_id: u64,
+    channel_ids: &[u64],
+    category: Option<&Category>,
+) -> Result<Vec<u64>> {
+    let channel_ids: Vec<i64> = channel_ids.iter().map(|&id| id as i64).collect();
+    let category = canonical_category(database, guild_id, user_id, category).await?;
+
+    let added: Vec<i64> = sqlx::query_scalar(
+        "INSERT INTO threads (channel_id, user_id, guild_id, category)
+        SELECT DISTINCT UNNEST($1::bigint[]), $2, $3, $4
+        ON CONFLICT (guild_id, user_id, channel_id)
+        DO UPDATE SET category = EXCLUDED.category, note = NULL, label = NULL, snoozed_until = NULL, deleted_at = NULL WHERE threads.deleted_at IS NOT NULL
+        RETURNING channel_id",
+    )
+    .bind(channel_ids)
+    .bind(user_id as i64)
+    .bind(guild_id as i64)
+    .bind(category)
+    .fetch_all(database)
+    .await?;
+
+    Ok(added.into_iter().map(|id| id as u64).collect())
+}
+
+/// Update the category of an entry in the threads table. Returns the number of entries updated.
+pub(crate) async fn update_thread_category(
+    database: &Database,
+    guild_id: u64,
+    channel_id: u64,
+    user_id: u64,
+    category: Option<&Category>,
+) -> Result<u64> {
+    let category = canonical_category(database, guild_id, user_id, category).await?;
+    let result = sqlx::query(
+        "UPDATE threads SET category = $1 WHERE guild_id = $2 AND channel_id = $3 AND user_id = $4 AND deleted_at IS NULL",
+    )
+    .bind(category)
+    .bind(guild_id as i64)
+    .bind(channel_id as i64)
+    .bind(user_id as i64)
+    .execute(database)
+    .await?;
+
+    Ok(result.rows_affected())
+}
+
+/// Move all of a user's entries in the threads table from one category to another. A `None` category refers to
+/// uncategorised threads. Returns the number of threads moved.
+pub(crate) async fn move_category_threads(
+    database: &Database,
+    guild_id: u64,
+    user_id: u64,
+    from_category: Option<&str>,
+    to_category: Option<&Category>,
+) -> Result<u64> {
+    let to_category = canonical_category(database, guild_id, user_id, to_category).await?;
+    let result = sqlx::query(
+        "UPDATE threads SET category = $3
+        WHERE user_id = $1 AND guild_id = $2 AND deleted_at IS NULL
+        AND (($4::varchar IS NULL AND category IS NULL) OR lower(category) = lower($4))",
+    )
+    .bind(user_id as i64)
+    .bind(guild_id as i64)
+    .bind(to_category)
+    .bind(from_category)
+    .execute(database)
+    .await?;
+
+    Ok(result.rows_affected())
+}
+
+/// Copy a user's entries in the threads table to another user, optionally only those in a given category.
+/// The uncategorised keywords select threads without a category.
+/// Threads the target user already tracks are skipped, and at most `limit` threads are copied. Threads the target
+/// recently untracked are tracked afresh, as in `add_threads_bulk`. Returns the number of threads copied.
+pub(crate) async fn copy_threads_to_user(
+    database: &Database,
+    guild_id: u64,
+    from_user_id: u64,
+    to_user_id: u64,
+    category: Option<&str>,
+    limit: i64,
+) -> Result<u64> {
+    let result = sqlx::query(
+        "INSERT INTO threads (channel_id, user_id, guild_id, category, thread_name)
+        SELECT c.channel_id, $2, c.guild_id, c.category, c.thread_name FROM (
+            SELECT DISTINCT ON (t.channel_id) t.channel_id, t.guild_id, t.category, t.thread_name FROM threads t
+            WHERE t.user_id = $1 AND t.guild_id = $3 AND t.deleted_at IS NULL
+            AND ($4::varchar IS NULL OR CASE WHEN $5 THEN t.category IS NULL ELSE lower(t.category) = lower($4) END)
+            AND NOT EXISTS (
+                SELECT 1 FROM threads o
+                WHERE o.user_id = $2 AND o.guild_id = t.guild_id AND o.channel_id = t.channel_id AND o.deleted_at IS NULL
+            )
+            ORDER BY t.channel_id, t.id
+            LIMIT $6
+        ) c
+        ON CONFLICT (guild_id, user_id, channel_id)
+        DO UPDATE SET category = EXCLUDED.category, note = NULL, label = NULL, snoozed_until = NULL, deleted_at = NULL WHERE threads.deleted_at IS NOT NULL",
+    )
+    .bind(from_user_id as i64)
+    .bind(to_user_id as i64)
+    .bind(guild_id as i64)
+    .bind(category)
+    .bind(category.is_some_and(is_uncategorised))
+    .bind(limit)
+    .execute(database)
+    .await?;
+
+    Ok(result.rows_affected())
+}
+
+/// Set or clear the note on an entry in the threads table.
+pub(crate) async fn set_thread_note(
+    database: &Database,
+    guild_id: u64,
+    channel_id: u64,
+    user_id: u64,
+    note: Option<&str>,
+) -> Result<bool> {
+    let result = sqlx::query(
+        "UPDATE threads SET note = $1 WHERE guild_id = $2 AND channel_id = $3 AND user_id = $4 AND deleted_at IS NULL",
+    )
+    .bind(note)
+    .bind(guild_id as i64)
+    .bind(channel_id as i64)
+    .bind(user_id as i64)
+    .execute(database)
+    .await?;
+
+    Ok(result.rows_affected() > 0)
+}
+
+/// Hide an entry in the threads table from the user's pending threads until the given Unix timestamp, or clear the
+/// snooze if none is given.
+pub(crate) async fn set_thread_snooze(
+    database: &Database,
+    guild_id: u64,
+    channel_id: u64,
+    user_id: u64,
+    until: Option<i64>,
+) -> Result<bool> {
+    let result = sqlx::query(
+        "UPDATE threads SET snoozed_until = to_timestamp($1) WHERE guild_id = $2 AND channel_id = $3 AND user_id = $4 AND deleted_at IS NULL",
+    )
+    .bind(until.map(|t| t as f64))
+    .bind(guild_id as i64)
+    .bind(channel_id as i64)
+    .bind(user_id as i64)
+    .execute(database)
+    .await?;
+
+    Ok(result.rows_affected() > 0)
+}
+
+/// Get the IDs of a user's threads which are currently snoozed.
+pub(crate) async fn list_snoozed_thread_ids(database: &Database, guild_id: u64, user_id: u64) -> Result<Vec<u64>> {
+    let ids: Vec<i64> = sqlx::query_scalar(
+        "SELECT channel_id FROM threads WHERE guild_id = $1 AND user_id = $2 AND snoozed_until > NOW() AND deleted_at IS NULL",
+    )
+    .bind(guild_id as i64)
+    .bind(user_id as i64)
+    .fetch_all(database)
+    .await?;
+
+    Ok(ids.into_iter().map(|id| id as u64).collect())
+}
+
+/// Set or clear the label shown in place of the thread's name for an entry in the threads table.
+pub(crate) async fn set_thread_label(
+    database: &Database,
+    guild_id: u64,
+    channel_id: u64,
+    user_id: u64,
+    label: Option<&str>,
+) -> Result<bool> {
+    let result = sqlx::query(
+        "UPDATE threads SET label = $1 WHERE guild_id = $2 AND channel_id = $3 AND user_id = $4 AND deleted_at IS NULL",
+    )
+    .bind(label)
+    .bind(guild_id as i64)
+    .bind(channel_id as i64)
+    .bind(user_id as i64)
+    .execute(database)
+    .await?;
+
+    Ok(result.rows_affected() > 0)
+}
+
+/// Get when the user last saw each of the given threads in a list, for the threads they have seen.
+pub(crate) async fn list_thread_views(
+    database: &Database,
+    user_id: u64,
+    channel_ids: &[i64],
+) -> Result<Vec<ThreadView>> {
+    sqlx::query_as(
+        "SELECT channel_id, EXTRACT(EPOCH FROM last_seen)::BIGINT AS last_seen FROM thread_views WHERE user_id = $1 AND channel_id = ANY($2)",
+    )
+    .bind(user_id as i64)
+    .bind(channel_ids)
+    .fetch_all(database)
+    .await
+}
+
+/// Record that the user has just seen the given threads in a list.
+pub(crate) async fn mark_threads_seen(database: &Database, user_id: u64, channel_ids: &[i64]) -> Result<u64> {
+    let result = sqlx::query(
+        "INSERT INTO thread_views (user_id, channel_id, last_seen) SELECT $1, UNNEST($2::BIGINT[]), NOW()
+        ON CONFLICT (user_id, channel_id) DO UPDATE SET last_seen = NOW()",
+    )
+    .bind(user_id as i64)
+    .bind(channel_ids)
+    .execute(database)
+    .await?;
+
+    Ok(result.rows_affected())
+}
+
+/// Delete the records of when users last saw threads they no longer have an entry for in the threads table.
+/// Threads untracked within the undo window keep their entry until purged, so restoring them keeps their views.
+pub(crate) async fn purge_orphaned_thread_views(database: &Database) -> Result<u64> {
+    let result = sqlx::query(
+        "DELETE FROM thread_views v
+        WHERE NOT EXISTS (SELECT 1 FROM threads t WHERE t.user_id = v.user_id AND t.channel_id = v.channel_id)",
+    )
+    .execute(database)
+    .await?;
+
+    Ok(result.rows_affected())
+}
+
+/// List the display positions a user has given their categories in a server, in display order.
+pub(crate) async fn list_category_order(
+    database: &Database,
+    guild_id: u64,
+    user_id: u64,
+) -> Result<Vec<CategoryOrder>> {
+    sqlx::query_as(
+        "SELECT category, sort_order FROM category_order WHERE guild_id = $1 AND user_id = $2 ORDER BY sort_order, lower(category)",
+    )
+    .bind(guild_id as i64)
+    .bind(user_id as i64)
+    .fetch_all(database)
+    .await
+}
+
+/// Set the display position of one of a user's categories in a server, or clear it if `sort_order` is `None`.
+pub(crate) async fn set_category_order(
+    database: &Database,
+    guild_id: u64,
+    user_id: u64,
+    category: &Category,
+    sort_order: Option<i32>,
+) -> Result<bool> {
+    let result = match sort_order {
+        Some(sort_order) => {
+            sqlx::query(
+                "INSERT INTO category_order (guild_id, user_id, category, sort_order) VALUES ($1, $2, $3, $4)
+                ON CONFLICT (guild_id, user_id, lower(category)) DO UPDATE SET category = $3, sort_order = $4",
+            )
+            .bind(guild_id as i64)
+            .bind(user_id as i64)
+            .bind(category.as_str())
+            .bind(sort_order)
+            .execute(database)
+            .await?
+        },
+        None => {
+            sqlx::query("DELETE FROM category_order WHERE guild_id = $1 AND user_id = $2 AND lower(category) = lower($3)")
+                .bind(guild_id as i64)
+                .bind(user_id as i64)
+                .bind(category.as_str())
+                .execute(database)
+                .await?
+        },
+    };
+
+    Ok(result.rows_affected() > 0)
+}
+
+/// Store the last-known name of a thread for every user tracking it.
+pub(crate) async fn update_thread_name(
+    database: &Database,
+    guild_id: u64,
+    channel_id: u64,
+    name: &str,
+) -> Result<u64> {
+    let result = sqlx::query(
+        "UPDATE threads SET thread_name = $1 WHERE guild_id = $2 AND channel_id = $3 AND thread_name IS DISTINCT FROM $1",
+    )
+    .bind(name)
+    .bind(guild_id as i64)
+    .bind(channel_id as i64)
+    .execute(database)
+    .await?;
+
+    Ok(result.rows_affected())
+}
+
+/// Remove an entry from the threads table. The entry can be restored with `restore_threads` until it is purged.
+pub(crate) async fn remove_thread(
+    database: &Database,
+    guild_id: u64,
+    channel_id: u64,
+    user_id: u64,
+) -> Result<u64> {
     let result =
-        sqlx::query("DELETE FROM threads WHERE channel_id = $1 AND user_id = $2 AND guild_id = $3")
+        sqlx::query("UPDATE threads SET deleted_at = NOW() WHERE channel_id = $1 AND user_id = $2 AND guild_id = $3 AND deleted_at IS NULL")
             .bind(channel_id as i64)
             .bind(user_id as i64)
             .bind(guild_id as i64)
@@ -366,7 +994,9 @@ pub(crate) async fn remove_thread(
     Ok(result.rows_affected())
 }
 
-/// Remove all entries from the threads table for a given user and guild ID.
+/// Remove all entries from the threads table for a given user and guild ID, optionally only those in a given
+/// category. The uncategorised keywords select threads without a category.
+/// The entries can be restored with `restore_threads` until they are purged.
 pub(crate) async fn remove_all_threads(
     database: &Database,
     guild_id: u64,
@@ -374,13 +1004,18 @@ pub(crate) async fn remove_all_threads(
     category: Option<&str>,
 ) -> Result<u64> {
     let query = match category {
+        Some(c) if is_uncategorised(c) => sqlx::query(
+            "UPDATE threads SET deleted_at = NOW() WHERE user_id = $1 AND guild_id = $2 AND category IS NULL AND deleted_at IS NULL",
+        )
+        .bind(user_id as i64)
+        .bind(guild_id as i64),
         Some(c) => sqlx::query(
-            "DELETE FROM threads where user_id = $1 AND guild_id = $2 AND category = $3",
+            "UPDATE threads SET deleted_at = NOW() WHERE user_id = $1 AND guild_id = $2 AND lower(category) = lower($3) AND deleted_at IS NULL",
         )
         .bind(user_id as i64)
         .bind(guild_id as i64)
         .bind(c),
-        None => sqlx::query("DELETE FROM threads where user_id = $1 AND guild_id = $2")
+        None => sqlx::query("UPDATE threads SET deleted_at = NOW() WHERE user_id = $1 AND guild_id = $2 AND deleted_at IS NULL")
             .bind(user_id as i64)
             .bind(guild_id as i64),
     };
@@ -390,6 +1025,66 @@ pub(crate) async fn remove_all_threads(
     Ok(result.rows_affected())
 }
 
+/// Count the entries in the threads table that `remove_all_threads` would remove for the same arguments.
+pub(crate) async fn count_threads_in_category(
+    database: &Database,
+    guild_id: u64,
+    user_id: u64,
+    category: Option<&str>,
+) -> Result<i64> {
+    sqlx::query_scalar(
+        "SELECT COUNT(*) FROM threads WHERE user_id = $1 AND guild_id = $2
+        AND ($3::varchar IS NULL OR CASE WHEN $4 THEN category IS NULL ELSE lower(category) = lower($3) END) AND deleted_at IS NULL",
+    )
+    .bind(user_id as i64)
+    .bind(guild_id as i64)
+    .bind(category)
+    .bind(category.is_some_and(is_uncategorised))
+    .fetch_one(database)
+    .await
+}
+
+/// Restore up to `limit` entries in the threads table that the user removed within the given window, most recently
+/// removed first. Returns the channel IDs of the restored threads.
+pub(crate) async fn restore_threads(
+    database: &Database,
+    guild_id: u64,
+    user_id: u64,
+    window: Duration,
+    limit: i64,
+) -> Result<Vec<u64>> {
+    let restored: Vec<i64> = sqlx::query_scalar(
+        "UPDATE threads SET deleted_at = NULL
+        WHERE id IN (
+            SELECT id FROM threads
+            WHERE user_id = $1 AND guild_id = $2 AND deleted_at > NOW() - make_interval(secs => $3)
+            ORDER BY deleted_at DESC
+            LIMIT $4
+        )
+        RETURNING channel_id",
+    )
+    .bind(user_id as i64)
+    .bind(guild_id as i64)
+    .bind(window.as_secs_f64())
+    .bind(limit)
+    .fetch_all(database)
+    .await?;
+
+    Ok(restored.into_iter().map(|id| id as u64).collect())
+}
+
+/// Permanently delete entries in the threads table that were removed longer ago than the given window.
+pub(crate) async fn purge_deleted_threads(database: &Database, window: Duration) -> Result<u64> {
+    let result = sqlx::query(
+        "DELETE FROM threads WHERE deleted_at < NOW() - make_interval(secs => $1)",
+    )
+    .bind(window.as_secs_f64())
+    .execute(database)
+    .await?;
+
+    Ok(result.rows_affected())
+}
+
 /// Get all entries from the threads table.
 pub(crate) async fn list_threads(
     database: &Database,
@@ -398,11 +1093,14 @@ pub(crate) async fn list_threads(
     category: Option<&str>,
 ) -> Result<Vec<TrackedThread>> {
     let query = match category {
-        Some(c) => sqlx::query_as("SELECT channel_id, category, guild_id, id FROM threads WHERE user_id = $1 AND guild_id = $2 AND lower(category) = lower($3) ORDER BY id")
+        Some(c) if is_uncategorised(c) => sqlx::query_as("SELECT channel_id, category, guild_id, id, thread_name, note, label FROM threads WHERE user_id = $1 AND guild_id = $2 AND category IS NULL AND deleted_at IS NULL ORDER BY id")
+            .bind(user_id as i64)
+            .bind(guild_id as i64),
+        Some(c) => sqlx::query_as("SELECT channel_id, category, guild_id, id, thread_name, note, label FROM threads WHERE user_id = $1 AND guild_id = $2 AND lower(category) = lower($3) AND deleted_at IS NULL ORDER BY id")
             .bind(user_id as i64)
             .bind(guild_id as i64)
             .bind(c),
-        None => sqlx::query_as("SELECT channel_id, category, guild_id, id FROM threads WHERE user_id = $1 AND guild_id = $2 ORDER BY id")
+        None => sqlx::query_as("SELECT channel_id, category, guild_id, id, thread_name, note, label FROM threads WHERE user_id = $1 AND guild_id = $2 AND deleted_at IS NULL ORDER BY id")
             .bind(user_id as i64)
             .bind(guild_id as i64),
     };
@@ -410,17 +1108,25 @@ pub(crate) async fn list_threads(
     query.fetch_all(database).await
 }
 
-/// Get an entry from the threads table with a specific channel ID and user ID.
+/// Get all of a user's entries from the threads table across every guild, ordered by guild.
+pub(crate) async fn list_all_threads_for_user(database: &Database, user_id: u64) -> Result<Vec<TrackedThread>> {
+    sqlx::query_as("SELECT channel_id, category, guild_id, id, thread_name, note, label FROM threads WHERE user_id = $1 AND deleted_at IS NULL ORDER BY guild_id, id")
+        .bind(user_id as i64)
+        .fetch_all(database)
+        .await
+}
+
+/// Get a user's entry for a specific thread from the threads table.
 pub(crate) async fn get_thread(
     database: &Database,
     guild_id: u64,
-    user_id: u64,
     channel_id: u64,
+    user_id: u64,
 ) -> Result<Option<TrackedThread>> {
-    sqlx::query_as("SELECT channel_id, category, guild_id, id FROM threads WHERE user_id = $1 AND channel_id = $2 AND guild_id = $3 ORDER BY id")
-        .bind(user_id as i64)
-        .bind(channel_id as i64)
+    sqlx::query_as("SELECT channel_id, category, guild_id, id, thread_name, note, label FROM threads WHERE guild_id = $1 AND channel_id = $2 AND user_id = $3 AND deleted_at IS NULL")
         .bind(guild_id as i64)
+        .bind(channel_id as i64)
+        .bind(user_id as i64)
         .fetch_optional(database)
         .await
 }
@@ -432,7 +1138,7 @@ pub(crate) async fn get_users_tracking_thread(
     channel_id: impl Into<u64>,
 ) -> Result<Vec<UserId>> {
     let result: Vec<TrackedThreadUser> = sqlx::query_as(
-        "SELECT user_id FROM threads WHERE channel_id = $1 AND guild_id = $2 ORDER BY id",
+        "SELECT user_id FROM threads WHERE channel_id = $1 AND guild_id = $2 AND deleted_at IS NULL ORDER BY id",
     )
     .bind(channel_id.into() as i64)
     .bind(guild_id.into() as i64)
@@ -442,11 +1148,69 @@ pub(crate) async fn get_users_tracking_thread(
     Ok(result.into_iter().map(|user| user.into()).collect())
 }
 
+/// Find threads that a user has more than one entry for in the threads table.
+pub(crate) async fn find_duplicate_threads(
+    database: &Database,
+    guild_id: u64,
+    user_id: u64,
+) -> Result<Vec<DuplicateThread>> {
+    sqlx::query_as(
+        "SELECT channel_id, COUNT(*) AS copies FROM threads WHERE guild_id = $1 AND user_id = $2
+        GROUP BY channel_id HAVING COUNT(*) > 1 ORDER BY channel_id",
+    )
+    .bind(guild_id as i64)
+    .bind(user_id as i64)
+    .fetch_all(database)
+    .await
+}
+
+/// Remove a user's duplicate entries from the threads table, keeping the lowest ID for each thread, or the lowest
+/// ID that hasn't been untracked if there is one. Returns the number of entries removed.
+pub(crate) async fn dedupe_threads(database: &Database, guild_id: u64, user_id: u64) -> Result<u64> {
+    let result = sqlx::query(
+        "DELETE FROM threads WHERE id IN (
+            SELECT id FROM (
+                SELECT id, ROW_NUMBER() OVER (PARTITION BY channel_id ORDER BY deleted_at IS NOT NULL, id) AS position
+                FROM threads WHERE guild_id = $1 AND user_id = $2
+            ) ranked WHERE position > 1
+        )",
+    )
+    .bind(guild_id as i64)
+    .bind(user_id as i64)
+    .execute(database)
+    .await?;
+
+    Ok(result.rows_affected())
+}
+
+/// Get every channel tracked in a guild along with the number of users tracking it.
+pub(crate) async fn guild_thread_summary(
+    database: &Database,
+    guild_id: u64,
+) -> Result<Vec<TrackedThreadSummary>> {
+    sqlx::query_as(
+        "SELECT channel_id, COUNT(*) AS trackers FROM threads WHERE guild_id = $1 AND deleted_at IS NULL GROUP BY channel_id ORDER BY trackers DESC, channel_id",
+    )
+    .bind(guild_id as i64)
+    .fetch_all(database)
+    .await
+}
+
 /// Get all unique channel_ids from tracked threads (globally).
 pub(crate) async fn get_global_tracked_thread_ids(
     database: &Database,
 ) -> Result<Vec<TrackedThreadId>> {
-    sqlx::query_as("SELECT DISTINCT channel_id FROM threads").fetch_all(database).await
+    sqlx::query_as("SELECT DISTINCT channel_id FROM threads WHERE deleted_at IS NULL")
+        .fetch_all(database)
+        .await
+}
+
+/// Count the users tracking the given channel (globally).
+pub(crate) async fn count_trackers_for_channel(database: &Database, channel_id: u64) -> Result<i64> {
+    sqlx::query_scalar("SELECT COUNT(*) FROM threads WHERE channel_id = $1 AND deleted_at IS NULL")
+        .bind(channel_id as i64)
+        .fetch_one(database)
+        .await
 }
 
 /// Add an entry to the muses table
@@ -456,51 +1220,66 @@ pub(crate) async fn add_muse(
     user_id: u64,
     muse: &str,
 ) -> Result<bool> {
-    match get_muse(database, guild_id, user_id, muse).await? {
-        Some(_) => Ok(false),
-        None => {
-            sqlx::query("INSERT INTO muses (muse_name, user_id, guild_id) VALUES ($1, $2, $3)")
-                .bind(muse)
-                .bind(user_id as i64)
-                .bind(guild_id as i64)
-                .execute(database)
-                .await?;
-
-            Ok(true)
-        },
-    }
-}
-
-/// Get an entry from the muses table by name
-pub(crate) async fn get_muse(
-    database: &Database,
-    guild_id: u64,
-    user_id: u64,
-    muse: &str,
-) -> Result<Option<Muse>> {
-    sqlx::query_as(
-        "SELECT id, muse_name FROM muses WHERE user_id = $1 AND guild_id = $2 AND lower(muse_name) = lower($3)",
+    let added: Option<i32> = sqlx::query_scalar(
+        "INSERT INTO muses (muse_name, user_id, guild_id) VALUES ($1, $2, $3)
+        ON CONFLICT (guild_id, user_id, lower(muse_name)) DO NOTHING
+        RETURNING id",
     )
+    .bind(muse)
     .bind(user_id as i64)
     .bind(guild_id as i64)
-    .bind(muse)
     .fetch_optional(database)
-    .await
+    .await?;
+
+    Ok(added.is_some())
 }
 
-/// Get all entries from the muses table for a given user and guild ID
+/// Get all entries from the muses table for a given user and guild ID, including the user's global muses.
 pub(crate) async fn list_muses(
     database: &Database,
     guild_id: u64,
     user_id: u64,
 ) -> Result<Vec<Muse>> {
-    sqlx::query_as("SELECT id, muse_name FROM muses WHERE user_id = $1 AND guild_id = $2")
+    sqlx::query_as("SELECT id, muse_name, guild_id FROM muses WHERE user_id = $1 AND guild_id IN ($2, $3) ORDER BY id")
         .bind(user_id as i64)
         .bind(guild_id as i64)
+        .bind(GLOBAL_MUSE_GUILD_ID as i64)
         .fetch_all(database)
         .await
 }
 
+/// Remove an entry from the muses table by its ID. Only muses belonging to the given user are removed.
+pub(crate) async fn remove_muse_by_id(database: &Database, user_id: u64, id: i32) -> Result<u64> {
+    let result = sqlx::query("DELETE FROM muses WHERE id = $1 AND user_id = $2")
+        .bind(id)
+        .bind(user_id as i64)
+        .execute(database)
+        .await?;
+
+    Ok(result.rows_affected())
+}
+
+/// Rename an entry in the muses table. Fails with a unique violation if the user already has a muse with the new name.
+pub(crate) async fn rename_muse(
+    database: &Database,
+    guild_id: u64,
+    user_id: u64,
+    old_name: &str,
+    new_name: &str,
+) -> Result<u64> {
+    let result = sqlx::query(
+        "UPDATE muses SET muse_name = $1 WHERE lower(muse_name) = lower($2) AND user_id = $3 AND guild_id = $4",
+    )
+    .bind(new_name)
+    .bind(old_name)
+    .bind(user_id as i64)
+    .bind(guild_id as i64)
+    .execute(database)
+    .await?;
+
+    Ok(result.rows_affected())
+}
+
 /// Remove an entry from the muses table
 pub(crate) async fn remove_muse(
     database: &Database,
@@ -526,48 +1305,26 @@ pub(crate) async fn add_todo(
     guild_id: u64,
     user_id: u64,
     content: &str,
-    category: Option<&str>,
+    category: Option<&Category>,
+    channel_id: Option<u64>,
 ) -> Result<bool> {
-    match get_todo(database, guild_id, user_id, content).await? {
-        Some(t) if t.category.as_deref() != category => {
-            sqlx::query("UPDATE todos SET category = $1 WHERE user_id = $2 AND guild_id = $3 AND lower(content) = lower($4)")
-                .bind(category)
-                .bind(user_id as i64)
-                .bind(guild_id as i64)
-                .bind(content)
-                .execute(database).await?;
-
-            Ok(true)
-        },
-        Some(_) => Ok(false),
-        None => {
-            sqlx::query(
-                "INSERT INTO todos (content, category, user_id, guild_id) VALUES ($1, $2, $3, $4)",
-            )
-            .bind(content)
-            .bind(category)
-            .bind(user_id as i64)
-            .bind(guild_id as i64)
-            .execute(database)
-            .await?;
-
-            Ok(true)
-        },
-    }
-}
+    let category = canonical_category(database, guild_id, user_id, category).await?;
+    let changed: Option<i32> = sqlx::query_scalar(
+        "INSERT INTO todos (content, category, user_id, guild_id, channel_id) VALUES ($1, $2, $3, $4, $5)
+        ON CONFLICT (guild_id, user_id, lower(content))
+        DO UPDATE SET category = EXCLUDED.category, channel_id = EXCLUDED.channel_id
+        WHERE todos.category IS DISTINCT FROM EXCLUDED.category OR todos.channel_id IS DISTINCT FROM EXCLUDED.channel_id
+        RETURNING id",
+    )
+    .bind(content)
+    .bind(category)
+    .bind(user_id as i64)
+    .bind(guild_id as i64)
+    .bind(channel_id.map(|id| id as i64))
+    .fetch_optional(database)
+    .await?;
 
-/// Get an entry from the todos table by its content
-pub(crate) async fn get_todo(
-    database: &Database,
-    guild_id: u64,
-    user_id: u64,
-    content: &str,
-) -> Result<Option<Todo>> {
-    sqlx::query_as("SELECT id, content, category FROM todos WHERE user_id = $1 AND guild_id = $2 AND lower(content) = lower($3)")
-        .bind(user_id as i64)
-        .bind(guild_id as i64)
-        .bind(content)
-        .fetch_optional(database).await
+    Ok(changed.is_some())
 }
 
 /// Get all entries from the todos table for a given user and guild ID
@@ -578,14 +1335,103 @@ pub(crate) async fn list_todos(
     category: Option<&str>,
 ) -> Result<Vec<Todo>> {
     let query = match category {
-        Some(cat) => sqlx::query_as("SELECT id, content, category FROM todos WHERE lower(category) = lower($1) AND user_id = $2 AND guild_id = $3")
+        Some(cat) if is_uncategorised(cat) => sqlx::query_as("SELECT id, content, category, channel_id FROM todos WHERE category IS NULL AND user_id = $1 AND guild_id = $2 ORDER BY sort_order NULLS LAST, id"),
+        Some(cat) => sqlx::query_as("SELECT id, content, category, channel_id FROM todos WHERE lower(category) = lower($1) AND user_id = $2 AND guild_id = $3 ORDER BY sort_order NULLS LAST, id")
             .bind(cat),
-        None => sqlx::query_as("SELECT id, content, category FROM todos WHERE user_id = $1 AND guild_id = $2"),
+        None => sqlx::query_as("SELECT id, content, category, channel_id FROM todos WHERE user_id = $1 AND guild_id = $2 ORDER BY sort_order NULLS LAST, id"),
     };
 
     query.bind(user_id as i64).bind(guild_id as i64).fetch_all(database).await
 }
 
+/// Search the todos table for entries containing the given text, case-insensitively.
+pub(crate) async fn search_todos(
+    database: &Database,
+    guild_id: u64,
+    user_id: u64,
+    query: &str,
+) -> Result<Vec<Todo>> {
+    let pattern = format!("%{}%", query.replace('\\', "\\\\").replace('%', "\\%").replace('_', "\\_"));
+
+    sqlx::query_as("SELECT id, content, category, channel_id FROM todos WHERE user_id = $1 AND guild_id = $2 AND content ILIKE $3 ORDER BY sort_order NULLS LAST, id")
+        .bind(user_id as i64)
+        .bind(guild_id as i64)
+        .bind(pattern)
+        .fetch_all(database)
+        .await
+}
+
+/// Get the entries from the todos table whose category no longer has any tracked threads for the same user.
+pub(crate) async fn list_orphaned_todos(database: &Database, guild_id: u64, user_id: u64) -> Result<Vec<Todo>> {
+    sqlx::query_as(
+        "SELECT id, content, category, channel_id FROM todos t WHERE user_id = $1 AND guild_id = $2 AND category IS NOT NULL
+        AND NOT EXISTS (
+            SELECT 1 FROM threads th WHERE th.user_id = t.user_id AND th.guild_id = t.guild_id
+            AND lower(th.category) = lower(t.category) AND th.deleted_at IS NULL
+        )
+        ORDER BY lower(category), sort_order NULLS LAST, id",
+    )
+    .bind(user_id as i64)
+    .bind(guild_id as i64)
+    .fetch_all(database)
+    .await
+}
+
+/// Move the entries in the todos table with the given IDs to a different category.
+pub(crate) async fn update_todos_category(
+    database: &Database,
+    guild_id: u64,
+    user_id: u64,
+    ids: &[i32],
+    category: Option<&Category>,
+) -> Result<u64> {
+    let category = canonical_category(database, guild_id, user_id, category).await?;
+    let result = sqlx::query(
+        "UPDATE todos SET category = $1, sort_order = NULL WHERE id = ANY($2) AND user_id = $3 AND guild_id = $4",
+    )
+    .bind(category)
+    .bind(ids)
+    .bind(user_id as i64)
+    .bind(guild_id as i64)
+    .execute(database)
+    .await?;
+
+    Ok(result.rows_affected())
+}
+
+/// Remove the entries from the todos table with the given IDs.
+pub(crate) async fn remove_todos_by_id(database: &Database, guild_id: u64, user_id: u64, ids: &[i32]) -> Result<u64> {
+    let result = sqlx::query("DELETE FROM todos WHERE id = ANY($1) AND user_id = $2 AND guild_id = $3")
+        .bind(ids)
+        .bind(user_id as i64)
+        .bind(guild_id as i64)
+        .execute(database)
+        .await?;
+
+    Ok(result.rows_affected())
+}
+
+/// Set the order of entries in the todos table to match the order of the given IDs.
+pub(crate) async fn reorder_todos(
+    database: &Database,
+    guild_id: u64,
+    user_id: u64,
+    ids: &[i32],
+) -> Result<u64> {
+    let result = sqlx::query(
+        "UPDATE todos SET sort_order = ordered.position
+        FROM UNNEST($1::integer[]) WITH ORDINALITY AS ordered(id, position)
+        WHERE todos.id = ordered.id AND todos.user_id = $2 AND todos.guild_id = $3",
+    )
+    .bind(ids)
+    .bind(user_id as i64)
+    .bind(guild_id as i64)
+    .execute(database)
+    .await?;
+
+    Ok(result.rows_affected())
+}
+
 /// Remove an entry from the todos table
 pub(crate) async fn remove_todo(
     database: &Database,
@@ -629,3 +1475,16 @@ pub(crate) async fn remove_all_todos(
 pub(crate) async fn statistics(database: &Database) -> Result<Statistics> {
     sqlx::query_as(include_str!("../sql/queries/stats.sql")).fetch_one(database).await
 }
+
+/// Query for the statistics of every guild from the database, one row per guild
+pub(crate) async fn all_guild_statistics(database: &Database) -> Result<Vec<ServerStatistics>> {
+    sqlx::query_as(include_str!("../sql/queries/all_guild_stats.sql")).fetch_all(database).await
+}
+
+/// Query for statistics scoped to a single guild from the database
+pub(crate) async fn guild_statistics(database: &Database, guild_id: u64) -> Result<GuildStatistics> {
+    sqlx::query_as(include_str!("../sql/queries/guild_stats.sql"))
+        .bind(guild_id as i64)
+        .fetch_one(database)
+        .await
+}
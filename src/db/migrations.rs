@@ -0,0 +1,51 @@
+use sqlx::Executor;
+use tracing::info;
+
+use super::{Database, Result};
+
+/// The schema migrations, in order. Each migration is applied once, in a transaction, and its version is recorded
+/// in the `schema_version` table. Add new migrations to the end of this list; never edit one that has shipped.
+const MIGRATIONS: &[(i32, &str)] = &[
+    (1, include_str!("../../sql/migrations/0001_initial_schema.sql")),
+    (2, include_str!("../../sql/migrations/0002_soft_delete_threads.sql")),
+    (3, include_str!("../../sql/migrations/0003_todo_sort_order.sql")),
+    (4, include_str!("../../sql/migrations/0004_watcher_notifications.sql")),
+    (5, include_str!("../../sql/migrations/0005_subscription_categories.sql")),
+    (6, include_str!("../../sql/migrations/0006_queued_notification_threads.sql")),
+    (7, include_str!("../../sql/migrations/0007_pause_watchers.sql")),
+    (8, include_str!("../../sql/migrations/0008_todo_threads.sql")),
+    (9, include_str!("../../sql/migrations/0009_snooze_threads.sql")),
+    (10, include_str!("../../sql/migrations/0010_thread_views.sql")),
+    (11, include_str!("../../sql/migrations/0011_thread_labels.sql")),
+    (12, include_str!("../../sql/migrations/0012_watcher_status.sql")),
+    (13, include_str!("../../sql/migrations/0013_category_order.sql")),
+];
+
+/// Apply any migrations newer than the database's recorded schema version.
+pub(crate) async fn run_migrations(database: &Database) -> Result<()> {
+    let mut transaction = database.begin().await?;
+
+    transaction
+        .execute("CREATE TABLE IF NOT EXISTS schema_version (version INTEGER NOT NULL)")
+        .await?;
+    transaction.execute("LOCK TABLE schema_version IN EXCLUSIVE MODE").await?;
+
+    let current_version: i32 =
+        sqlx::query_scalar("SELECT COALESCE(MAX(version), 0) FROM schema_version")
+            .fetch_one(&mut *transaction)
+            .await?;
+
+    for &(version, migration) in MIGRATIONS.iter().filter(|(version, _)| *version > current_version) {
+        info!("applying schema migration {}", version);
+        transaction.execute(migration).await?;
+
+        sqlx::query("INSERT INTO schema_version (version) VALUES ($1)")
+            .bind(version)
+            .execute(&mut *transaction)
+            .await?;
+    }
+
+    transaction.commit().await?;
+
+    Ok(())
+}
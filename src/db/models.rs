@@ -1,9 +1,14 @@
 #![allow(dead_code)]
 
+use chrono::{DateTime, Utc};
 use poise::serenity_prelude::{ChannelId, GuildId, MessageId, UserId};
+use serde::Serialize;
 use sqlx::FromRow;
 
-use crate::utils::{ChannelMessage, GuildUser};
+use crate::{
+    consts::GLOBAL_MUSE_GUILD_ID,
+    utils::{ChannelMessage, GuildUser},
+};
 
 #[derive(FromRow)]
 pub(crate) struct TrackedThread {
@@ -14,6 +19,12 @@ pub(crate) struct TrackedThread {
     #[sqlx(try_from = "i64")]
     pub guild_id: u64,
     pub category: Option<String>,
+    /// The last-known name of the thread, used when the thread can't be looked up.
+    pub thread_name: Option<String>,
+    /// The user's private note for this thread, if any.
+    pub note: Option<String>,
+    /// The name the user has chosen to show for this thread in their lists, if any.
+    pub label: Option<String>,
 }
 
 impl TrackedThread {
@@ -35,6 +46,57 @@ pub(crate) struct TrackedThreadId {
     pub channel_id: u64,
 }
 
+#[derive(FromRow)]
+pub(crate) struct TrackedThreadSummary {
+    #[sqlx(try_from = "i64")]
+    pub channel_id: u64,
+    pub trackers: i64,
+}
+
+impl TrackedThreadSummary {
+    /// Get the ChannelId for this thread.
+    pub fn channel_id(&self) -> ChannelId {
+        self.channel_id.into()
+    }
+}
+
+#[derive(FromRow)]
+pub(crate) struct ThreadView {
+    #[sqlx(try_from = "i64")]
+    pub channel_id: u64,
+    /// When the user last saw the thread in one of Titi's lists, as a Unix timestamp.
+    pub last_seen: i64,
+}
+
+impl ThreadView {
+    /// Get the ChannelId for this thread.
+    pub fn channel_id(&self) -> ChannelId {
+        self.channel_id.into()
+    }
+}
+
+#[derive(FromRow)]
+pub(crate) struct CategoryOrder {
+    pub category: String,
+    /// Where the category is placed in the user's lists; lower numbers are shown first.
+    pub sort_order: i32,
+}
+
+#[derive(FromRow)]
+pub(crate) struct DuplicateThread {
+    #[sqlx(try_from = "i64")]
+    pub channel_id: u64,
+    /// The number of entries for this thread, including the one that is kept.
+    pub copies: i64,
+}
+
+impl DuplicateThread {
+    /// Get the ChannelId for this thread.
+    pub fn channel_id(&self) -> ChannelId {
+        self.channel_id.into()
+    }
+}
+
 #[derive(FromRow)]
 #[repr(transparent)]
 pub(crate) struct TrackedThreadUser {
@@ -60,6 +122,18 @@ pub(crate) struct ThreadWatcher {
     #[sqlx(try_from = "i64")]
     pub guild_id: u64,
     pub categories: Option<String>,
+    /// Whether to notify the user when more threads are awaiting their reply.
+    pub notify: bool,
+    /// A hash of the threads awaiting a reply when the watcher was last updated.
+    pub content_hash: Option<i64>,
+    /// The number of threads awaiting a reply when the watcher was last updated.
+    pub pending_count: Option<i32>,
+    /// Whether updates to the watched message are paused.
+    pub paused: bool,
+    /// The Unix timestamp of the last successful update to the watched message.
+    pub last_updated: Option<i64>,
+    /// The error from the most recent failed update, cleared when an update succeeds.
+    pub last_error: Option<String>,
 }
 
 impl ThreadWatcher {
@@ -96,17 +170,33 @@ impl ThreadWatcher {
 
 #[derive(FromRow)]
 pub(crate) struct Muse {
-    #[allow(dead_code)]
     pub id: i32,
     pub muse_name: String,
+    #[sqlx(try_from = "i64")]
+    pub guild_id: u64,
+}
+
+impl Muse {
+    /// Returns true if this muse applies in every server rather than a single one.
+    pub fn is_global(&self) -> bool {
+        self.guild_id == GLOBAL_MUSE_GUILD_ID
+    }
 }
 
 #[derive(FromRow)]
 pub(crate) struct Todo {
-    #[allow(dead_code)]
     pub id: i32,
     pub content: String,
     pub category: Option<String>,
+    /// The thread this entry is linked to, if any.
+    pub channel_id: Option<i64>,
+}
+
+impl Todo {
+    /// Get the ChannelId of the thread this entry is linked to, if any.
+    pub fn channel_id(&self) -> Option<ChannelId> {
+        self.channel_id.map(|id| ChannelId::new(id as u64))
+    }
 }
 
 #[derive(FromRow)]
@@ -122,7 +212,7 @@ impl Subscription {
     }
 }
 
-#[derive(FromRow)]
+#[derive(FromRow, Serialize)]
 pub(crate) struct Statistics {
     pub users: i64,
     pub servers: i64,
@@ -134,6 +224,29 @@ pub(crate) struct Statistics {
     pub scheduled_messages: i64,
 }
 
+#[derive(FromRow)]
+pub(crate) struct GuildStatistics {
+    pub users: i64,
+    pub threads_distinct: i64,
+    pub threads_total: i64,
+    pub muses: i64,
+    pub todos: i64,
+    pub watchers: i64,
+}
+
+/// Usage statistics for one server, as listed for every server at once.
+#[derive(FromRow, Serialize)]
+pub(crate) struct ServerStatistics {
+    #[sqlx(try_from = "i64")]
+    pub guild_id: u64,
+    pub users: i64,
+    pub threads_distinct: i64,
+    pub threads_total: i64,
+    pub muses: i64,
+    pub todos: i64,
+    pub watchers: i64,
+}
+
 #[derive(FromRow)]
 pub(crate) struct UserSetting {
     #[sqlx(try_from = "i64")]
@@ -148,6 +261,33 @@ impl UserSetting {
     }
 }
 
+#[derive(FromRow)]
+pub(crate) struct GuildSetting {
+    #[sqlx(try_from = "i64")]
+    pub guild_id: u64,
+    pub name: String,
+    pub value: String,
+}
+
+impl GuildSetting {
+    pub(crate) fn guild_id(&self) -> GuildId {
+        self.guild_id.into()
+    }
+}
+
+#[derive(FromRow)]
+pub(crate) struct ServerNickname {
+    #[sqlx(try_from = "i64")]
+    pub guild_id: u64,
+    pub nickname: String,
+}
+
+impl ServerNickname {
+    pub(crate) fn guild_id(&self) -> GuildId {
+        self.guild_id.into()
+    }
+}
+
 #[derive(FromRow)]
 pub(crate) struct ScheduledMessage {
     pub id: i32,
@@ -170,6 +310,38 @@ impl ScheduledMessage {
     pub(crate) fn channel_id(&self) -> ChannelId {
         self.channel_id.into()
     }
+
+    /// Apply the given changes, leaving out any that are `None`. A new datetime makes an already sent message
+    /// sendable again.
+    pub(crate) fn apply_update(
+        &mut self,
+        datetime: Option<DateTime<Utc>>,
+        repeat: Option<String>,
+        title: Option<String>,
+        message: Option<String>,
+        channel_id: Option<u64>,
+    ) {
+        if let Some(datetime) = datetime {
+            self.datetime = datetime.to_rfc3339();
+            self.archived = false;
+        }
+
+        if let Some(repeat) = repeat {
+            self.repeat = repeat;
+        }
+
+        if let Some(title) = title {
+            self.title = title;
+        }
+
+        if let Some(message) = message {
+            self.message = message;
+        }
+
+        if let Some(channel_id) = channel_id {
+            self.channel_id = channel_id;
+        }
+    }
 }
 
 #[derive(FromRow)]
@@ -180,6 +352,7 @@ pub(crate) struct ScheduledMessageSummary {
     pub datetime: String,
     pub repeat: String,
     pub title: String,
+    pub archived: bool,
 }
 
 impl ScheduledMessageSummary {
@@ -187,3 +360,72 @@ impl ScheduledMessageSummary {
         self.channel_id.into()
     }
 }
+
+#[derive(FromRow)]
+pub(crate) struct QueuedNotification {
+    pub id: i32,
+    #[sqlx(try_from = "i64")]
+    pub user_id: u64,
+    /// The server and thread the notification is for. Notifications queued before these were recorded have neither.
+    pub guild_id: Option<i64>,
+    pub channel_id: Option<i64>,
+    pub content: String,
+    pub preview_title: Option<String>,
+    pub preview: Option<String>,
+}
+
+impl QueuedNotification {
+    pub(crate) fn user_id(&self) -> UserId {
+        self.user_id.into()
+    }
+
+    /// Get the server and thread the notification is for, if they were recorded.
+    pub(crate) fn thread(&self) -> Option<(GuildId, ChannelId)> {
+        Some((GuildId::new(self.guild_id? as u64), ChannelId::new(self.channel_id? as u64)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::TimeZone;
+
+    use super::*;
+
+    fn archived_message() -> ScheduledMessage {
+        ScheduledMessage {
+            id: 1,
+            user_id: 10,
+            channel_id: 20,
+            datetime: "2024-01-01T12:00:00+00:00".to_owned(),
+            repeat: String::new(),
+            title: "Title".to_owned(),
+            message: "Message".to_owned(),
+            archived: true,
+        }
+    }
+
+    #[test]
+    fn new_datetime_makes_archived_message_sendable() {
+        let mut message = archived_message();
+        let datetime = Utc.with_ymd_and_hms(2030, 6, 1, 9, 30, 0).unwrap();
+
+        message.apply_update(Some(datetime), None, None, None, None);
+
+        assert!(!message.archived);
+        assert_eq!(message.datetime, datetime.to_rfc3339());
+    }
+
+    #[test]
+    fn other_changes_leave_archived_message_archived() {
+        let mut message = archived_message();
+
+        message.apply_update(None, Some("1d".to_owned()), Some("New".to_owned()), Some("Body".to_owned()), Some(30));
+
+        assert!(message.archived);
+        assert_eq!(message.datetime, "2024-01-01T12:00:00+00:00");
+        assert_eq!(message.repeat, "1d");
+        assert_eq!(message.title, "New");
+        assert_eq!(message.message, "Body");
+        assert_eq!(message.channel_id, 30);
+    }
+}
@@ -5,11 +5,11 @@ use std::{
         Arc,
         Mutex,
     },
-    time::Duration,
+    time::{Duration, Instant},
 };
 
 use background_tasks::Task;
-use cache::MessageCache;
+use cache::{ActiveThreadsCache, DeleteEmojiCache, GuildPrefixCache, MessageCache, UnavailableChannelCache};
 use commands::{threads, CommandError};
 use db::Database;
 use poise::{
@@ -20,14 +20,13 @@ use serenity::model::channel::Message;
 use sqlx::{
     postgres::{PgConnectOptions, PgPoolOptions},
     ConnectOptions,
-    Executor,
 };
 use tokio::{
-    sync::{mpsc::{self, Sender}, RwLock},
-    time::sleep,
+    sync::{mpsc::{self, Sender}, watch, RwLock, Semaphore},
+    time::{sleep, timeout},
 };
 use toml::Table;
-use tracing::{debug, error, info, log::LevelFilter};
+use tracing::{debug, error, info, log::LevelFilter, warn};
 use utils::message_is_command;
 
 use crate::{
@@ -36,12 +35,30 @@ use crate::{
         run_periodic_shard_tasks,
         start_periodic_tasks,
     },
-    consts::{DELETE_EMOJI, MPSC_BUFFER_SIZE, SHARD_CHECKUP_INTERVAL},
+    consts::{
+        setting_names::{GUILD_DELETE_BY_REACTION, GUILD_DELETE_EMOJI, GUILD_PREFIX},
+        ACTIVE_THREADS_CACHE_LIFETIME,
+        CACHE_LIFETIME,
+        CACHE_TRIM_INTERVAL,
+        DEBUG_USER,
+        DEFAULT_PREFIX,
+        DELETE_EMOJI,
+        LAST_REPLY_LOOKBACK,
+        MAX_DATABASE_CONNECTIONS,
+        MAX_WATCHER_UPDATE_TASKS,
+        MESSAGE_CACHE_MAX_ENTRIES,
+        MPSC_BUFFER_SIZE,
+        SHARD_CHECKUP_INTERVAL,
+        SHUTDOWN_TIMEOUT,
+        TRACK_THREAD_BUTTON_ID,
+        UNAVAILABLE_CHANNEL_CACHE_LIFETIME,
+    },
     messaging::reply_error,
 };
 
 mod background_tasks;
 mod cache;
+mod category;
 mod commands;
 mod consts;
 mod db;
@@ -60,35 +77,155 @@ struct Data {
     guild_count: AtomicUsize,
     /// Threadsafe memory cache for messages the bot has sent or looked up
     message_cache: MessageCache,
+    /// Short-lived cache of each guild's active threads, shared between list renders
+    active_threads_cache: ActiveThreadsCache,
+    /// Channels which recently couldn't be looked up, so list renders can skip them for a while
+    unavailable_channels: UnavailableChannelCache,
+    /// Cache of each guild's text command prefix
+    guild_prefixes: GuildPrefixCache,
+    /// Cache of the emojis which delete Titi's messages in each guild
+    delete_emojis: DeleteEmojiCache,
     /// The current list of tracked threads
     tracked_threads: Arc<RwLock<HashSet<ChannelId>>>,
+    /// The users who currently have a thread list being built
+    rendering_lists: Mutex<HashSet<UserId>>,
+    /// How many watcher update tasks may run at once
+    max_watcher_update_tasks: usize,
+    /// Limits how many watched messages are updated at once when watcher updates overlap
+    watcher_update_permits: Arc<Semaphore>,
+    /// The user that bug reports are sent to
+    bug_report_user: UserId,
+    /// When the bot was started
+    started: Instant,
+}
+
+/// Marks a user as having a thread list being built until it is dropped.
+struct ListRenderGuard<'a> {
+    data: &'a Data,
+    user_id: UserId,
+}
+
+impl Drop for ListRenderGuard<'_> {
+    fn drop(&mut self) {
+        self.data.rendering_lists.lock().unwrap_or_else(|e| e.into_inner()).remove(&self.user_id);
+    }
 }
 
 impl Data {
     /// Create a new Data.
-    fn new(database: Database) -> Self {
+    fn new(
+        database: Database,
+        message_cache_lifetime: Duration,
+        max_watcher_update_tasks: usize,
+        bug_report_user: UserId,
+    ) -> Self {
         Self {
             database,
-            message_cache: MessageCache::new(),
+            message_cache: MessageCache::new(Some(MESSAGE_CACHE_MAX_ENTRIES)).with_lifetime(message_cache_lifetime),
+            active_threads_cache: ActiveThreadsCache::new(None)
+                .with_lifetime(ACTIVE_THREADS_CACHE_LIFETIME),
+            unavailable_channels: UnavailableChannelCache::new(None)
+                .with_lifetime(UNAVAILABLE_CHANNEL_CACHE_LIFETIME),
+            guild_prefixes: GuildPrefixCache::new(None),
+            delete_emojis: DeleteEmojiCache::new(None),
             tracked_threads: Arc::new(RwLock::new(HashSet::new())),
+            rendering_lists: Mutex::new(HashSet::new()),
+            max_watcher_update_tasks,
+            watcher_update_permits: Arc::new(Semaphore::new(max_watcher_update_tasks)),
+            bug_report_user,
             guild_count: AtomicUsize::new(0),
+            started: Instant::now(),
         }
     }
 
+    /// Get how long the bot has been running.
+    fn uptime(&self) -> Duration {
+        self.started.elapsed()
+    }
+
     /// Retrieve the current guild count.
     fn guilds(&self) -> usize {
         self.guild_count.load(Ordering::SeqCst)
     }
 
+    /// Mark the user as having a thread list being built, so that they can't start building another one at the
+    /// same time. The returned guard clears the mark when dropped.
+    fn begin_list_render(&self, user_id: UserId) -> Result<ListRenderGuard<'_>, CommandError> {
+        let mut users = self.rendering_lists.lock().unwrap_or_else(|e| e.into_inner());
+        if users.insert(user_id) {
+            Ok(ListRenderGuard { data: self, user_id })
+        }
+        else {
+            Err(CommandError::new("Your previous thread list is still being put together. Please wait for it to finish first."))
+        }
+    }
+
+    /// Get the text command prefix for the given guild, or the default prefix outside of guilds.
+    async fn guild_prefix(&self, guild_id: Option<GuildId>) -> String {
+        let Some(guild_id) = guild_id else {
+            return DEFAULT_PREFIX.to_owned();
+        };
+
+        let prefix = self
+            .guild_prefixes
+            .get_or_else(&guild_id, || async {
+                db::get_guild_setting(&self.database, guild_id, GUILD_PREFIX)
+                    .await
+                    .map(|setting| setting.map_or_else(|| DEFAULT_PREFIX.to_owned(), |s| s.value))
+            })
+            .await;
+
+        match prefix {
+            Ok(prefix) => prefix.to_string(),
+            Err(e) => {
+                error!("Unable to look up the command prefix for guild {}: {}", guild_id, e);
+                DEFAULT_PREFIX.to_owned()
+            },
+        }
+    }
+
+    /// Get the emojis which can be used to delete Titi's messages in the given guild. Returns an empty list if
+    /// deleting messages by reaction has been disabled.
+    async fn guild_delete_emojis(&self, guild_id: Option<GuildId>) -> Arc<Vec<String>> {
+        let default = || DELETE_EMOJI.iter().map(|&e| e.to_owned()).collect::<Vec<_>>();
+        let Some(guild_id) = guild_id else {
+            return Arc::new(default());
+        };
+
+        let emojis = self
+            .delete_emojis
+            .get_or_else(&guild_id, || async {
+                let enabled = db::get_guild_setting(&self.database, guild_id, GUILD_DELETE_BY_REACTION)
+                    .await?
+                    .is_none_or(|s| s.value.parse().unwrap_or(true));
+                if !enabled {
+                    return Ok(Vec::new());
+                }
+
+                db::get_guild_setting(&self.database, guild_id, GUILD_DELETE_EMOJI)
+                    .await
+                    .map(|s| s.map_or_else(default, |s| s.value.split_whitespace().map(str::to_owned).collect()))
+            })
+            .await;
+
+        match emojis {
+            Ok(emojis) => emojis,
+            Err(e) => {
+                error!("Unable to look up the delete emojis for guild {}: {}", guild_id, e);
+                Arc::new(default())
+            },
+        }
+    }
+
     /// Retrieve the full list of tracked threads from the database to populate the in-memory
     /// list of tracked threads.
     async fn update_tracked_threads(&self) -> sqlx::Result<()> {
-        let mut tracked_threads = self.tracked_threads.write().await;
-        tracked_threads.clear();
+        let current: HashSet<ChannelId> = threads::enumerate_tracked_channel_ids(&self.database).await?.collect();
 
-        threads::enumerate_tracked_channel_ids(&self.database).await?.for_each(|id| {
-            tracked_threads.insert(id);
-        });
+        // Update the existing set in place rather than clearing it, so lookups never see an empty list.
+        let mut tracked_threads = self.tracked_threads.write().await;
+        tracked_threads.retain(|id| current.contains(id));
+        tracked_threads.extend(current);
 
         Ok(())
     }
@@ -102,11 +239,9 @@ impl Data {
     /// list of tracked threads. The thread will only be removed from the list if it is no longer
     /// being tracked by any users.
     async fn remove_tracked_thread(&self, channel_id: ChannelId) -> sqlx::Result<()> {
-        let still_tracked = threads::enumerate_tracked_channel_ids(&self.database)
-            .await?
-            .any(|id| id == channel_id);
+        let trackers = db::count_trackers_for_channel(&self.database, channel_id.get()).await?;
 
-        if !still_tracked {
+        if trackers == 0 {
             self.tracked_threads.write().await.remove(&channel_id);
         }
 
@@ -131,6 +266,8 @@ struct Handler {
     user_id: AtomicU64,
     /// The root sender for the background task message queue
     channel: Sender<Task>,
+    /// Signalled when the bot is shutting down, to stop periodic tasks
+    shutdown: watch::Receiver<bool>,
 }
 
 impl Handler {
@@ -138,12 +275,22 @@ impl Handler {
     fn new(
         options: poise::FrameworkOptions<Data, CommandError>,
         database: Database,
+        message_cache_lifetime: Duration,
+        max_watcher_update_tasks: usize,
+        bug_report_user: UserId,
         channel: Sender<Task>,
+        shutdown: watch::Receiver<bool>,
     ) -> Self {
         Self {
             options,
             channel,
-            data: Arc::new(RwLock::new(Data::new(database))),
+            shutdown,
+            data: Arc::new(RwLock::new(Data::new(
+                database,
+                message_cache_lifetime,
+                max_watcher_update_tasks,
+                bug_report_user,
+            ))),
             shard_manager: Mutex::new(None),
             user_id: AtomicU64::new(0),
         }
@@ -192,7 +339,9 @@ impl EventHandler for Handler {
 
         debug!("Received reaction {} on message {}", reaction.emoji, reaction.message_id);
 
-        if DELETE_EMOJI.iter().any(|&emoji| reaction.emoji.unicode_eq(emoji)) {
+        let delete_emojis = self.data.read().await.guild_delete_emojis(reaction.guild_id).await;
+        let emoji = reaction.emoji.to_string();
+        if delete_emojis.iter().any(|e| reaction.emoji.unicode_eq(e) || *e == emoji) {
             let channel_message = (reaction.channel_id, reaction.message_id).into();
             let data = self.data.read().await;
             if let Ok(message) = data
@@ -243,7 +392,8 @@ impl EventHandler for Handler {
             return;
         }
 
-        if !message_is_command(&message.content) {
+        let prefix = self.data.read().await.guild_prefix(message.guild_id).await;
+        if !message_is_command(&message.content, &prefix) {
             let is_tracking_thread =
                 { self.data.read().await.tracking_thread(message.channel_id).await };
 
@@ -255,7 +405,7 @@ impl EventHandler for Handler {
                     .await;
 
                 // Send notification task to background task runner.
-                if let Err(e) = self.channel.send(Task::Notify(message.clone())).await {
+                if let Err(e) = self.channel.send(Task::Notify(Box::new(message.clone()))).await {
                     error!(
                         "Error sending reply notifications due to internal communication error: {}",
                         e
@@ -278,13 +428,34 @@ impl EventHandler for Handler {
             .await;
     }
 
+    async fn thread_create(&self, ctx: Context, thread: GuildChannel) {
+        self.data.read().await.active_threads_cache.remove(&thread.guild_id).await;
+        self.forward_to_poise(&ctx, FullEvent::ThreadCreate { thread }).await;
+    }
+
+    async fn thread_update(&self, ctx: Context, old: Option<GuildChannel>, new: GuildChannel) {
+        self.data.read().await.active_threads_cache.remove(&new.guild_id).await;
+        self.data.read().await.unavailable_channels.remove(&new.id).await;
+        self.forward_to_poise(&ctx, FullEvent::ThreadUpdate { old, new }).await;
+    }
+
+    async fn thread_delete(
+        &self,
+        ctx: Context,
+        thread: PartialGuildChannel,
+        full_thread_data: Option<GuildChannel>,
+    ) {
+        self.data.read().await.active_threads_cache.remove(&thread.guild_id).await;
+        self.forward_to_poise(&ctx, FullEvent::ThreadDelete { thread, full_thread_data }).await;
+    }
+
     async fn guild_create(&self, ctx: Context, guild: Guild, is_new: Option<bool>) {
         if let Some(true) = is_new {
             info!("notified that Titi was added to a new guild: `{}` ({})!", guild.name, guild.id);
             self.data.read().await.guild_count.fetch_add(1, Ordering::SeqCst);
 
             if cfg!(debug_assertions) {
-                utils::register_guild_commands(&self.options.commands, guild.id, &ctx).await;
+                utils::register_guild_commands(&self.options.commands, guild.id, &ctx).await.ok();
             }
         }
 
@@ -298,14 +469,17 @@ impl EventHandler for Handler {
         guild_full: Option<Guild>,
     ) {
         if !guild_partial.unavailable {
-            let guild_name =
-                guild_full.as_ref().map(|g| g.name.clone()).unwrap_or_else(|| format!("{}", guild_partial.id));
+            let data = self.data.read().await;
+            let guild_name = match guild_full.as_ref() {
+                Some(guild) => guild.name.clone(),
+                None => utils::get_guild_display_name(&data.database, None, guild_partial.id, &ctx).await,
+            };
             info!(
                 "notified that Titi has been removed from the `{}` guild ({})",
                 guild_name, guild_partial.id
             );
 
-            self.data.read().await.guild_count.fetch_sub(1, Ordering::SeqCst);
+            data.guild_count.fetch_sub(1, Ordering::SeqCst);
         }
 
         self.forward_to_poise(&ctx, FullEvent::GuildDelete { incomplete: guild_partial, full: guild_full }).await;
@@ -331,12 +505,23 @@ impl EventHandler for Handler {
             Err(e) => error!("Unable to register commands globally: {}", e),
         }
 
-        run_periodic_shard_tasks(&ctx, &self.channel);
+        run_periodic_shard_tasks(&ctx, &self.channel, &self.shutdown);
 
         self.forward_to_poise(&ctx, FullEvent::Ready { data_about_bot: ready }).await;
     }
 
     async fn interaction_create(&self, ctx: Context, interaction: Interaction) {
+        if let Interaction::Component(component) = &interaction {
+            if component.data.custom_id.starts_with(TRACK_THREAD_BUTTON_ID) {
+                let data = self.data.read().await;
+                if let Err(e) = threads::track_from_notification(&ctx, component, &data).await {
+                    error!("Unable to track thread from notification button: {}", e);
+                }
+
+                return;
+            }
+        }
+
         self.forward_to_poise(&ctx, FullEvent::InteractionCreate { interaction }).await;
     }
 }
@@ -379,26 +564,42 @@ async fn main() -> anyhow::Result<()> {
     let discord_token = configuration[token_entry].as_str().unwrap();
     let connection_string = configuration[db_entry].as_str().unwrap();
 
+    // Optional cache tuning, in seconds
+    let cache_lifetime = configured_duration(&configuration, "CACHE_LIFETIME_SECS", CACHE_LIFETIME);
+    let cache_trim_interval = configured_duration(&configuration, "CACHE_TRIM_INTERVAL_SECS", CACHE_TRIM_INTERVAL);
+    let max_watcher_update_tasks =
+        configured_count(&configuration, "MAX_WATCHER_UPDATE_TASKS", MAX_WATCHER_UPDATE_TASKS);
+
+    // Optional number of recent messages to check for a thread's last reply
+    threads::set_last_reply_lookback(configured_count(
+        &configuration,
+        "LAST_REPLY_LOOKBACK",
+        LAST_REPLY_LOOKBACK as usize,
+    ));
+
+    // Optional user to send bug reports to, for anyone running their own copy of the bot
+    let bug_report_user = configured_user(&configuration, "BUG_REPORT_USER_ID", UserId::new(DEBUG_USER));
+
     let options = connection_string
         .parse::<PgConnectOptions>()?
         .log_statements(LevelFilter::Trace)
         .log_slow_statements(LevelFilter::Warn, Duration::from_secs(5));
     let database = PgPoolOptions::new()
-        .max_connections(20)
+        .max_connections(MAX_DATABASE_CONNECTIONS)
         .connect_with(options)
         .await?;
 
-    // Run the schema migration
-    database
-        .execute(include_str!("../sql/schema.sql"))
-        .await?;
+    // Apply any pending schema migrations
+    db::run_migrations(&database).await?;
 
     // FrameworkOptions contains all of poise's configuration option in one struct
     // Every option can be omitted to use its default value
     let options = poise::FrameworkOptions {
         commands: commands::list(),
         prefix_options: poise::PrefixFrameworkOptions {
-            prefix: Some("tt!".into()),
+            dynamic_prefix: Some(|ctx| {
+                Box::pin(async move { Ok(Some(ctx.data.guild_prefix(ctx.guild_id).await)) })
+            }),
             edit_tracker: Some(Arc::new(poise::EditTracker::for_timespan(Duration::from_secs(3600)))),
             mention_as_prefix: true,
             ..Default::default()
@@ -409,12 +610,21 @@ async fn main() -> anyhow::Result<()> {
         pre_command: |ctx| {
             Box::pin(async move {
                 info!("Executing command {}...", ctx.invoked_command_name());
+                ctx.set_invocation_data(Instant::now()).await;
             })
         },
         // This code is run after a command if it was successful (returned Ok)
         post_command: |ctx| {
             Box::pin(async move {
-                info!("Execution of {} completed", ctx.invoked_command_name());
+                let start_time = ctx.invocation_data::<Instant>().await.map(|start| *start);
+                let command = ctx.invoked_command_name();
+                match start_time {
+                    Some(start) => {
+                        let elapsed_ms = start.elapsed().as_millis() as u64;
+                        info!(command, elapsed_ms, "Execution of {} completed in {} ms", command, elapsed_ms);
+                    },
+                    None => info!("Execution of {} completed", command),
+                }
             })
         },
         // Enforce command checks even for owners (enforced by default)
@@ -425,8 +635,18 @@ async fn main() -> anyhow::Result<()> {
 
     // Setup the MPSC channel for sending off background tasks
     let (sender, receiver) = mpsc::channel(MPSC_BUFFER_SIZE);
-
-    let mut handler = Handler::new(options, database, sender);
+    let (shutdown_sender, shutdown_receiver) = watch::channel(false);
+
+    let pool = database.clone();
+    let mut handler = Handler::new(
+        options,
+        database,
+        cache_lifetime,
+        max_watcher_update_tasks,
+        bug_report_user,
+        sender,
+        shutdown_receiver.clone(),
+    );
 
     poise::set_qualified_names(&mut handler.options.commands);
 
@@ -458,15 +678,113 @@ async fn main() -> anyhow::Result<()> {
             for (id, runner) in runners.iter() {
                 info!("Shard ID {} is {} with a latency of {:?}", id, runner.stage, runner.latency);
             }
+
+            if db::pool_exhausted(&pool) {
+                warn!("Database connection pool is exhausted: {}", db::pool_summary(&pool));
+            }
+            else {
+                info!("Database connection pool: {}", db::pool_summary(&pool));
+            }
         }
     });
 
     *handler.shard_manager.lock().unwrap() = Some(client.shard_manager.clone());
 
-    listen_for_background_tasks(receiver, handler.data.clone(), client.http.clone());
-    start_periodic_tasks(&handler.channel);
+    let background_tasks =
+        listen_for_background_tasks(receiver, handler.data.clone(), client.http.clone(), shutdown_receiver.clone());
+    start_periodic_tasks(&handler.channel, &shutdown_receiver, cache_trim_interval);
+
+    let manager = client.shard_manager.clone();
+    tokio::spawn(async move {
+        wait_for_shutdown_signal().await;
+        info!("Shutting down");
+
+        shutdown_sender.send_replace(true);
+        if timeout(SHUTDOWN_TIMEOUT, background_tasks).await.is_err() {
+            warn!("Background tasks did not finish within {:?}", SHUTDOWN_TIMEOUT);
+        }
+
+        manager.shutdown_all().await;
+    });
 
     client.start_autosharded().await.context("Error starting client")?;
 
     Ok(())
 }
+
+/// Read an optional duration in seconds from the configuration, using the default if it's missing or invalid.
+fn configured_duration(configuration: &Table, key: &str, default: Duration) -> Duration {
+    match configuration.get(key).map(|value| value.as_integer()) {
+        None => default,
+        Some(Some(seconds)) if seconds > 0 => Duration::from_secs(seconds as u64),
+        Some(_) => {
+            warn!("Ignoring `{}` in Secrets.toml as it is not a positive whole number of seconds", key);
+            default
+        },
+    }
+}
+
+/// Read an optional positive count from the configuration, using the default if it's missing or invalid.
+fn configured_count(configuration: &Table, key: &str, default: usize) -> usize {
+    match configuration.get(key).map(|value| value.as_integer()) {
+        None => default,
+        Some(Some(count)) if count > 0 => count as usize,
+        Some(_) => {
+            warn!("Ignoring `{}` in Secrets.toml as it is not a positive whole number", key);
+            default
+        },
+    }
+}
+
+/// Read an optional user ID from the configuration, using the default if it's missing or invalid.
+/// IDs may be given as a number or a string of digits.
+fn configured_user(configuration: &Table, key: &str, default: UserId) -> UserId {
+    let Some(value) = configuration.get(key) else {
+        return default;
+    };
+
+    let id = match value {
+        toml::Value::Integer(id) => u64::try_from(*id).ok(),
+        toml::Value::String(id) => id.trim().parse::<u64>().ok(),
+        _ => None,
+    };
+
+    match id {
+        Some(id) if id > 0 => UserId::new(id),
+        _ => {
+            warn!("Ignoring `{}` in Secrets.toml as it is not a valid user ID", key);
+            default
+        },
+    }
+}
+
+/// Wait until the process is asked to stop, either with Ctrl+C or, on Unix, SIGTERM.
+async fn wait_for_shutdown_signal() {
+    let ctrl_c = async {
+        if let Err(e) = tokio::signal::ctrl_c().await {
+            error!("Unable to listen for Ctrl+C: {}", e);
+            std::future::pending::<()>().await;
+        }
+    };
+
+    #[cfg(unix)]
+    let terminate = async {
+        match tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate()) {
+            Ok(mut signal) => {
+                signal.recv().await;
+            },
+            Err(e) => {
+                error!("Unable to listen for SIGTERM: {}", e);
+                std::future::pending::<()>().await;
+            },
+        }
+    };
+
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+
+    tokio::select! {
+        _ = ctrl_c => {},
+        _ = terminate => {},
+    }
+}
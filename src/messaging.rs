@@ -3,7 +3,15 @@ use std::borrow::Cow;
 use anyhow::anyhow;
 use poise::{serenity_prelude::*, CreateReply};
 use serenity::{
-    builder::{CreateEmbed, CreateMessage},
+    builder::{
+        CreateActionRow,
+        CreateAllowedMentions,
+        CreateAttachment,
+        CreateButton,
+        CreateEmbed,
+        CreateInteractionResponse,
+        CreateMessage,
+    },
     http::CacheHttp,
     model::Colour,
     Result,
@@ -16,23 +24,27 @@ use crate::{
     utils,
 };
 
-/// Send the target user a private/direct message.
+/// Send the target user a private/direct message. Anything longer than Discord allows is truncated so that the
+/// message is still delivered.
 pub(crate) async fn dm(
     ctx: impl CacheHttp,
     user_id: UserId,
     message: &str,
     embed_title: Option<&str>,
     embed_description: Option<&str>,
+    components: Vec<CreateActionRow>,
 ) -> Result<()> {
     let channel = user_id.create_dm_channel(&ctx).await?;
 
-    let mut message = CreateMessage::new().content(message);
+    let mut message = CreateMessage::new()
+        .content(utils::truncate_string(message, MAX_MESSAGE_CHARS))
+        .components(components);
 
     match (embed_title, embed_description) {
         (Some(_), _) | (_, Some(_)) => {
             let embed = CreateEmbed::new()
-                .title(embed_title.unwrap_or(""))
-                .description(embed_description.unwrap_or(""))
+                .title(utils::truncate_string(embed_title.unwrap_or(""), MAX_EMBED_TITLE_CHARS))
+                .description(utils::truncate_string(embed_description.unwrap_or(""), MAX_EMBED_CHARS))
                 .colour(Colour::PURPLE);
             message = message.embed(embed);
         },
@@ -80,6 +92,24 @@ pub(crate) async fn reply_error<'a>(
     send_chunked_reply(ctx, title, description, Colour::RED, false).await
 }
 
+/// Send a reply with an embed made up of inline fields, displaying each name and value pair in a grid.
+pub(crate) async fn reply_fields<'a, N, V>(
+    ctx: &CommandContext<'a>,
+    title: &str,
+    fields: impl IntoIterator<Item = (N, V)>,
+) -> Result<poise::ReplyHandle<'a>>
+where
+    N: Into<String>,
+    V: Into<String>,
+{
+    let embed = CreateEmbed::default()
+        .title(title)
+        .fields(fields.into_iter().map(|(name, value)| (name, value, true)))
+        .colour(Colour::PURPLE);
+
+    ctx.send(CreateReply::default().embed(embed)).await
+}
+
 /// Send a reply, divided into chunks if needed, to fit replies into Discord's message limit.
 async fn send_chunked_reply<'a>(
     ctx: &CommandContext<'a>,
@@ -122,6 +152,86 @@ where
     Ok(())
 }
 
+/// Send a plain message to a guild channel, only allowing the given role (if any) to be pinged.
+pub(crate) async fn send_role_mention_message(
+    ctx: impl CacheHttp,
+    channel_id: ChannelId,
+    content: &str,
+    role_id: Option<RoleId>,
+    components: Vec<CreateActionRow>,
+) -> anyhow::Result<()> {
+    let Some(channel) = channel_id.to_channel(&ctx).await?.guild() else {
+        return Err(anyhow!("This method can only be used to send messages to guild channels"));
+    };
+
+    let message = CreateMessage::new()
+        .content(content)
+        .allowed_mentions(CreateAllowedMentions::new().roles(role_id))
+        .components(components);
+    channel.send_message(ctx, message).await?;
+
+    Ok(())
+}
+
+/// The outcome of a confirmation prompt.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum ConfirmationResponse {
+    /// The user pressed the confirm button.
+    Confirmed,
+    /// The user pressed the cancel button.
+    Denied,
+    /// The user didn't respond before the prompt expired.
+    TimedOut,
+}
+
+/// Send a prompt with confirm and cancel buttons, and wait for the given user to respond to it.
+/// The buttons are removed once the user responds or the prompt times out.
+pub(crate) async fn send_confirmation_prompt(
+    ctx: &CommandContext<'_>,
+    title: &str,
+    description: &str,
+    confirming_user: UserId,
+) -> Result<ConfirmationResponse> {
+    let confirm_id = format!("{}-confirm", ctx.id());
+    let cancel_id = format!("{}-cancel", ctx.id());
+
+    let buttons = vec![CreateActionRow::Buttons(vec![
+        CreateButton::new(&confirm_id).label("Confirm").style(ButtonStyle::Success),
+        CreateButton::new(&cancel_id).label("Cancel").style(ButtonStyle::Secondary),
+    ])];
+
+    let embed = CreateEmbed::default().title(title).description(description).colour(Colour::GOLD);
+    let mut prompt = CreateReply::default().embed(embed.clone()).components(buttons);
+    if confirming_user != ctx.author().id {
+        prompt = prompt
+            .content(confirming_user.mention().to_string())
+            .allowed_mentions(CreateAllowedMentions::new().users([confirming_user]));
+    }
+
+    let handle = ctx.send(prompt).await?;
+    let message = handle.message().await?;
+
+    let interaction = ComponentInteractionCollector::new(ctx.serenity_context())
+        .author_id(confirming_user)
+        .message_id(message.id)
+        .timeout(CONFIRMATION_TIMEOUT)
+        .await;
+
+    let response = match &interaction {
+        Some(i) if i.data.custom_id == confirm_id => ConfirmationResponse::Confirmed,
+        Some(_) => ConfirmationResponse::Denied,
+        None => ConfirmationResponse::TimedOut,
+    };
+
+    if let Some(interaction) = interaction {
+        interaction.create_response(ctx, CreateInteractionResponse::Acknowledge).await?;
+    }
+
+    handle.edit(*ctx, CreateReply::default().embed(embed).components(Vec::new())).await?;
+
+    Ok(response)
+}
+
 pub(crate) async fn send_invalid_command_call_error(ctx: CommandContext<'_>) -> CommandResult<()> {
     let result = whisper_error(&ctx, "Invalid command called", "The command you called is not intended to be called directly. This may happen if command registrations have been recently updated. Check for any subcommands or other options when trying to enter the command and use those as well instead of only this base command.").await;
 
@@ -132,58 +242,44 @@ pub(crate) async fn send_invalid_command_call_error(ctx: CommandContext<'_>) ->
     Ok(())
 }
 
-// pub(crate) async fn submit_bug_report(
-//     message: &str,
-//     attachments: &[Attachment],
-//     reporting_user: &User,
-//     message_cache: &MessageCache,
-//     reply_context: &ReplyContext,
-// ) -> anyhow::Result<()> {
-//     if message.trim().is_empty() {
-//         return Ok(());
-//     }
-
-//     let mut report = MessageBuilder::new();
-//     report
-//         .push("__**Bug Report**__ from ")
-//         .push_line(reporting_user.mention())
-//         .push_line("")
-//         .push_line(message);
-
-//     let target_user = UserId(DEBUG_USER);
-
-//     let dm = target_user
-//         .to_user(&reply_context.context)
-//         .await?
-//         .direct_message(&reply_context.context, |msg| {
-//             msg.content(report)
-//                 .add_files(
-//                     attachments
-//                         .iter()
-//                         .filter_map(|a| url::Url::parse(&a.url).ok())
-//                         .map(AttachmentType::Image),
-//                 )
-//                 .embed(|embed| {
-//                     embed
-//                         .title("Reported By")
-//                         .field(
-//                             "User",
-//                             format!("{} #{}", reporting_user.name, reporting_user.discriminator),
-//                             true,
-//                         )
-//                         .field("User ID", reporting_user.id, true)
-//                 })
-//         })
-//         .await?;
-
-//     message_cache.store((dm.channel_id, dm.id).into(), dm).await;
-//     reply_context
-//         .send_success_embed(
-//             "Bug report submitted successfully!",
-//             "Your bug report has been sent.",
-//             message_cache,
-//         )
-//         .await;
-
-//     Ok(())
-// }
+/// Send a bug report to the maintainer by direct message, along with any attachments, and confirm to the reporter.
+pub(crate) async fn submit_bug_report(
+    ctx: &CommandContext<'_>,
+    message: &str,
+    attachments: &[Attachment],
+) -> anyhow::Result<()> {
+    if message.trim().is_empty() {
+        return Err(anyhow!("Bug reports can't be empty."));
+    }
+
+    let reporting_user = ctx.author();
+
+    let mut report = MessageBuilder::new();
+    report
+        .push("__**Bug Report**__ from ")
+        .push_line(reporting_user.mention().to_string())
+        .push_line("")
+        .push_line(message);
+
+    let mut files = Vec::new();
+    for attachment in attachments {
+        files.push(CreateAttachment::url(ctx, &attachment.url).await?);
+    }
+
+    let embed = CreateEmbed::new()
+        .title("Reported By")
+        .field("User", &reporting_user.name, true)
+        .field("User ID", reporting_user.id.to_string(), true)
+        .colour(Colour::PURPLE);
+
+    let dm = ctx
+        .data()
+        .bug_report_user
+        .direct_message(ctx, CreateMessage::new().content(report.build()).add_files(files).embed(embed))
+        .await?;
+
+    ctx.data().message_cache.store((dm.channel_id, dm.id).into(), dm).await;
+    whisper(ctx, "Bug report submitted successfully!", "Your bug report has been sent.").await?;
+
+    Ok(())
+}
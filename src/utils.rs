@@ -1,14 +1,22 @@
-use std::collections::BTreeMap;
+use std::{
+    collections::{BTreeMap, HashSet},
+    future::Future,
+};
 
+use chrono::Utc;
 use serenity::{
     http::{CacheHttp, Http},
     model::prelude::*,
     prelude::*,
     utils::MessageBuilder,
 };
-use tracing::{error, info};
+use tokio::time::sleep;
+use tracing::{error, info, warn};
 
-use crate::db::ThreadWatcher;
+use crate::{
+    consts::{DEFAULT_PREFIX, TRANSIENT_RETRY_ATTEMPTS, TRANSIENT_RETRY_DELAY},
+    db::{self, Database, ThreadWatcher},
+};
 
 /// Wrapper struct for a UserId and GuildId to represent a User in a specific Guild.
 pub(crate) struct GuildUser {
@@ -22,6 +30,63 @@ impl From<&ThreadWatcher> for GuildUser {
     }
 }
 
+/// The IDs referenced by a link to a Discord channel or message, or by a channel mention.
+#[derive(PartialEq, Eq, Debug, Clone, Copy)]
+pub(crate) struct DiscordLink {
+    /// The server the link points to. This is `None` for mentions and links to direct messages.
+    pub guild_id: Option<GuildId>,
+    pub channel_id: ChannelId,
+    pub message_id: Option<MessageId>,
+}
+
+/// Parse a Discord channel or message link (`https://discord.com/channels/<guild>/<channel>[/<message>]`) or a
+/// channel mention into the IDs it refers to. Returns `None` if the text isn't a link or mention.
+pub(crate) fn parse_discord_link(text: &str) -> Option<DiscordLink> {
+    let text = text.trim();
+    // Links wrapped in angle brackets have their embeds suppressed; mentions are always wrapped.
+    let text = text.strip_prefix('<').and_then(|t| t.strip_suffix('>')).unwrap_or(text);
+
+    if let Some(id) = text.strip_prefix('#') {
+        let channel_id = ChannelId::new(parse_snowflake(id)?);
+        return Some(DiscordLink { guild_id: None, channel_id, message_id: None });
+    }
+
+    let path = text.strip_prefix("https://").or_else(|| text.strip_prefix("http://")).unwrap_or(text);
+    let path = path.split(['?', '#']).next().unwrap_or(path);
+    let mut segments = path.trim_end_matches('/').split('/');
+
+    let host = segments.next()?;
+    let host = host.strip_prefix("www.").unwrap_or(host);
+    if !matches!(host, "discord.com" | "discordapp.com" | "canary.discord.com" | "ptb.discord.com") {
+        return None;
+    }
+
+    if segments.next()? != "channels" {
+        return None;
+    }
+
+    let guild_id = match segments.next()? {
+        "@me" => None,
+        id => Some(GuildId::new(parse_snowflake(id)?)),
+    };
+    let channel_id = ChannelId::new(parse_snowflake(segments.next()?)?);
+    let message_id = match segments.next() {
+        Some(id) => Some(MessageId::new(parse_snowflake(id)?)),
+        None => None,
+    };
+
+    if segments.next().is_some() {
+        return None;
+    }
+
+    Some(DiscordLink { guild_id, channel_id, message_id })
+}
+
+/// Parse a Discord ID. IDs are never zero, so zero is rejected along with anything that isn't a number.
+pub(crate) fn parse_snowflake(id: &str) -> Option<u64> {
+    id.trim().parse().ok().filter(|&id| id != 0)
+}
+
 /// Wrapper struct for the MessageId and ChannelId of a Discord message.
 #[derive(PartialEq, Eq, Hash, Debug, Clone, Copy)]
 pub(crate) struct ChannelMessage {
@@ -66,10 +131,15 @@ where
     map
 }
 
-/// If the given string starts with `tt!` or `tt?` (case-insensitive), returns true.
-pub(crate) fn message_is_command(content: &str) -> bool {
-    let prefix: String = content.chars().take(3).flat_map(|c| c.to_lowercase()).collect();
-    prefix == "tt!" || prefix == "tt?"
+/// If the given string starts with the command prefix (case-insensitive), returns true.
+/// When the default `tt!` prefix is in use, `tt?` is also recognised.
+pub(crate) fn message_is_command(content: &str, prefix: &str) -> bool {
+    let starts_with = |prefix: &str| {
+        let start: String = content.chars().take(prefix.chars().count()).flat_map(|c| c.to_lowercase()).collect();
+        start == prefix.to_lowercase()
+    };
+
+    starts_with(prefix) || (prefix == DEFAULT_PREFIX && starts_with("tt?"))
 }
 
 /// Trim the given string to the maximum length in characters.
@@ -83,6 +153,20 @@ pub(crate) fn substring(string: &str, max_length: usize) -> &str {
     }
 }
 
+/// Escape any characters in the given text which would break out of a markdown named link or apply formatting.
+pub(crate) fn escape_link_text(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        if matches!(c, '\\' | '[' | ']' | '(' | ')' | '|' | '`' | '*' | '_' | '~' | '<' | '>') {
+            escaped.push('\\');
+        }
+
+        escaped.push(c);
+    }
+
+    escaped
+}
+
 /// Trim the given string to the max length in characters, appending ellipsis if it was trimmed.
 pub(crate) fn truncate_string(string: &str, max_length: usize) -> String {
     let substring = substring(string, max_length - 1);
@@ -94,6 +178,55 @@ pub(crate) fn truncate_string(string: &str, max_length: usize) -> String {
     }
 }
 
+/// Get the HTTP status code of a failed Discord API request, if the error came from one.
+fn error_status_code(error: &SerenityError) -> Option<u16> {
+    match error {
+        SerenityError::Http(e) => e.status_code().map(|status| status.as_u16()),
+        _ => None,
+    }
+}
+
+/// Returns true if the error is Discord reporting that the requested resource does not exist.
+pub(crate) fn is_not_found(error: &SerenityError) -> bool {
+    error_status_code(error) == Some(404)
+}
+
+/// Returns true if the error is likely to be temporary, such as Discord rate limiting the bot or a server error.
+pub(crate) fn is_transient(error: &SerenityError) -> bool {
+    matches!(error_status_code(error), Some(429 | 500..=599))
+}
+
+/// Run a Discord request, retrying with exponential backoff while it fails with a transient error.
+/// Any other error is returned immediately, as retrying won't help.
+pub(crate) async fn retry_transient<T, F, Fut>(mut request: F) -> Result<T, SerenityError>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<T, SerenityError>>,
+{
+    let mut delay = TRANSIENT_RETRY_DELAY;
+    let mut attempt = 1;
+
+    loop {
+        match request().await {
+            Err(e) if attempt < TRANSIENT_RETRY_ATTEMPTS && is_transient(&e) => {
+                warn!(
+                    "Discord request failed (attempt {} of {}), retrying in {:?}: {}",
+                    attempt, TRANSIENT_RETRY_ATTEMPTS, delay, e
+                );
+                sleep(delay).await;
+                delay *= 2;
+                attempt += 1;
+            },
+            result => return result,
+        }
+    }
+}
+
+/// Returns true if the error is Discord reporting that the bot isn't allowed to access the requested resource.
+pub(crate) fn is_forbidden(error: &SerenityError) -> bool {
+    matches!(error_status_code(error), Some(401) | Some(403))
+}
+
 /// Retrieve the name of a Discord channel as a string. May return None if the channel cannot be accessed.
 pub(crate) async fn get_channel_name(
     channel_id: ChannelId,
@@ -102,6 +235,89 @@ pub(crate) async fn get_channel_name(
     channel_id.to_channel(cache_http.http()).await.map_or(None, |c| c.guild()).map(|gc| gc.name)
 }
 
+/// Check whether the given user can send messages in a channel. Threads are checked against their parent channel's
+/// permissions, as threads don't have permission overwrites of their own.
+pub(crate) async fn can_send_messages(
+    channel: &GuildChannel,
+    user_id: UserId,
+    cache_http: impl CacheHttp,
+) -> Result<bool, SerenityError> {
+    let is_thread = channel.thread_metadata.is_some();
+    let permission_channel = match channel.parent_id {
+        Some(parent_id) if is_thread => match parent_id.to_channel(&cache_http).await?.guild() {
+            Some(parent) => parent,
+            None => return Ok(false),
+        },
+        _ => channel.clone(),
+    };
+
+    let guild = channel.guild_id.to_partial_guild(&cache_http).await?;
+    let member = channel.guild_id.member(&cache_http, user_id).await?;
+    let permissions = guild.user_permissions_in(&permission_channel, &member);
+
+    let can_send = if is_thread {
+        permissions.send_messages_in_threads()
+    }
+    else {
+        permissions.send_messages()
+    };
+
+    Ok(permissions.view_channel() && can_send)
+}
+
+/// Fetch all of the active threads in a server that the bot can see.
+///
+/// Discord may flag the response as incomplete for very large servers. The active threads endpoint has no cursor to
+/// request the next page with, so in that case the response is filled out with the active threads the gateway has
+/// told us about, leaving fewer threads to be looked up one at a time.
+pub(crate) async fn get_active_threads(
+    guild_id: GuildId,
+    cache_http: impl CacheHttp,
+) -> Result<Vec<GuildChannel>, SerenityError> {
+    let response = retry_transient(|| guild_id.get_active_threads(cache_http.http())).await?;
+    let mut threads = response.threads;
+
+    if response.has_more {
+        let mut seen: HashSet<ChannelId> = threads.iter().map(|t| t.id).collect();
+        let cached_threads = cache_http
+            .cache()
+            .and_then(|cache| cache.guild(guild_id).map(|guild| guild.threads.clone()))
+            .unwrap_or_default();
+
+        let fetched = threads.len();
+        threads.extend(cached_threads.into_iter().filter(|t| seen.insert(t.id)));
+        info!(
+            "Active threads for guild {} were truncated at {}, added {} more from the cache",
+            guild_id,
+            fetched,
+            threads.len() - fetched
+        );
+    }
+
+    Ok(threads)
+}
+
+/// Get a display name for a server: the user's own nickname for it if one is set, otherwise the server's name,
+/// falling back to the server ID if the server can't be accessed.
+pub(crate) async fn get_guild_display_name(
+    database: &Database,
+    user_id: Option<UserId>,
+    guild_id: GuildId,
+    cache_http: impl CacheHttp,
+) -> String {
+    if let Some(user_id) = user_id {
+        if let Ok(Some(entry)) = db::get_server_nickname(database, user_id.get(), guild_id.get()).await {
+            return entry.nickname;
+        }
+    }
+
+    guild_id
+        .to_partial_guild(cache_http)
+        .await
+        .map(|guild| guild.name)
+        .unwrap_or_else(|_| guild_id.to_string())
+}
+
 /// Subdivide a string into pieces of a given maximum length. All but the last piece will be the max length.
 pub(crate) fn subdivide_string(s: &str, max_chunk_length: usize) -> Vec<&str> {
     let mut result = Vec::with_capacity(s.len() / max_chunk_length);
@@ -176,28 +392,164 @@ pub(crate) async fn delete_message(
     }
 }
 
-/// Register the provided commands in the given guild.
+/// Register the provided commands in the given guild, replacing any commands previously registered there.
 pub(crate) async fn register_guild_commands<U, E>(
     commands: &[poise::Command<U, E>],
     guild_id: GuildId,
     ctx: &impl AsRef<Http>,
-) {
+) -> Result<(), SerenityError> {
     let commands = poise::builtins::create_application_commands(commands);
     let result = guild_id.set_commands(ctx, commands).await;
 
-    if let Err(e) = result {
+    if let Err(e) = &result {
         error!("Unable to register commands in guild {}: {}", guild_id, e);
     }
+
+    result.map(|_| ())
 }
 
 /// Custom extensions for MessageBuilder.
 pub(crate) trait MessageBuilderExtensions {
     /// Push a Discord-formatted timestamp to the message builder.
     fn push_timestamp(self, timestamp: Timestamp) -> Self;
+
+    /// Push the time elapsed since the given timestamp to the message builder, e.g. `waiting 3d 4h`.
+    fn push_elapsed(self, timestamp: Timestamp) -> Self;
 }
 
 impl MessageBuilderExtensions for &mut MessageBuilder {
     fn push_timestamp(self, timestamp: Timestamp) -> Self {
         self.push(format!("<t:{}:R>", timestamp.unix_timestamp()))
     }
+
+    fn push_elapsed(self, timestamp: Timestamp) -> Self {
+        let seconds = (Utc::now().timestamp() - timestamp.unix_timestamp()).max(0);
+        self.push(format!("waiting {}", format_duration(seconds)))
+    }
+}
+
+/// Format a number of seconds as a short human-readable duration, showing at most the two largest units.
+pub(crate) fn format_duration(seconds: i64) -> String {
+    let days = seconds / 86_400;
+    let hours = seconds % 86_400 / 3_600;
+    let minutes = seconds % 3_600 / 60;
+
+    if days > 0 && hours > 0 {
+        format!("{}d {}h", days, hours)
+    }
+    else if days > 0 {
+        format!("{}d", days)
+    }
+    else if hours > 0 && minutes > 0 {
+        format!("{}h {}m", hours, minutes)
+    }
+    else if hours > 0 {
+        format!("{}h", hours)
+    }
+    else if minutes > 0 {
+        format!("{}m", minutes)
+    }
+    else {
+        "<1m".to_owned()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn link(guild_id: Option<u64>, channel_id: u64, message_id: Option<u64>) -> DiscordLink {
+        DiscordLink {
+            guild_id: guild_id.map(GuildId::new),
+            channel_id: ChannelId::new(channel_id),
+            message_id: message_id.map(MessageId::new),
+        }
+    }
+
+    #[test]
+    fn parse_discord_link_parses_channel_links() {
+        assert_eq!(
+            parse_discord_link("https://discord.com/channels/111/222"),
+            Some(link(Some(111), 222, None))
+        );
+        assert_eq!(
+            parse_discord_link("https://www.discordapp.com/channels/111/222/"),
+            Some(link(Some(111), 222, None))
+        );
+    }
+
+    #[test]
+    fn parse_discord_link_parses_message_links() {
+        assert_eq!(
+            parse_discord_link("https://discord.com/channels/111/222/333"),
+            Some(link(Some(111), 222, Some(333)))
+        );
+        assert_eq!(
+            parse_discord_link("https://canary.discord.com/channels/111/222/333?query=1#fragment"),
+            Some(link(Some(111), 222, Some(333)))
+        );
+    }
+
+    #[test]
+    fn parse_discord_link_parses_mentions() {
+        assert_eq!(parse_discord_link("<#222>"), Some(link(None, 222, None)));
+        assert_eq!(parse_discord_link(" <#222> "), Some(link(None, 222, None)));
+        assert_eq!(parse_discord_link("<#abc>"), None);
+    }
+
+    #[test]
+    fn parse_discord_link_parses_direct_message_links() {
+        assert_eq!(
+            parse_discord_link("https://discord.com/channels/@me/222/333"),
+            Some(link(None, 222, Some(333)))
+        );
+    }
+
+    #[test]
+    fn parse_discord_link_parses_wrapped_links() {
+        assert_eq!(
+            parse_discord_link("<https://discord.com/channels/111/222/333>"),
+            Some(link(Some(111), 222, Some(333)))
+        );
+    }
+
+    #[test]
+    fn parse_discord_link_rejects_trailing_segments() {
+        assert_eq!(parse_discord_link("https://discord.com/channels/111/222/333/444"), None);
+    }
+
+    #[test]
+    fn parse_discord_link_rejects_other_text() {
+        assert_eq!(parse_discord_link("https://example.com/channels/111/222"), None);
+        assert_eq!(parse_discord_link("https://discord.com/invite/abc"), None);
+        assert_eq!(parse_discord_link("https://discord.com/channels/111"), None);
+        assert_eq!(parse_discord_link("https://discord.com/channels/0/222"), None);
+        assert_eq!(parse_discord_link("222"), None);
+    }
+
+    #[test]
+    fn escape_link_text_escapes_brackets() {
+        assert_eq!(escape_link_text("[OOC] Plot (part 2)"), r"\[OOC\] Plot \(part 2\)");
+    }
+
+    #[test]
+    fn escape_link_text_escapes_pipes() {
+        assert_eq!(escape_link_text("a || spoiler || b"), r"a \|\| spoiler \|\| b");
+    }
+
+    #[test]
+    fn escape_link_text_escapes_nested_markdown() {
+        assert_eq!(escape_link_text("**_~~`x`~~_**"), r"\*\*\_\~\~\`x\`\~\~\_\*\*");
+        assert_eq!(escape_link_text("[link](<https://example.com>)"), r"\[link\]\(\<https://example.com\>\)");
+    }
+
+    #[test]
+    fn escape_link_text_escapes_backslashes() {
+        assert_eq!(escape_link_text(r"a\]b"), r"a\\\]b");
+    }
+
+    #[test]
+    fn escape_link_text_leaves_plain_text_alone() {
+        assert_eq!(escape_link_text("Tea at the café, 3pm!"), "Tea at the café, 3pm!");
+    }
 }